@@ -0,0 +1,13 @@
+//! Transient notifications drawn on top of the current frame by an [`AlertQueue`].
+//!
+//! The time is read from a [`Clock`], [`MockClock`] makes the expiry of the alerts
+//! testable without waiting.
+
+mod clock;
+pub use clock::*;
+
+mod pattern;
+pub use pattern::*;
+
+mod queue;
+pub use queue::*;
@@ -0,0 +1,87 @@
+//! Contains the trait [`Clock`], [`SystemClock`] and [`MockClock`]
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Source of the time of an [`super::AlertQueue`]
+pub trait Clock {
+    /// Get the time elapsed since an origin fixed by the clock
+    fn now(&self) -> Duration;
+}
+
+/// [`Clock`] measuring the time since its creation
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{Clock, SystemClock};
+///
+/// let clock = SystemClock::new();
+/// assert!(clock.now() < std::time::Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemClock {
+    /// Origin of the time
+    start: Instant,
+}
+
+impl SystemClock {
+    /// Create a clock starting now
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    /// Start now
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// [`Clock`] only moving when told to, for tests. The clones share the same time.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{Clock, MockClock};
+///
+/// let clock = MockClock::new();
+/// let shared = clock.clone();
+/// clock.advance(Duration::from_secs(2));
+/// assert_eq!(shared.now(), Duration::from_secs(2));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockClock {
+    /// Current time, shared by the clones
+    now: Arc<Mutex<Duration>>,
+}
+
+impl MockClock {
+    /// Create a clock at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the time forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += duration;
+    }
+
+    /// Set the time
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
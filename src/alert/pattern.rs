@@ -0,0 +1,244 @@
+//! Contains [`Alert`] and [`AlertPattern`]
+
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{color::ColorRgb, profile::KeyTarget};
+
+/// Width of the band of [`AlertPattern::Sweep`], relative to the width of the keys targeted
+const SWEEP_WIDTH: f64 = 0.25_f64;
+
+/// How an [`Alert`] is drawn during one repetition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum AlertPattern {
+    /// On during the first half of the repetition, off during the second half
+    Flash,
+    /// Fade in then fade out
+    Pulse,
+    /// A band moving from the left to the right of the keys targeted
+    Sweep,
+}
+
+impl AlertPattern {
+    /// Get the intensity between 0 and 1 of the pattern.
+    ///
+    /// `phase` is the progress of the repetition between 0 and 1 and `x` is the position of
+    /// the key between 0 (left) and 1 (right).
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::AlertPattern;
+    ///
+    /// assert_eq!(AlertPattern::Flash.intensity(0.25_f64, 0_f64), 1_f64);
+    /// assert_eq!(AlertPattern::Flash.intensity(0.75_f64, 0_f64), 0_f64);
+    /// assert_eq!(AlertPattern::Pulse.intensity(0.5_f64, 0_f64), 1_f64);
+    /// ```
+    pub fn intensity(self, phase: f64, x: f64) -> f64 {
+        match self {
+            Self::Flash => {
+                if phase < 0.5_f64 {
+                    1_f64
+                } else {
+                    0_f64
+                }
+            }
+            Self::Pulse => (PI * phase).sin().max(0_f64),
+            Self::Sweep => {
+                // the band enters from the left and fully leaves on the right
+                let center = phase.mul_add(2_f64.mul_add(SWEEP_WIDTH, 1_f64), -SWEEP_WIDTH);
+                (1_f64 - (x - center).abs() / SWEEP_WIDTH).max(0_f64)
+            }
+        }
+    }
+}
+
+impl Display for AlertPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// A transient notification drawn on some keys by an [`super::AlertQueue`].
+///
+/// The pattern is played `repeat` times, each repetition lasting `duration`, then the alert
+/// expires. When several alerts target the same key the one with the highest priority is
+/// drawn, and the most recent one among equal priorities.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{Alert, AlertPattern, ColorRgb, KeyGroup};
+///
+/// let alert = Alert::new(
+///     AlertPattern::Flash,
+///     ColorRgb::new(255, 0, 0),
+///     Duration::from_millis(500),
+/// )
+/// .unwrap()
+/// .with_targets(vec![KeyGroup::FunctionKeys.into()])
+/// .with_priority(10)
+/// .with_repeat(3);
+/// assert_eq!(alert.lifetime(), Duration::from_millis(1500));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// Pattern drawn
+    pattern: AlertPattern,
+    /// Color at full intensity
+    color: ColorRgb,
+    /// Duration of one repetition
+    duration: Duration,
+    /// Number of repetitions
+    repeat: u32,
+    /// Keys drawn, all keys if empty
+    targets: Vec<KeyTarget>,
+    /// Priority over the other alerts
+    priority: i32,
+}
+
+impl Alert {
+    /// Create an alert on all keys, played once with a priority of 0.
+    /// Returns [`None`] if `duration` is zero.
+    pub fn new(pattern: AlertPattern, color: ColorRgb, duration: Duration) -> Option<Self> {
+        if duration == Duration::ZERO {
+            None
+        } else {
+            Some(Self {
+                pattern,
+                color,
+                duration,
+                repeat: 1,
+                targets: Vec::new(),
+                priority: 0,
+            })
+        }
+    }
+
+    /// Set the keys drawn, all keys if `targets` is empty.
+    ///
+    /// The keys that are not in the layout are ignored when drawing.
+    pub fn with_targets(mut self, targets: impl IntoIterator<Item = KeyTarget>) -> Self {
+        self.targets = targets.into_iter().collect();
+        self
+    }
+
+    /// Set the priority, higher priorities are drawn over lower ones
+    pub const fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the number of repetitions of the pattern, at least 1
+    pub fn with_repeat(mut self, repeat: u32) -> Self {
+        self.repeat = repeat.max(1);
+        self
+    }
+
+    /// Get the pattern
+    pub const fn pattern(&self) -> AlertPattern {
+        self.pattern
+    }
+
+    /// Get the color at full intensity
+    pub const fn color(&self) -> ColorRgb {
+        self.color
+    }
+
+    /// Get the duration of one repetition
+    pub const fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Get the number of repetitions
+    pub const fn repeat(&self) -> u32 {
+        self.repeat
+    }
+
+    /// Get the keys drawn, all keys if empty
+    pub fn targets(&self) -> &[KeyTarget] {
+        &self.targets
+    }
+
+    /// Get the priority
+    pub const fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Get the time after which the alert expires
+    pub const fn lifetime(&self) -> Duration {
+        self.duration.saturating_mul(self.repeat)
+    }
+
+    /// Get the progress between 0 and 1 of the current repetition at `age`,
+    /// [`None`] if the alert has expired
+    pub(super) fn phase(&self, age: Duration) -> Option<f64> {
+        if age >= self.lifetime() {
+            None
+        } else {
+            let duration = self.duration.as_secs_f64();
+            Some((age.as_secs_f64() % duration) / duration)
+        }
+    }
+}
+
+impl Display for Alert {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} for {} s, {} time(s) with priority {}",
+            self.pattern,
+            self.color,
+            self.duration.as_secs_f64(),
+            self.repeat,
+            self.priority
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn intensity() {
+        for pattern in [
+            AlertPattern::Flash,
+            AlertPattern::Pulse,
+            AlertPattern::Sweep,
+        ] {
+            for i in 0..=20_u8 {
+                let value = pattern.intensity(f64::from(i) / 20_f64, 0.5_f64);
+                assert!((0_f64..=1_f64).contains(&value));
+            }
+        }
+        assert!(AlertPattern::Pulse.intensity(0_f64, 0_f64) < 1E-12_f64);
+        // the band is outside of the keys at the start and at the end
+        assert_eq!(AlertPattern::Sweep.intensity(0_f64, 0_f64), 0_f64);
+        assert_eq!(AlertPattern::Sweep.intensity(1_f64, 1_f64), 0_f64);
+        assert!((AlertPattern::Sweep.intensity(0.5_f64, 0.5_f64) - 1_f64).abs() < 1E-12_f64);
+        assert!(
+            AlertPattern::Sweep.intensity(0.25_f64, 0.2_f64)
+                > AlertPattern::Sweep.intensity(0.25_f64, 0.8_f64)
+        );
+    }
+
+    #[test]
+    fn alert() {
+        let color = ColorRgb::new(0, 255, 0);
+        assert!(Alert::new(AlertPattern::Pulse, color, Duration::ZERO).is_none());
+        let alert = Alert::new(AlertPattern::Pulse, color, Duration::from_secs(2))
+            .unwrap()
+            .with_repeat(0);
+        assert_eq!(alert.repeat(), 1);
+        assert_eq!(alert.phase(Duration::from_secs(1)), Some(0.5_f64));
+        assert_eq!(alert.phase(Duration::from_secs(2)), None);
+        let alert = alert.with_repeat(2);
+        assert_eq!(alert.phase(Duration::from_secs(3)), Some(0.5_f64));
+        assert_eq!(alert.lifetime(), Duration::from_secs(4));
+    }
+}
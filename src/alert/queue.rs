@@ -0,0 +1,372 @@
+//! Contains [`AlertQueue`] and [`AlertId`]
+
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use super::{Alert, Clock, SystemClock};
+use crate::{
+    color::{ColorBuffer, ColorRgb, ColorSpace, NUMBER_KEY_LED_BUFFER},
+    effect::Effect,
+    layout::Layout,
+};
+
+/// Identifier of an alert pushed on an [`AlertQueue`], used to cancel it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AlertId(u64);
+
+impl Display for AlertId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// An alert pushed on the queue
+#[derive(Debug, Clone)]
+struct ActiveAlert {
+    /// Identifier, increasing with the time of the push
+    id: AlertId,
+    /// Alert drawn
+    alert: Alert,
+    /// Time of the push given by the clock
+    start: Duration,
+}
+
+impl ActiveAlert {
+    /// Check whether the alert is still drawn at `now`
+    fn is_active(&self, now: Duration) -> bool {
+        self.alert.phase(now.saturating_sub(self.start)).is_some()
+    }
+}
+
+/// State shared by the clones of an [`AlertQueue`]
+#[derive(Debug, Default)]
+struct QueueState {
+    /// Identifier of the next alert
+    next_id: u64,
+    /// Alerts not yet expired, in the order of the push
+    alerts: Vec<ActiveAlert>,
+}
+
+/// Queue of transient [`Alert`]s drawn on top of the current frame.
+///
+/// The clones share the same alerts, so that one clone can be pushed on a
+/// [`crate::ProfileRenderer`] while another one receives the notifications.
+/// The alerts expire on their own according to the [`Clock`] of the queue.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     Alert, AlertPattern, AlertQueue, ColorBuffer, ColorRgb, KeyName, LayoutFrCh, MockClock,
+/// };
+///
+/// let clock = MockClock::new();
+/// let queue = AlertQueue::with_clock(clock.clone());
+/// let red = ColorRgb::new(255, 0, 0);
+/// queue.push(
+///     Alert::new(AlertPattern::Flash, red, Duration::from_secs(1))
+///         .unwrap()
+///         .with_targets(vec![KeyName::Escape.into()]),
+/// );
+///
+/// let layout = LayoutFrCh::new();
+/// let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+/// queue.overlay(&mut buffer, &layout);
+/// assert_eq!(buffer.get_key(&layout, KeyName::Escape), Some(&red));
+///
+/// clock.advance(Duration::from_secs(1));
+/// queue.overlay(&mut buffer, &layout);
+/// assert!(queue.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct AlertQueue<C = SystemClock> {
+    /// Source of the time
+    clock: C,
+    /// Alerts shared by the clones
+    state: Arc<Mutex<QueueState>>,
+}
+
+impl AlertQueue<SystemClock> {
+    /// Create an empty queue using the time of the system
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::new())
+    }
+}
+
+impl Default for AlertQueue<SystemClock> {
+    /// Empty queue using the time of the system
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> AlertQueue<C> {
+    /// Create an empty queue using `clock`
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            clock,
+            state: Arc::default(),
+        }
+    }
+
+    /// Get the clock
+    pub const fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Lock the shared state, a panic of another thread does not leave it inconsistent
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Start drawing `alert` now
+    pub fn push(&self, alert: Alert) -> AlertId {
+        let start = self.clock.now();
+        let mut state = self.state();
+        let id = AlertId(state.next_id);
+        state.next_id += 1;
+        state.alerts.push(ActiveAlert { id, alert, start });
+        id
+    }
+
+    /// Remove an alert before it expires, returns `false` if it is not in the queue
+    pub fn cancel(&self, id: AlertId) -> bool {
+        let mut state = self.state();
+        let len = state.alerts.len();
+        state.alerts.retain(|active| active.id != id);
+        state.alerts.len() != len
+    }
+
+    /// Remove all the alerts
+    pub fn clear(&self) {
+        self.state().alerts.clear();
+    }
+
+    /// Get the number of alerts not yet expired
+    pub fn len(&self) -> usize {
+        let now = self.clock.now();
+        self.state()
+            .alerts
+            .iter()
+            .filter(|active| active.is_active(now))
+            .count()
+    }
+
+    /// Check whether there is no alert left
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Draw the alerts on top of `buffer` and drop the expired ones.
+    ///
+    /// Each key shows the alert with the highest priority targeting it, even while
+    /// the pattern of that alert is dark.
+    pub fn overlay(&self, buffer: &mut ColorBuffer<ColorRgb>, layout: &dyn Layout) {
+        let now = self.clock.now();
+        let mut state = self.state();
+        state.alerts.retain(|active| active.is_active(now));
+
+        let mut alerts: Vec<&ActiveAlert> = state.alerts.iter().collect();
+        alerts.sort_by_key(|active| (active.alert.priority(), active.id));
+
+        // for each light the alert drawn and the position of the key in its targets
+        let mut owners: Vec<Option<(&ActiveAlert, f64)>> = vec![None; NUMBER_KEY_LED_BUFFER];
+        for active in alerts {
+            let keys = target_positions(&active.alert, layout);
+            let (min, max) = keys
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (_, x)| {
+                    (min.min(*x), max.max(*x))
+                });
+            for (index, x) in keys {
+                let position = if max > min {
+                    (x - min) / (max - min)
+                } else {
+                    0.5_f64
+                };
+                owners[index] = Some((active, position));
+            }
+        }
+
+        for (color, owner) in buffer.iter_mut().zip(owners) {
+            if let Some((active, position)) = owner {
+                let age = now.saturating_sub(active.start);
+                if let Some(phase) = active.alert.phase(age) {
+                    let intensity = active.alert.pattern().intensity(phase, position);
+                    *color = color.lerp(active.alert.color(), intensity, ColorSpace::Rgb);
+                }
+            }
+        }
+    }
+}
+
+/// Get the indices of the lights targeted by `alert` with the horizontal positions of the keys
+fn target_positions(alert: &Alert, layout: &dyn Layout) -> Vec<(usize, f64)> {
+    let mut targeted = [alert.targets().is_empty(); NUMBER_KEY_LED_BUFFER];
+    for target in alert.targets() {
+        // the keys missing from the layout are ignored
+        for index in target.light_indices(layout).unwrap_or_default() {
+            targeted[index] = true;
+        }
+    }
+    layout
+        .layout()
+        .iter()
+        .map(|key| (usize::from(key.key_code_light().code()), key.key_pos().x()))
+        .filter(|(index, _)| targeted.get(*index).copied().unwrap_or(false))
+        .collect()
+}
+
+impl<C: Clock> Effect for AlertQueue<C> {
+    /// Draw the alerts, the time is given by the clock of the queue
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        _elapsed: Duration,
+    ) {
+        self.overlay(buffer, layout);
+    }
+}
+
+impl<C: Clock> Display for AlertQueue<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} alert(s)", self.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        alert::{AlertPattern, MockClock},
+        layout::{KeyGroup, KeyName, LayoutFrCh, LayoutKind},
+        profile::{KeyTarget, ProfileRenderer},
+    };
+
+    const BLACK: ColorRgb = ColorRgb::new(0, 0, 0);
+    const RED: ColorRgb = ColorRgb::new(255, 0, 0);
+    const BLUE: ColorRgb = ColorRgb::new(0, 0, 255);
+
+    fn flash(color: ColorRgb, target: KeyTarget) -> Alert {
+        Alert::new(AlertPattern::Flash, color, Duration::from_secs(1))
+            .unwrap()
+            .with_targets(vec![target])
+    }
+
+    fn draw(queue: &AlertQueue<MockClock>, layout: &dyn Layout) -> ColorBuffer<ColorRgb> {
+        let mut buffer = ColorBuffer::from_element(BLACK);
+        queue.overlay(&mut buffer, layout);
+        buffer
+    }
+
+    #[test]
+    fn expiry_and_repeat() {
+        let layout = LayoutFrCh::new();
+        let clock = MockClock::new();
+        let queue = AlertQueue::with_clock(clock.clone());
+        queue.push(flash(RED, KeyName::Escape.into()).with_repeat(2));
+        assert_eq!(queue.len(), 1);
+        let key =
+            |buffer: &ColorBuffer<ColorRgb>| *buffer.get_key(&layout, KeyName::Escape).unwrap();
+
+        assert_eq!(key(&draw(&queue, &layout)), RED);
+        clock.set(Duration::from_millis(750));
+        assert_eq!(key(&draw(&queue, &layout)), BLACK);
+        // second repetition
+        clock.set(Duration::from_millis(1250));
+        assert_eq!(key(&draw(&queue, &layout)), RED);
+        // other keys are untouched
+        assert_eq!(
+            *draw(&queue, &layout).get_key(&layout, KeyName::F1).unwrap(),
+            BLACK
+        );
+        clock.set(Duration::from_secs(2));
+        assert_eq!(key(&draw(&queue, &layout)), BLACK);
+        assert!(queue.is_empty());
+        assert!(queue.state().alerts.is_empty());
+    }
+
+    #[test]
+    fn priority() {
+        let layout = LayoutFrCh::new();
+        let clock = MockClock::new();
+        let queue = AlertQueue::with_clock(clock.clone());
+        let high = queue.push(flash(RED, KeyGroup::FunctionKeys.into()).with_priority(1));
+        let low = queue.push(flash(BLUE, KeyName::F1.into()));
+        queue.push(flash(BLUE, KeyName::Escape.into()));
+
+        let buffer = draw(&queue, &layout);
+        assert_eq!(*buffer.get_key(&layout, KeyName::F1).unwrap(), RED);
+        assert_eq!(*buffer.get_key(&layout, KeyName::Escape).unwrap(), BLUE);
+
+        // equal priorities: the newest wins
+        let newest = queue.push(flash(RED, KeyName::Escape.into()));
+        let buffer = draw(&queue, &layout);
+        assert_eq!(*buffer.get_key(&layout, KeyName::Escape).unwrap(), RED);
+
+        assert!(queue.cancel(newest));
+        assert!(!queue.cancel(newest));
+        assert!(queue.cancel(high));
+        let buffer = draw(&queue, &layout);
+        assert_eq!(*buffer.get_key(&layout, KeyName::F1).unwrap(), BLUE);
+        assert_eq!(*buffer.get_key(&layout, KeyName::Escape).unwrap(), BLUE);
+        assert!(queue.cancel(low));
+        queue.clear();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn patterns() {
+        let layout = LayoutFrCh::new();
+        let clock = MockClock::new();
+        let queue = AlertQueue::with_clock(clock.clone());
+        queue.push(
+            Alert::new(AlertPattern::Pulse, RED, Duration::from_secs(2))
+                .unwrap()
+                .with_targets(vec![KeyName::Escape.into()]),
+        );
+        clock.set(Duration::from_millis(1));
+        assert_ne!(
+            *draw(&queue, &layout)
+                .get_key(&layout, KeyName::Escape)
+                .unwrap(),
+            RED
+        );
+        clock.set(Duration::from_secs(1));
+        assert_eq!(
+            *draw(&queue, &layout)
+                .get_key(&layout, KeyName::Escape)
+                .unwrap(),
+            RED
+        );
+
+        queue.clear();
+        clock.set(Duration::ZERO);
+        queue.push(Alert::new(AlertPattern::Sweep, RED, Duration::from_secs(1)).unwrap());
+        clock.set(Duration::from_millis(300));
+        let buffer = draw(&queue, &layout);
+        let escape = buffer.get_key(&layout, KeyName::Escape).unwrap().r();
+        let enter = buffer.get_key(&layout, KeyName::NumEnter).unwrap().r();
+        assert!(escape > enter);
+        clock.set(Duration::from_millis(700));
+        let buffer = draw(&queue, &layout);
+        let escape = buffer.get_key(&layout, KeyName::Escape).unwrap().r();
+        let enter = buffer.get_key(&layout, KeyName::NumEnter).unwrap().r();
+        assert!(escape < enter);
+    }
+
+    #[test]
+    fn effect() {
+        let layout = LayoutKind::FrCh.layout();
+        let clock = MockClock::new();
+        let queue = AlertQueue::with_clock(clock);
+        let mut renderer = ProfileRenderer::new(layout, ColorBuffer::from_element(BLACK));
+        renderer.push_effect(queue.clone(), None);
+        queue.push(flash(RED, KeyName::Escape.into()));
+        let frame = renderer.frame(Duration::from_secs(100));
+        assert_eq!(frame.get_key(layout, KeyName::Escape), Some(&RED));
+        assert_eq!(queue.to_string(), "1 alert(s)");
+    }
+}
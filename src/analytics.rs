@@ -0,0 +1,12 @@
+//! Typing statistics accumulated from the [`crate::KeyPress`] events by [`TypingStats`].
+//!
+//! The statistics can be drawn as a heatmap with [`TypingStats::heatmap`], exported
+//! to CSV and, with the feature `serde-serialize`, saved in a JSON file between sessions.
+
+#[cfg(feature = "serde-serialize")]
+mod error;
+#[cfg(feature = "serde-serialize")]
+pub use error::*;
+
+mod stats;
+pub use stats::*;
@@ -0,0 +1,56 @@
+//! Contains [`StatsError`]
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Error returned when loading or saving [`super::TypingStats`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StatsError {
+    /// Error while reading or writing the file
+    Io(std::io::Error),
+    /// The file is not valid JSON or does not match the format
+    Json(serde_json::Error),
+    /// The field `version` is missing
+    MissingVersion,
+    /// The version of the file is not supported
+    UnsupportedVersion(u64),
+}
+
+impl Display for StatsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "io error : {}", error),
+            Self::Json(error) => write!(f, "invalid statistics : {}", error),
+            Self::MissingVersion => write!(f, "the statistics have no version"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported statistics version {}, the latest supported is {}",
+                version,
+                super::STATS_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for StatsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Json(error) => Some(error),
+            Self::MissingVersion | Self::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for StatsError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for StatsError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
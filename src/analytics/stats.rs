@@ -0,0 +1,598 @@
+//! Contains [`TypingStats`] and [`KeyStats`]
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+#[cfg(feature = "serde-serialize")]
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde-serialize")]
+use super::StatsError;
+use crate::{
+    color::{ColorBuffer, ColorRgb, Gradient, NUMBER_KEY_LED_BUFFER},
+    layout::{KeyGroup, KeyName, KeyPress, Layout},
+};
+
+/// Version of the statistics format written by this version of the library
+pub const STATS_VERSION: u64 = 1;
+
+/// Default duration over which the words per minute are computed
+const DEFAULT_WPM_WINDOW: Duration = Duration::from_secs(60);
+
+/// Default pause after which the time between two presses is not counted as an interval
+const DEFAULT_IDLE: Duration = Duration::from_secs(2);
+
+/// Number of characters in a word for the words per minute
+const CHARACTERS_PER_WORD: f64 = 5_f64;
+
+/// Statistics of one key accumulated by [`TypingStats`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(deny_unknown_fields))]
+pub struct KeyStats {
+    /// Key counted
+    key: KeyName,
+    /// Number of presses
+    presses: u64,
+    /// Sum of the hold durations in seconds
+    hold_time: f64,
+    /// Number of releases whose press was seen
+    holds: u64,
+    /// Sum of the intervals since the previous press of any key in seconds
+    interval_time: f64,
+    /// Number of intervals counted
+    intervals: u64,
+}
+
+impl KeyStats {
+    /// Create empty statistics for `key`
+    const fn new(key: KeyName) -> Self {
+        Self {
+            key,
+            presses: 0,
+            hold_time: 0_f64,
+            holds: 0,
+            interval_time: 0_f64,
+            intervals: 0,
+        }
+    }
+
+    /// Get the key
+    pub const fn key(&self) -> KeyName {
+        self.key
+    }
+
+    /// Get the number of presses
+    pub const fn presses(&self) -> u64 {
+        self.presses
+    }
+
+    /// Get the total time the key was held
+    pub fn total_hold(&self) -> Duration {
+        Duration::try_from_secs_f64(self.hold_time).unwrap_or_default()
+    }
+
+    /// Get the mean time the key was held, [`None`] if it was never released
+    pub fn mean_hold(&self) -> Option<Duration> {
+        mean(self.hold_time, self.holds)
+    }
+
+    /// Get the mean time between the previous press of any key and a press of this key,
+    /// [`None`] if there is no interval. The pauses longer than [`TypingStats::idle`]
+    /// are not counted.
+    pub fn mean_interval(&self) -> Option<Duration> {
+        mean(self.interval_time, self.intervals)
+    }
+}
+
+/// Get the mean duration of `count` durations summing up to `total` seconds
+fn mean(total: f64, count: u64) -> Option<Duration> {
+    if count == 0 {
+        None
+    } else {
+        Duration::try_from_secs_f64(total / count as f64).ok()
+    }
+}
+
+/// Check whether a press of the key types a character, for the words per minute
+fn is_character(key: KeyName) -> bool {
+    !matches!(key, KeyName::Unbound | KeyName::Escape)
+        && ![
+            KeyGroup::FunctionKeys,
+            KeyGroup::Modifiers,
+            KeyGroup::Navigation,
+            KeyGroup::Arrows,
+            KeyGroup::System,
+            KeyGroup::Wheel,
+        ]
+        .iter()
+        .any(|group| group.contains(key))
+}
+
+/// Per key press counts, hold durations and intervals, and rolling words per minute.
+///
+/// The events are given with the time since the start of the recording as `elapsed`.
+/// Only the per key statistics are saved, the keys held and the words per minute
+/// start again with each session.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{KeyName, TypingStats};
+///
+/// let mut stats = TypingStats::new();
+/// stats.record_key(KeyName::A, true, Duration::from_millis(0));
+/// stats.record_key(KeyName::A, false, Duration::from_millis(100));
+/// stats.record_key(KeyName::B, true, Duration::from_millis(300));
+///
+/// let a = stats.key(KeyName::A).unwrap();
+/// assert_eq!(a.presses(), 1);
+/// assert_eq!(a.mean_hold(), Some(Duration::from_millis(100)));
+/// let b = stats.key(KeyName::B).unwrap();
+/// assert_eq!(b.mean_interval(), Some(Duration::from_millis(300)));
+/// assert_eq!(stats.total_presses(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypingStats {
+    /// Statistics of the keys pressed at least once
+    keys: BTreeMap<KeyName, KeyStats>,
+    /// Keys currently held with the time of their press
+    held: BTreeMap<KeyName, Duration>,
+    /// Time of the last press of any key
+    last_press: Option<Duration>,
+    /// Times of the characters typed during the last window
+    characters: VecDeque<Duration>,
+    /// Duration over which the words per minute are computed
+    wpm_window: Duration,
+    /// Pause after which an interval is not counted
+    idle: Duration,
+}
+
+impl TypingStats {
+    /// Create empty statistics
+    pub const fn new() -> Self {
+        Self {
+            keys: BTreeMap::new(),
+            held: BTreeMap::new(),
+            last_press: None,
+            characters: VecDeque::new(),
+            wpm_window: DEFAULT_WPM_WINDOW,
+            idle: DEFAULT_IDLE,
+        }
+    }
+
+    /// Set the duration over which the words per minute are computed, one minute by default.
+    /// A window of zero gives zero words per minute.
+    pub const fn with_wpm_window(mut self, wpm_window: Duration) -> Self {
+        self.wpm_window = wpm_window;
+        self
+    }
+
+    /// Set the pause after which the time between two presses is not counted as an
+    /// interval, two seconds by default
+    pub const fn with_idle(mut self, idle: Duration) -> Self {
+        self.idle = idle;
+        self
+    }
+
+    /// Get the duration over which the words per minute are computed
+    pub const fn wpm_window(&self) -> Duration {
+        self.wpm_window
+    }
+
+    /// Get the pause after which the time between two presses is not counted as an interval
+    pub const fn idle(&self) -> Duration {
+        self.idle
+    }
+
+    /// Record an event read from the keyboard, the keys not in `layout` are ignored
+    pub fn record(&mut self, key_press: KeyPress, layout: &dyn Layout, elapsed: Duration) {
+        if let Some(info) = layout.find_from_key_code(*key_press.key_code()) {
+            self.record_key(*info.key_name(), key_press.is_pressed(), elapsed);
+        }
+    }
+
+    /// Record the press or the release of `key`.
+    ///
+    /// A press of a key already held, sent by the key repeat, is ignored, and so is a release
+    /// of a key not held.
+    pub fn record_key(&mut self, key: KeyName, is_pressed: bool, elapsed: Duration) {
+        if is_pressed {
+            if self.held.contains_key(&key) {
+                return;
+            }
+            self.held.insert(key, elapsed);
+            let stats = self.keys.entry(key).or_insert_with(|| KeyStats::new(key));
+            stats.presses += 1;
+            if let Some(last_press) = self.last_press {
+                let interval = elapsed.saturating_sub(last_press);
+                if interval <= self.idle {
+                    stats.interval_time += interval.as_secs_f64();
+                    stats.intervals += 1;
+                }
+            }
+            self.last_press = Some(elapsed);
+            if is_character(key) {
+                self.characters.push_back(elapsed);
+            }
+            self.forget_characters(elapsed);
+        } else if let Some(pressed) = self.held.remove(&key) {
+            if let Some(stats) = self.keys.get_mut(&key) {
+                stats.hold_time += elapsed.saturating_sub(pressed).as_secs_f64();
+                stats.holds += 1;
+            }
+        }
+    }
+
+    /// Drop the characters typed before the window ending at `now`
+    fn forget_characters(&mut self, now: Duration) {
+        while let Some(time) = self.characters.front() {
+            if now.saturating_sub(*time) >= self.wpm_window {
+                self.characters.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Get the statistics of `key`, [`None`] if it was never pressed
+    pub fn key(&self, key: KeyName) -> Option<&KeyStats> {
+        self.keys.get(&key)
+    }
+
+    /// Get the statistics of the keys pressed at least once, in the order of [`KeyName`]
+    pub fn keys(&self) -> impl Iterator<Item = &KeyStats> {
+        self.keys.values()
+    }
+
+    /// Get the number of presses of all keys
+    pub fn total_presses(&self) -> u64 {
+        self.keys.values().map(KeyStats::presses).sum()
+    }
+
+    /// Get the mean interval between two presses over all keys
+    pub fn mean_interval(&self) -> Option<Duration> {
+        let (total, count) = self
+            .keys
+            .values()
+            .fold((0_f64, 0), |(total, count), stats| {
+                (total + stats.interval_time, count + stats.intervals)
+            });
+        mean(total, count)
+    }
+
+    /// Get the words per minute over the window ending at `now`, a word being five characters.
+    ///
+    /// The modifiers, the function keys, the navigation keys and the arrows are not characters.
+    pub fn wpm(&self, now: Duration) -> f64 {
+        let minutes = self.wpm_window.as_secs_f64() / 60_f64;
+        if minutes == 0_f64 {
+            return 0_f64;
+        }
+        let characters = self
+            .characters
+            .iter()
+            .filter(|time| **time <= now && now.saturating_sub(**time) < self.wpm_window)
+            .count();
+        characters as f64 / CHARACTERS_PER_WORD / minutes
+    }
+
+    /// Draw the number of presses of each key of `layout` with `gradient`, sampled at 0 for
+    /// keys never pressed and at 1 for the most pressed key.
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use roccat_vulcan_api_rs::{ColorRgb, Gradient, KeyName, LayoutFrCh, TypingStats};
+    ///
+    /// let black = ColorRgb::new(0, 0, 0);
+    /// let red = ColorRgb::new(255, 0, 0);
+    /// let gradient = Gradient::new_evenly_spaced(&[black, red]).unwrap();
+    /// let mut stats = TypingStats::new();
+    /// stats.record_key(KeyName::A, true, Duration::ZERO);
+    ///
+    /// let layout = LayoutFrCh::new();
+    /// let heatmap = stats.heatmap(&layout, &gradient);
+    /// assert_eq!(heatmap.get_key(&layout, KeyName::A), Some(&red));
+    /// assert_eq!(heatmap.get_key(&layout, KeyName::B), Some(&black));
+    /// ```
+    pub fn heatmap(&self, layout: &dyn Layout, gradient: &Gradient) -> ColorBuffer<ColorRgb> {
+        let max = self.keys.values().map(KeyStats::presses).max().unwrap_or(0);
+        let mut buffer = ColorBuffer::new();
+        for key in layout.layout() {
+            let index = usize::from(key.key_code_light().code());
+            if index < NUMBER_KEY_LED_BUFFER {
+                let presses = self.key(*key.key_name()).map_or(0, KeyStats::presses);
+                let t = if max == 0 {
+                    0_f64
+                } else {
+                    presses as f64 / max as f64
+                };
+                buffer[index] = gradient.sample(t);
+            }
+        }
+        buffer
+    }
+
+    /// Write the statistics of each key as CSV, with the durations in milliseconds.
+    ///
+    /// The columns are `key,presses,total_hold_ms,mean_hold_ms,mean_interval_ms`, a mean is
+    /// empty if there is no value.
+    /// # Errors
+    /// Returns the errors of `writer`
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        /// Format an optional duration in milliseconds
+        fn millis(duration: Option<Duration>) -> String {
+            duration.map_or_else(String::new, |duration| {
+                format!("{:.3}", duration.as_secs_f64() * 1000_f64)
+            })
+        }
+
+        writeln!(
+            writer,
+            "key,presses,total_hold_ms,mean_hold_ms,mean_interval_ms"
+        )?;
+        for stats in self.keys() {
+            writeln!(
+                writer,
+                "{:?},{},{},{},{}",
+                stats.key(),
+                stats.presses(),
+                millis(Some(stats.total_hold())),
+                millis(stats.mean_hold()),
+                millis(stats.mean_interval())
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Get the statistics of each key as CSV, see [`Self::write_csv`]
+    pub fn to_csv(&self) -> String {
+        let mut csv = Vec::new();
+        // writing in a Vec does not fail
+        let _ = self.write_csv(&mut csv);
+        String::from_utf8_lossy(&csv).into_owned()
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+/// Format of the saved statistics
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StatsFile {
+    /// Version of the format
+    version: u64,
+    /// Statistics of the keys
+    keys: Vec<KeyStats>,
+}
+
+#[cfg(feature = "serde-serialize")]
+impl TypingStats {
+    /// Read the per key statistics saved by [`Self::to_json`], the other parameters are the
+    /// defaults of [`Self::new`].
+    /// # Errors
+    /// - [`StatsError::Json`] if the JSON is not valid
+    /// - [`StatsError::MissingVersion`] if there is no version
+    /// - [`StatsError::UnsupportedVersion`] if the version is not supported
+    pub fn from_json(json: &str) -> Result<Self, StatsError> {
+        /// Only the version, to check it before parsing the rest
+        #[derive(Deserialize)]
+        struct Version {
+            /// Version of the format
+            version: Option<u64>,
+        }
+
+        match serde_json::from_str::<Version>(json)?.version {
+            None => return Err(StatsError::MissingVersion),
+            Some(STATS_VERSION) => {}
+            Some(version) => return Err(StatsError::UnsupportedVersion(version)),
+        }
+        let file: StatsFile = serde_json::from_str(json)?;
+        let mut stats = Self::new();
+        stats.keys = file.keys.into_iter().map(|key| (key.key, key)).collect();
+        Ok(stats)
+    }
+
+    /// Serialize the per key statistics in pretty printed JSON
+    /// # Errors
+    /// [`StatsError::Json`] if the serialization fails
+    pub fn to_json(&self) -> Result<String, StatsError> {
+        Ok(serde_json::to_string_pretty(&StatsFile {
+            version: STATS_VERSION,
+            keys: self.keys().copied().collect(),
+        })?)
+    }
+
+    /// Read statistics from a JSON file, see [`Self::from_json`]
+    /// # Errors
+    /// [`StatsError::Io`] if the file cannot be read, and the errors of [`Self::from_json`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, StatsError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write the statistics in a JSON file
+    /// # Errors
+    /// [`StatsError::Io`] if the file cannot be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StatsError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+impl Default for TypingStats {
+    /// Empty statistics
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for TypingStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} presses on {} keys",
+            self.total_presses(),
+            self.keys.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::LayoutFrCh;
+
+    /// Time in milliseconds
+    const fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn record() {
+        let layout = LayoutFrCh::new();
+        let code = |key| *layout.find_from_key_name(key).unwrap().key_code_press();
+        let mut stats = TypingStats::new();
+        stats.record(KeyPress::new(code(KeyName::Q), true), &layout, ms(0));
+        // key repeat
+        stats.record(KeyPress::new(code(KeyName::Q), true), &layout, ms(50));
+        stats.record(KeyPress::new(code(KeyName::Q), false), &layout, ms(80));
+        // release without press
+        stats.record(KeyPress::new(code(KeyName::W), false), &layout, ms(90));
+        stats.record_key(KeyName::LeftShift, true, ms(100));
+        stats.record_key(KeyName::W, true, ms(200));
+        stats.record_key(KeyName::W, false, ms(240));
+        stats.record_key(KeyName::LeftShift, false, ms(300));
+        // after a pause
+        stats.record_key(KeyName::Q, true, ms(10_000));
+        stats.record_key(KeyName::Q, false, ms(10_120));
+
+        let q = stats.key(KeyName::Q).unwrap();
+        assert_eq!(q.presses(), 2);
+        assert_eq!(q.mean_hold(), Some(ms(100)));
+        assert_eq!(q.total_hold(), ms(200));
+        assert_eq!(q.mean_interval(), None);
+        let w = stats.key(KeyName::W).unwrap();
+        assert_eq!(w.presses(), 1);
+        assert_eq!(w.mean_interval(), Some(ms(100)));
+        assert_eq!(
+            stats.key(KeyName::LeftShift).unwrap().mean_hold(),
+            Some(ms(200))
+        );
+        assert!(stats.key(KeyName::E).is_none());
+        assert_eq!(stats.total_presses(), 4);
+        assert_eq!(stats.mean_interval(), Some(ms(100)));
+        assert_eq!(
+            stats.keys().map(KeyStats::key).collect::<Vec<_>>(),
+            vec![KeyName::Q, KeyName::W, KeyName::LeftShift]
+        );
+        assert_eq!(stats.to_string(), "4 presses on 3 keys");
+    }
+
+    #[test]
+    fn wpm() {
+        let mut stats = TypingStats::new().with_wpm_window(Duration::from_secs(6));
+        // 10 characters and modifiers in 6 seconds: 2 words per 0.1 minute
+        for i in 0..10_u64 {
+            stats.record_key(KeyName::A, true, ms(i * 500));
+            stats.record_key(KeyName::A, false, ms(i * 500 + 100));
+            stats.record_key(KeyName::LeftShift, true, ms(i * 500 + 200));
+            stats.record_key(KeyName::LeftShift, false, ms(i * 500 + 300));
+        }
+        assert!((stats.wpm(ms(5_000)) - 20_f64).abs() < 1E-9_f64);
+        // the first characters leave the window
+        assert!((stats.wpm(ms(7_000)) - 14_f64).abs() < 1E-9_f64);
+        assert_eq!(stats.wpm(ms(20_000)), 0_f64);
+        assert_eq!(
+            TypingStats::new()
+                .with_wpm_window(Duration::ZERO)
+                .wpm(Duration::ZERO),
+            0_f64
+        );
+    }
+
+    #[test]
+    fn heatmap() {
+        let layout = LayoutFrCh::new();
+        let gradient =
+            Gradient::new_evenly_spaced(&[ColorRgb::new(0, 0, 255), ColorRgb::new(255, 0, 0)])
+                .unwrap();
+        let mut stats = TypingStats::new();
+        let blue = stats.heatmap(&layout, &gradient);
+        assert_eq!(
+            blue.get_key(&layout, KeyName::A),
+            Some(&gradient.sample(0_f64))
+        );
+        for i in 0..4_u64 {
+            stats.record_key(KeyName::A, true, ms(i));
+            stats.record_key(KeyName::A, false, ms(i));
+        }
+        stats.record_key(KeyName::S, true, ms(10));
+        let heatmap = stats.heatmap(&layout, &gradient);
+        assert_eq!(
+            heatmap.get_key(&layout, KeyName::A),
+            Some(&gradient.sample(1_f64))
+        );
+        assert_eq!(
+            heatmap.get_key(&layout, KeyName::S),
+            Some(&gradient.sample(0.25_f64))
+        );
+        assert_eq!(
+            heatmap.get_key(&layout, KeyName::D),
+            Some(&gradient.sample(0_f64))
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let mut stats = TypingStats::new();
+        stats.record_key(KeyName::Escape, true, ms(0));
+        stats.record_key(KeyName::Escape, false, ms(150));
+        stats.record_key(KeyName::Space, true, ms(500));
+        assert_eq!(
+            stats.to_csv(),
+            "key,presses,total_hold_ms,mean_hold_ms,mean_interval_ms\n\
+             Escape,1,150.000,150.000,\n\
+             Space,1,0.000,,500.000\n"
+        );
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn save_and_load() {
+        let mut stats = TypingStats::new();
+        stats.record_key(KeyName::A, true, ms(0));
+        stats.record_key(KeyName::A, false, ms(100));
+        stats.record_key(KeyName::B, true, ms(300));
+        let path =
+            std::env::temp_dir().join(format!("roccat-vulcan-stats-{}.json", std::process::id()));
+        stats.save(&path).unwrap();
+        let mut loaded = TypingStats::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.key(KeyName::A), stats.key(KeyName::A));
+        assert_eq!(loaded.key(KeyName::B), stats.key(KeyName::B));
+        // the next session goes on counting
+        loaded.record_key(KeyName::A, true, ms(0));
+        assert_eq!(loaded.key(KeyName::A).unwrap().presses(), 2);
+
+        assert!(matches!(
+            TypingStats::from_json(r#"{"keys": []}"#),
+            Err(StatsError::MissingVersion)
+        ));
+        assert!(matches!(
+            TypingStats::from_json(r#"{"version": 2, "keys": []}"#),
+            Err(StatsError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            TypingStats::from_json(r#"{"version": 1, "keys": [{"key": "Escap"}]}"#),
+            Err(StatsError::Json(_))
+        ));
+        assert!(matches!(
+            TypingStats::load("/nonexistent/stats.json"),
+            Err(StatsError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,44 @@
+//! Daemon sharing the keyboard between several programs through a Unix domain socket.
+//!
+//! Usage: `roccat-vulcan-daemon [--socket <PATH>]`, the protocol is described in the
+//! documentation of the module `daemon` of the library.
+
+#[cfg(unix)]
+fn main() {
+    use std::path::PathBuf;
+    use std::process::exit;
+    use std::time::Duration;
+
+    use roccat_vulcan_api_rs::{default_socket_path, Daemon, KeyboardApi};
+
+    /// Time between two frames
+    const FRAME_DURATION: Duration = Duration::from_millis(20);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = match args.as_slice() {
+        [] => default_socket_path(),
+        [option, path] if option == "--socket" => PathBuf::from(path),
+        _ => {
+            eprintln!("usage: roccat-vulcan-daemon [--socket <PATH>]");
+            exit(2);
+        }
+    };
+    let keyboard = match KeyboardApi::new() {
+        Ok(keyboard) => keyboard,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            exit(1);
+        }
+    };
+    eprintln!("listening on {}", path.display());
+    if let Err(error) = Daemon::new(keyboard).serve(&path, FRAME_DURATION) {
+        eprintln!("error: {}", error);
+        exit(1);
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("the daemon needs Unix domain sockets");
+    std::process::exit(1);
+}
@@ -0,0 +1,307 @@
+//! Parsing of the command line arguments
+
+use std::time::Duration;
+
+use roccat_vulcan_api_rs::{ColorRgb, ControlerFeatureKind, KeyTarget, OPENRGB_DEFAULT_PORT};
+
+mod error;
+pub use error::*;
+
+mod run;
+pub use run::*;
+
+mod state;
+pub use state::*;
+
+/// Help printed by `--help`
+pub const USAGE: &str = "\
+Control the lighting of a Roccat Vulcan keyboard.
+
+USAGE:
+    roccat-vulcan-api-rs <COMMAND> [OPTIONS]
+
+COMMANDS:
+    list                        list the keyboards connected
+    info                        print the firmware version, serial number and paths
+    set <COLOR> [TARGET...]     color the targets, all the keys if none is given
+    effect <NAME> [--color <COLOR>...]
+                                run an effect: breathing, wave, gradient or reactive
+    mode <rainbow|custom>       switch the keyboard mode
+    capture                     print the key events
+    show                        print the colors set by `set`
+    openrgb [PORT]              serve the OpenRGB SDK protocol on 127.0.0.1,
+                                by default on the port 6742, until Escape is pressed
+    help                        print this message
+
+OPTIONS:
+    --duration <SECONDS>        stop after this time instead of waiting for Escape
+    --preview                   draw `set` and `effect` in the terminal instead of
+                                on the keyboard, until the duration elapsed or Ctrl-C
+
+A color is a hex code (#ff8000), a CSS name (orange), rgb(), hsv() or hsl().
+A target is a key name (Escape) or a group prefixed by @ (@Letters).
+The keyboard goes back to rainbow mode when the tool stops, so `set`, `effect`
+and `mode custom` keep running until Escape is pressed or the duration elapsed.
+The colors set are saved in the file given by ROCCAT_VULCAN_BUFFER,
+by default in the temporary directory, and `set` adds to them.
+";
+
+/// Command given on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Print the help
+    Help,
+    /// List the keyboards
+    List,
+    /// Color keys
+    Set {
+        /// Color to set
+        color: ColorRgb,
+        /// Keys colored, all if empty
+        targets: Vec<KeyTarget>,
+    },
+    /// Run an effect
+    Effect {
+        /// Name of the effect
+        name: String,
+        /// Colors given with `--color`
+        colors: Vec<ColorRgb>,
+    },
+    /// Switch the mode of the keyboard
+    Mode(ControlerFeatureKind),
+    /// Print the information on the keyboard
+    Info,
+    /// Print the key events
+    Capture,
+    /// Print the saved colors
+    Show,
+    /// Serve the OpenRGB SDK protocol
+    OpenRgb {
+        /// Local TCP port listened on
+        port: u16,
+    },
+}
+
+/// Command and the options common to all commands
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    /// Command to run
+    pub command: Command,
+    /// Time after which the command stops, [`None`] to wait for Escape
+    pub duration: Option<Duration>,
+    /// Whether the colors are drawn in the terminal instead of on the keyboard
+    pub preview: bool,
+}
+
+/// Parse the arguments, without the name of the program
+/// # Errors
+/// [`CliError::Usage`] if the arguments are invalid.
+pub fn parse(args: &[String]) -> Result<Invocation, CliError> {
+    let mut positional = Vec::new();
+    let mut colors = Vec::new();
+    let mut duration = None;
+    let mut preview = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                return Ok(Invocation {
+                    command: Command::Help,
+                    duration: None,
+                    preview: false,
+                })
+            }
+            "--duration" => duration = Some(parse_duration(option_value(&mut iter, arg)?)?),
+            "--preview" => preview = true,
+            "--color" => colors.push(parse_color(option_value(&mut iter, arg)?)?),
+            option if option.starts_with("--") => {
+                return Err(CliError::Usage(format!("unknown option {}", option)))
+            }
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let (name, arguments) = positional
+        .split_first()
+        .ok_or_else(|| CliError::Usage("no command given".to_owned()))?;
+    if !colors.is_empty() && *name != "effect" {
+        return Err(CliError::Usage(
+            "--color is only accepted by effect".to_owned(),
+        ));
+    }
+    let command = match (*name, arguments) {
+        ("help", []) => Command::Help,
+        ("list", []) => Command::List,
+        ("show", []) => Command::Show,
+        ("capture", []) => Command::Capture,
+        ("info", []) => Command::Info,
+        ("set", [color, targets @ ..]) => Command::Set {
+            color: parse_color(color)?,
+            targets: targets
+                .iter()
+                .map(|target| target.parse().map_err(CliError::usage))
+                .collect::<Result<_, _>>()?,
+        },
+        ("effect", [effect]) => Command::Effect {
+            name: (*effect).to_owned(),
+            colors,
+        },
+        ("openrgb", []) => Command::OpenRgb {
+            port: OPENRGB_DEFAULT_PORT,
+        },
+        ("openrgb", [port]) => Command::OpenRgb {
+            port: port
+                .parse()
+                .map_err(|_| CliError::Usage(format!("invalid port {}", port)))?,
+        },
+        ("mode", ["rainbow"]) => Command::Mode(ControlerFeatureKind::Rainbow),
+        ("mode", ["custom"]) => Command::Mode(ControlerFeatureKind::Custom),
+        ("mode", _) => {
+            return Err(CliError::Usage(
+                "expected mode rainbow or custom".to_owned(),
+            ))
+        }
+        ("help" | "list" | "info" | "show" | "capture" | "set" | "effect" | "openrgb", _) => {
+            return Err(CliError::Usage(format!(
+                "wrong number of arguments for {}",
+                name
+            )))
+        }
+        _ => return Err(CliError::Usage(format!("unknown command {}", name))),
+    };
+    if duration.is_some() && matches!(command, Command::OpenRgb { .. }) {
+        return Err(CliError::Usage(
+            "openrgb only stops when Escape is pressed".to_owned(),
+        ));
+    }
+    if preview && !matches!(command, Command::Set { .. } | Command::Effect { .. }) {
+        return Err(CliError::Usage(
+            "--preview is only accepted by set and effect".to_owned(),
+        ));
+    }
+    Ok(Invocation {
+        command,
+        duration,
+        preview,
+    })
+}
+
+/// Get the value following an option
+fn option_value<'a>(
+    iter: &mut impl Iterator<Item = &'a String>,
+    option: &str,
+) -> Result<&'a str, CliError> {
+    iter.next()
+        .map(String::as_str)
+        .ok_or_else(|| CliError::Usage(format!("{} expects a value", option)))
+}
+
+/// Parse a color argument
+fn parse_color(color: &str) -> Result<ColorRgb, CliError> {
+    color.parse().map_err(CliError::usage)
+}
+
+/// Parse a positive number of seconds
+fn parse_duration(seconds: &str) -> Result<Duration, CliError> {
+    seconds
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds > 0_f64)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| CliError::Usage(format!("invalid duration {}", seconds)))
+}
+
+#[cfg(test)]
+mod test {
+    use roccat_vulcan_api_rs::{KeyGroup, KeyName};
+
+    use super::*;
+
+    fn parse_str(args: &str) -> Result<Invocation, CliError> {
+        let args: Vec<String> = args.split_whitespace().map(str::to_owned).collect();
+        parse(&args)
+    }
+
+    fn command(args: &str) -> Command {
+        parse_str(args).unwrap().command
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(command("list"), Command::List);
+        assert_eq!(command("show"), Command::Show);
+        assert_eq!(command("capture"), Command::Capture);
+        assert_eq!(command("info"), Command::Info);
+        assert_eq!(command("openrgb"), Command::OpenRgb { port: 6742 });
+        assert_eq!(command("openrgb 1234"), Command::OpenRgb { port: 1234 });
+        assert_eq!(command("list --help"), Command::Help);
+        assert_eq!(command("help"), Command::Help);
+        assert_eq!(
+            command("mode rainbow"),
+            Command::Mode(ControlerFeatureKind::Rainbow)
+        );
+        assert_eq!(
+            command("set #ff0000"),
+            Command::Set {
+                color: ColorRgb::new(255, 0, 0),
+                targets: Vec::new()
+            }
+        );
+        assert_eq!(
+            command("set blue Escape @Letters"),
+            Command::Set {
+                color: ColorRgb::new(0, 0, 255),
+                targets: vec![KeyName::Escape.into(), KeyGroup::Letters.into()]
+            }
+        );
+        assert_eq!(
+            command("effect breathing --color red --color lime"),
+            Command::Effect {
+                name: "breathing".to_owned(),
+                colors: vec![ColorRgb::new(255, 0, 0), ColorRgb::new(0, 255, 0)]
+            }
+        );
+    }
+
+    #[test]
+    fn options() {
+        let invocation = parse_str("--duration 1.5 capture").unwrap();
+        assert_eq!(invocation.command, Command::Capture);
+        assert_eq!(invocation.duration, Some(Duration::from_millis(1_500)));
+        assert_eq!(parse_str("capture").unwrap().duration, None);
+        assert!(!invocation.preview);
+        assert!(parse_str("effect wave --preview").unwrap().preview);
+    }
+
+    #[test]
+    fn errors() {
+        for args in [
+            "",
+            "jump",
+            "list extra",
+            "set",
+            "set notacolor",
+            "set red NotAKey",
+            "set red @Nope",
+            "mode",
+            "mode disco",
+            "effect",
+            "set red --color blue",
+            "capture --duration",
+            "capture --duration -1",
+            "capture --duration nan",
+            "capture --duration 1e30",
+            "capture --fast",
+            "openrgb 70000",
+            "openrgb 1 2",
+            "openrgb --duration 1",
+            "capture --preview",
+        ] {
+            assert!(
+                matches!(parse_str(args), Err(CliError::Usage(_))),
+                "{}",
+                args
+            );
+        }
+    }
+}
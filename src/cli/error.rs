@@ -0,0 +1,145 @@
+//! Contains [`CliError`] and the exit codes
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use roccat_vulcan_api_rs::{ErrorRoccatVulcanApi, ProfileError};
+
+/// Error of the command line tool
+#[derive(Debug)]
+pub enum CliError {
+    /// The arguments are invalid
+    Usage(String),
+    /// Error of the keyboard
+    Keyboard(ErrorRoccatVulcanApi),
+    /// Error while reading or writing the saved colors
+    Io(std::io::Error),
+}
+
+impl CliError {
+    /// Create a [`Self::Usage`] from the message of an error
+    #[allow(clippy::needless_pass_by_value)] // used with map_err
+    pub fn usage(error: impl Display) -> Self {
+        Self::Usage(error.to_string())
+    }
+
+    /// Code returned by the process.
+    ///
+    /// - 1 for an input output error
+    /// - 2 for invalid arguments
+    /// - 10 and above for the errors of the keyboard, see [`keyboard_exit_code`]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Io(_) => 1,
+            Self::Usage(_) => 2,
+            Self::Keyboard(error) => keyboard_exit_code(error),
+        }
+    }
+}
+
+/// Code returned by the process for an error of the keyboard
+pub fn keyboard_exit_code(error: &ErrorRoccatVulcanApi) -> i32 {
+    match error {
+        ErrorRoccatVulcanApi::KeyboardNotFound { .. } => 10,
+        ErrorRoccatVulcanApi::NoLedDevice(_) => 11,
+        ErrorRoccatVulcanApi::LedDeviceError { .. } => 12,
+        ErrorRoccatVulcanApi::NoControlDevice(_) => 13,
+        ErrorRoccatVulcanApi::ControlDeviceError { .. } => 14,
+        ErrorRoccatVulcanApi::NoReadDevice(_) => 15,
+        ErrorRoccatVulcanApi::ReadDeviceError(_) => 16,
+        ErrorRoccatVulcanApi::WaitedToMuchTime { .. } => 17,
+        ErrorRoccatVulcanApi::HidApiError(_) => 18,
+        ErrorRoccatVulcanApi::InvalidInput => 19,
+        _ => 20,
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Usage(message) => write!(f, "{}", message),
+            Self::Keyboard(error) => write!(f, "{}", error),
+            Self::Io(error) => write!(f, "io error : {}", error),
+        }
+    }
+}
+
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Usage(_) => None,
+            Self::Keyboard(error) => Some(error),
+            Self::Io(error) => Some(error),
+        }
+    }
+}
+
+impl From<ErrorRoccatVulcanApi> for CliError {
+    fn from(error: ErrorRoccatVulcanApi) -> Self {
+        Self::Keyboard(error)
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ProfileError> for CliError {
+    fn from(error: ProfileError) -> Self {
+        match error {
+            ProfileError::Keyboard(error) => Self::Keyboard(error),
+            ProfileError::Io(error) => Self::Io(error),
+            error => Self::usage(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use roccat_vulcan_api_rs::HidInterfaceFilter;
+
+    use super::*;
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(CliError::Usage(String::new()).exit_code(), 2);
+        assert_eq!(
+            CliError::Io(std::io::ErrorKind::NotFound.into()).exit_code(),
+            1
+        );
+        let filter = HidInterfaceFilter::new(12_440, 1);
+        let errors = [
+            ErrorRoccatVulcanApi::KeyboardNotFound {
+                product_ids: Vec::new(),
+            },
+            ErrorRoccatVulcanApi::NoLedDevice(filter),
+            ErrorRoccatVulcanApi::NoControlDevice(filter),
+            ErrorRoccatVulcanApi::NoReadDevice(filter),
+            ErrorRoccatVulcanApi::WaitedToMuchTime {
+                report: Some(7),
+                duration: Duration::from_millis(100),
+            },
+            ErrorRoccatVulcanApi::InvalidInput,
+        ];
+        let codes: Vec<i32> = errors.iter().map(keyboard_exit_code).collect();
+        assert_eq!(codes, vec![10, 11, 13, 15, 17, 19]);
+        assert_eq!(
+            CliError::from(ErrorRoccatVulcanApi::KeyboardNotFound {
+                product_ids: Vec::new()
+            })
+            .exit_code(),
+            10
+        );
+        assert_eq!(
+            CliError::from(ProfileError::Keyboard(ErrorRoccatVulcanApi::NoLedDevice(
+                filter
+            )))
+            .exit_code(),
+            11
+        );
+    }
+}
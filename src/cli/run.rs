@@ -0,0 +1,359 @@
+//! Execution of the commands
+
+use std::fmt::{Display, Formatter};
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use hidapi::{DeviceInfo, HidApi};
+use roccat_vulcan_api_rs::{
+    Breathing, ColorBuffer, ColorRgb, ControlerFeatureKind, EffectConfig, ErrorRoccatVulcanApi,
+    Gradient, GradientMode, GradientSweep, KeyName, KeyPress, Keyboard, KeyboardApi, KeyboardEvent,
+    KeyboardIntrefacesFilter, Layout, LayoutKind, OpenRgbServer, Position, ProfileRenderer,
+    Reactive, TerminalKeyboard, TerminalPreview, Wave,
+};
+
+use super::{apply, buffer_file, format_buffer, load_buffer, save_buffer, CliError, Command};
+use super::{Invocation, USAGE};
+
+/// Time between two frames
+const FRAME_DURATION: Duration = Duration::from_millis(50);
+/// Colors of `effect gradient` when none is given
+const DEFAULT_GRADIENT: [ColorRgb; 2] = [ColorRgb::new(255, 0, 0), ColorRgb::new(0, 0, 255)];
+
+/// Run a command
+/// # Errors
+/// Returns the error of the command.
+pub fn run(invocation: Invocation) -> Result<(), CliError> {
+    let layout = LayoutKind::FrCh.layout();
+    let duration = invocation.duration;
+    match invocation.command {
+        Command::Help => print!("{}", USAGE),
+        Command::List => {
+            let api = HidApi::new().map_err(ErrorRoccatVulcanApi::HidApiError)?;
+            let keyboards = list_keyboards(&api);
+            if keyboards.is_empty() {
+                println!("no keyboard found");
+            }
+            for keyboard in keyboards {
+                println!("{}", keyboard);
+            }
+        }
+        Command::Info => {
+            let keyboard = KeyboardApi::new()?;
+            let info = keyboard.device_info();
+            println!("{}", info);
+            for (name, path) in [
+                ("read", info.read_path()),
+                ("control", info.control_path()),
+                ("led", info.led_path()),
+            ] {
+                println!("{} interface: {}", name, path.unwrap_or("unknown"));
+            }
+        }
+        Command::Show => print!(
+            "{}",
+            format_buffer(&load_buffer(&buffer_file(), layout)?, layout)
+        ),
+        Command::Set { color, targets } => {
+            let path = buffer_file();
+            let mut buffer = load_buffer(&path, layout)?;
+            apply(&mut buffer, layout, color, &targets)?;
+            save_buffer(&path, &buffer, layout)?;
+            let keyboard = open_keyboard(invocation.preview, layout)?;
+            render_until_stopped(
+                keyboard.as_ref(),
+                &mut ProfileRenderer::new(layout, buffer),
+                duration,
+            )?;
+        }
+        Command::Effect { name, colors } => {
+            let (base, effect) = effect_config(&name, &colors)?;
+            let mut renderer = ProfileRenderer::new(layout, ColorBuffer::from_element(base));
+            renderer.push_effect(effect, None);
+            let keyboard = open_keyboard(invocation.preview, layout)?;
+            render_until_stopped(keyboard.as_ref(), &mut renderer, duration)?;
+        }
+        Command::Mode(mode) => {
+            let keyboard = KeyboardApi::new()?;
+            keyboard.set_mode(mode)?;
+            if mode == ControlerFeatureKind::Custom {
+                let buffer = load_buffer(&buffer_file(), layout)?;
+                render_until_stopped(
+                    &keyboard,
+                    &mut ProfileRenderer::new(layout, buffer),
+                    duration,
+                )?;
+            }
+        }
+        Command::OpenRgb { port } => {
+            let keyboard = KeyboardApi::new()?;
+            let listener = TcpListener::bind(("127.0.0.1", port))?;
+            println!("OpenRGB SDK server listening on 127.0.0.1:{}", port);
+            OpenRgbServer::new(keyboard, layout).serve(&listener, FRAME_DURATION, |key_press| {
+                is_escape(layout, key_press)
+            })?;
+        }
+        Command::Capture => {
+            let keyboard = KeyboardApi::new()?;
+            let start = Instant::now();
+            while !is_elapsed(duration, start.elapsed()) {
+                for event in keyboard.read_events(FRAME_DURATION)? {
+                    println!("{}", format_event(start.elapsed(), event, layout));
+                    if event
+                        .key_press()
+                        .is_some_and(|key_press| is_escape(layout, key_press))
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Open the keyboard, or draw in the terminal with `--preview`
+fn open_keyboard(
+    preview: bool,
+    layout: &'static dyn Layout,
+) -> Result<Box<dyn Keyboard>, CliError> {
+    if preview {
+        Ok(Box::new(TerminalKeyboard::stdout(TerminalPreview::new(
+            layout,
+        ))))
+    } else {
+        Ok(Box::new(KeyboardApi::new()?))
+    }
+}
+
+/// Render until Escape is pressed or `duration` elapsed
+fn render_until_stopped(
+    keyboard: &dyn Keyboard,
+    renderer: &mut ProfileRenderer,
+    duration: Option<Duration>,
+) -> Result<(), CliError> {
+    let start = Instant::now();
+    while !is_elapsed(duration, start.elapsed()) {
+        keyboard.render(&renderer.frame(start.elapsed()))?;
+        for key_press in keyboard.read_key_press(FRAME_DURATION)? {
+            if is_escape(renderer.layout(), key_press) {
+                return Ok(());
+            }
+            renderer.key_press(key_press, start.elapsed());
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the command has run for longer than `duration`
+fn is_elapsed(duration: Option<Duration>, elapsed: Duration) -> bool {
+    duration.is_some_and(|duration| elapsed >= duration)
+}
+
+/// Returns whether Escape is pressed
+fn is_escape(layout: &dyn Layout, key_press: KeyPress) -> bool {
+    key_press.is_pressed()
+        && layout
+            .find_from_key_code(*key_press.key_code())
+            .map(|info| *info.key_name())
+            == Some(KeyName::Escape)
+}
+
+/// Get the base color and the effect run by `effect <name>`
+/// # Errors
+/// [`CliError::Usage`] if the effect is unknown or the number of colors is wrong.
+pub fn effect_config(
+    name: &str,
+    colors: &[ColorRgb],
+) -> Result<(ColorRgb, EffectConfig), CliError> {
+    let max_colors = match name {
+        "wave" => 0,
+        "breathing" | "reactive" => 1,
+        _ => usize::MAX,
+    };
+    if colors.len() > max_colors {
+        return Err(CliError::Usage(format!(
+            "the effect {} accepts at most {} color",
+            name, max_colors
+        )));
+    }
+    let white = ColorRgb::new(255, 255, 255);
+    let black = ColorRgb::new(0, 0, 0);
+    let first = colors.first().copied();
+    match name {
+        "breathing" => Ok((first.unwrap_or(white), Breathing::default().into())),
+        "wave" => Ok((black, Wave::default().into())),
+        "reactive" => {
+            let reactive = Reactive::default();
+            let effect = Reactive::new(first.unwrap_or(white), reactive.fade()).unwrap_or(reactive);
+            Ok((black, effect.into()))
+        }
+        "gradient" => {
+            let colors: &[ColorRgb] = if colors.is_empty() {
+                &DEFAULT_GRADIENT
+            } else {
+                colors
+            };
+            Gradient::new_evenly_spaced(colors)
+                .map(|gradient| gradient.with_mode(GradientMode::Mirror))
+                .and_then(|gradient| {
+                    GradientSweep::new(gradient, Position::new(1_f64, 0_f64), 0.25_f64)
+                })
+                .map(|effect| (black, effect.into()))
+                .ok_or_else(|| CliError::Usage("invalid gradient".to_owned()))
+        }
+        _ => Err(CliError::Usage(format!("unknown effect {}", name))),
+    }
+}
+
+/// Format an event for `capture`, with the name of the key if it is known
+pub fn format_event(elapsed: Duration, event: KeyboardEvent, layout: &dyn Layout) -> String {
+    let key_name = match event {
+        KeyboardEvent::KeyDown(key_code) | KeyboardEvent::KeyUp(key_code) => layout
+            .find_from_key_code(key_code)
+            .map(|info| format!(" {:?}", info.key_name())),
+        _ => None,
+    };
+    format!(
+        "{:>8} ms  {}{}",
+        elapsed.as_millis(),
+        event,
+        key_name.unwrap_or_default()
+    )
+}
+
+/// Keyboard found by `list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardListing {
+    /// Product id of the model
+    pub product_id: u16,
+    /// Name of the product reported by the device
+    pub product: Option<String>,
+    /// Serial number
+    pub serial_number: Option<String>,
+    /// Path of the led device
+    pub path: String,
+}
+
+impl KeyboardListing {
+    /// Create the listing from the led device of a keyboard
+    fn new(device: &DeviceInfo) -> Self {
+        Self {
+            product_id: device.product_id(),
+            product: device.product_string().map(str::to_owned),
+            serial_number: device.serial_number().map(str::to_owned),
+            path: device.path().to_string_lossy().into_owned(),
+        }
+    }
+}
+
+impl Display for KeyboardListing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (product id {:#06x}, serial number {}) at {}",
+            self.product.as_deref().unwrap_or("Roccat Vulcan"),
+            self.product_id,
+            self.serial_number.as_deref().unwrap_or("unknown"),
+            self.path
+        )
+    }
+}
+
+/// List the keyboards of the default models, one per led device
+fn list_keyboards(api: &HidApi) -> Vec<KeyboardListing> {
+    api.device_list()
+        .filter(|device| {
+            KeyboardIntrefacesFilter::DEFAULT_MODEL
+                .iter()
+                .any(|model| model.led_interface().match_filter(device))
+        })
+        .map(KeyboardListing::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use roccat_vulcan_api_rs::{KeyCode, LayoutFrCh};
+
+    use super::*;
+
+    #[test]
+    fn effects() {
+        let red = ColorRgb::new(255, 0, 0);
+        let blue = ColorRgb::new(0, 0, 255);
+        let (base, effect) = effect_config("breathing", &[red]).unwrap();
+        assert_eq!(base, red);
+        assert!(matches!(effect, EffectConfig::Breathing(_)));
+        match effect_config("reactive", &[blue]).unwrap() {
+            (base, EffectConfig::Reactive(reactive)) => {
+                assert_eq!(base, ColorRgb::new(0, 0, 0));
+                assert_eq!(reactive.color(), blue);
+            }
+            other => panic!("{:?}", other),
+        }
+        match effect_config("gradient", &[red, blue, red]).unwrap() {
+            (_, EffectConfig::Gradient(sweep)) => assert_eq!(sweep.gradient().stops().len(), 3),
+            other => panic!("{:?}", other),
+        }
+        assert!(effect_config("gradient", &[]).is_ok());
+        assert!(effect_config("wave", &[]).is_ok());
+        assert!(matches!(
+            effect_config("wave", &[red]),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            effect_config("breathing", &[red, blue]),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            effect_config("disco", &[]),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn events() {
+        let layout = LayoutFrCh::new();
+        let escape = KeyCode::new(0xfb, 0x11);
+        assert_eq!(
+            format_event(
+                Duration::from_millis(1_234),
+                KeyboardEvent::KeyDown(escape),
+                &layout
+            ),
+            "    1234 ms  key code (251, 17) pressed Escape"
+        );
+        assert_eq!(
+            format_event(
+                Duration::ZERO,
+                KeyboardEvent::Unknown { raw: [0; 5] },
+                &layout
+            ),
+            "       0 ms  unknown event [00, 00, 00, 00, 00]"
+        );
+        assert!(is_escape(&layout, KeyPress::new(escape, true)));
+        assert!(!is_escape(&layout, KeyPress::new(escape, false)));
+        assert!(!is_elapsed(None, Duration::from_secs(1_000)));
+        assert!(is_elapsed(
+            Some(Duration::from_secs(1)),
+            Duration::from_secs(1)
+        ));
+    }
+
+    #[test]
+    fn listing() {
+        let mut listing = KeyboardListing {
+            product_id: 0x307a,
+            product: Some("ROCCAT Vulcan 100 AIMO".to_owned()),
+            serial_number: None,
+            path: "/dev/hidraw3".to_owned(),
+        };
+        assert_eq!(
+            listing.to_string(),
+            "ROCCAT Vulcan 100 AIMO (product id 0x307a, serial number unknown) at /dev/hidraw3"
+        );
+        listing.product = None;
+        assert!(listing.to_string().starts_with("Roccat Vulcan (product"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Colors saved between two runs of `set`, printed by `show`
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use roccat_vulcan_api_rs::{ColorBuffer, ColorRgb, KeyName, KeyTarget, Layout};
+
+use super::CliError;
+
+/// Environment variable overriding the file of the saved colors
+const BUFFER_FILE_VARIABLE: &str = "ROCCAT_VULCAN_BUFFER";
+
+/// Get the file where the colors are saved
+pub fn buffer_file() -> PathBuf {
+    std::env::var_os(BUFFER_FILE_VARIABLE).map_or_else(
+        || std::env::temp_dir().join("roccat-vulcan-buffer.txt"),
+        PathBuf::from,
+    )
+}
+
+/// Set `color` on the keys of `targets`, or on all the keys if `targets` is empty
+/// # Errors
+/// [`CliError::Usage`] if a key has no light.
+pub fn apply(
+    buffer: &mut ColorBuffer<ColorRgb>,
+    layout: &dyn Layout,
+    color: ColorRgb,
+    targets: &[KeyTarget],
+) -> Result<(), CliError> {
+    if targets.is_empty() {
+        *buffer = ColorBuffer::from_element(color);
+        return Ok(());
+    }
+    for target in targets {
+        for index in target.light_indices(layout)? {
+            buffer[index] = color;
+        }
+    }
+    Ok(())
+}
+
+/// Write the color of each key with a light, one key per line
+pub fn format_buffer(buffer: &ColorBuffer<ColorRgb>, layout: &dyn Layout) -> String {
+    let mut text = String::new();
+    for key_name in KeyName::ALL {
+        if let Some(color) = buffer.get_key(layout, key_name) {
+            // writing in a String never fails
+            let _ = writeln!(
+                text,
+                "{:<16} {}",
+                format!("{:?}", key_name),
+                color.to_hex_string()
+            );
+        }
+    }
+    text
+}
+
+/// Read the text written by [`format_buffer`], the keys not listed are black
+/// # Errors
+/// [`CliError::Usage`] if a line is not a key name followed by a color.
+pub fn parse_buffer(text: &str, layout: &dyn Layout) -> Result<ColorBuffer<ColorRgb>, CliError> {
+    let mut buffer = ColorBuffer::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (key_name, color) = match words.as_slice() {
+            [key_name, color] => (key_name, color),
+            _ => return Err(CliError::Usage(format!("invalid line \"{}\"", line))),
+        };
+        let key_name: KeyName = key_name.parse().map_err(CliError::usage)?;
+        let color = color.parse().map_err(CliError::usage)?;
+        apply(&mut buffer, layout, color, &[key_name.into()])?;
+    }
+    Ok(buffer)
+}
+
+/// Load the saved colors, black if the file does not exist
+/// # Errors
+/// [`CliError::Io`] if the file cannot be read and [`CliError::Usage`] if it is invalid.
+pub fn load_buffer(path: &Path, layout: &dyn Layout) -> Result<ColorBuffer<ColorRgb>, CliError> {
+    if path.exists() {
+        parse_buffer(&std::fs::read_to_string(path)?, layout)
+    } else {
+        Ok(ColorBuffer::new())
+    }
+}
+
+/// Save the colors
+/// # Errors
+/// [`CliError::Io`] if the file cannot be written.
+pub fn save_buffer(
+    path: &Path,
+    buffer: &ColorBuffer<ColorRgb>,
+    layout: &dyn Layout,
+) -> Result<(), CliError> {
+    std::fs::write(path, format_buffer(buffer, layout))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use roccat_vulcan_api_rs::{KeyGroup, LayoutFrCh};
+
+    use super::*;
+
+    #[test]
+    fn apply_targets() {
+        let layout = LayoutFrCh::new();
+        let red = ColorRgb::new(255, 0, 0);
+        let blue = ColorRgb::new(0, 0, 255);
+        let mut buffer = ColorBuffer::new();
+        apply(&mut buffer, &layout, red, &[]).unwrap();
+        apply(
+            &mut buffer,
+            &layout,
+            blue,
+            &[KeyName::Escape.into(), KeyGroup::Arrows.into()],
+        )
+        .unwrap();
+        assert_eq!(buffer.get_key(&layout, KeyName::Escape), Some(&blue));
+        assert_eq!(buffer.get_key(&layout, KeyName::ArrowUp), Some(&blue));
+        assert_eq!(buffer.get_key(&layout, KeyName::A), Some(&red));
+        assert!(matches!(
+            apply(&mut buffer, &layout, red, &[KeyName::WheelUp.into()]),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn format_and_parse() {
+        let layout = LayoutFrCh::new();
+        let mut buffer = ColorBuffer::new();
+        apply(
+            &mut buffer,
+            &layout,
+            ColorRgb::new(255, 128, 0),
+            &[KeyName::Escape.into()],
+        )
+        .unwrap();
+        let text = format_buffer(&buffer, &layout);
+        assert!(text.starts_with("Escape           #ff8000\n"));
+        assert_eq!(parse_buffer(&text, &layout).unwrap(), buffer);
+        assert_eq!(
+            parse_buffer("A red\n\n", &layout)
+                .unwrap()
+                .get_key(&layout, KeyName::A),
+            Some(&ColorRgb::new(255, 0, 0))
+        );
+        for text in ["A", "A red blue", "Nope red", "A nope"] {
+            assert!(matches!(
+                parse_buffer(text, &layout),
+                Err(CliError::Usage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let layout = LayoutFrCh::new();
+        let path = std::env::temp_dir().join(format!("cli-buffer-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(load_buffer(&path, &layout).unwrap(), ColorBuffer::new());
+        let buffer = ColorBuffer::from_element(ColorRgb::new(1, 2, 3));
+        save_buffer(&path, &buffer, &layout).unwrap();
+        let loaded = load_buffer(&path, &layout);
+        std::fs::remove_file(&path).unwrap();
+        // only the lights of the keys are saved
+        assert_eq!(
+            loaded.unwrap().get_key(&layout, KeyName::Space),
+            Some(&ColorRgb::new(1, 2, 3))
+        );
+    }
+}
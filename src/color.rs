@@ -5,3 +5,18 @@ pub use base::*;
 
 mod buffer;
 pub use buffer::*;
+
+mod keyed;
+pub use keyed::*;
+
+mod parse;
+pub use parse::*;
+
+mod space;
+pub use space::*;
+
+mod gradient;
+pub use gradient::*;
+
+mod correction;
+pub use correction::*;
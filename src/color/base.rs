@@ -4,9 +4,6 @@
 use std::cmp::Ordering;
 use std::fmt::{Binary, Display, Formatter, LowerHex, Octal, UpperHex};
 
-#[cfg(feature = "serde-serialize")]
-use serde::{Deserialize, Serialize};
-
 /// RGB color representation
 /// # Example
 /// ```
@@ -15,7 +12,6 @@ use serde::{Deserialize, Serialize};
 /// let _buffer = ColorBuffer::from_element(ColorRgb::new(255, 255, 255));
 /// ```
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Copy, Hash, Default)]
-#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct ColorRgb {
     /// Red
     r: u8,
@@ -309,7 +305,7 @@ impl ColorRgb {
     /// Convert a value between 0 and 1 en un [u8] entre 0 et 255
     #[allow(clippy::cast_possible_truncation)]
     #[allow(clippy::cast_sign_loss)]
-    fn convert_to_u8(v: f64) -> u8 {
+    pub(crate) fn convert_to_u8(v: f64) -> u8 {
         // the cast is safe
         (v * 255_f64).round().min(255_f64).max(0_f64) as u8
     }
@@ -347,7 +343,8 @@ impl ColorRgb {
         let hue = if chroma == 0 {
             0_f64
         } else if value == self.r {
-            1_f64 / 6_f64 * (self.g as f64 - self.b as f64) / chroma as f64
+            // the hue is negative for color between magenta and red
+            (1_f64 / 6_f64 * (self.g as f64 - self.b as f64) / chroma as f64).rem_euclid(1_f64)
         } else if value == self.g {
             1_f64 / 6_f64 * (2_f64 + (self.b as f64 - self.r as f64) / chroma as f64)
         } else {
@@ -432,7 +429,6 @@ impl Octal for ColorRgb {
 
 /// Color with alpha parameter
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Default)]
-#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct ColorRgba {
     /// base color
     color: ColorRgb,
@@ -1,13 +1,20 @@
 //! module for the color buffer
 
-//#[cfg(feature = "serde-serialize")]
-//use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde-serialize")]
+use std::convert::TryInto;
 use std::fmt::{Display, Formatter};
 use std::iter::FusedIterator;
 use std::ops::{Index, IndexMut};
 use std::slice::SliceIndex;
 
-use crate::{color::ColorRgb, reports};
+#[cfg(feature = "serde-serialize")]
+use serde::{de, ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    color::{ColorCorrection, ColorRgb},
+    layout::{KeyName, Layout},
+    reports,
+};
 
 /// Size of the [`ColorBuffer`].
 pub const NUMBER_KEY_LED_BUFFER: usize = 144;
@@ -19,7 +26,7 @@ pub(crate) const BITE_PACKET_SIZE: usize = 64;
 /// raw buffer site based on buffer header and number of key
 const BUFFER_SIZE_RAW: usize = reports::LED_FEATURE_REPORT_HEAD.len() + NUMBER_KEY_LED_BUFFER * 3;
 /// Total size of the buffer separated in packer of [0x00, 64 bits]
-const BUFFER_SIZE_PACKETED: usize =
+pub(crate) const BUFFER_SIZE_PACKETED: usize =
     (((get_packeted_index_from_raw(BUFFER_SIZE_RAW - 1, BITE_PACKET_SIZE) + 1)
         / (BITE_PACKET_SIZE + 1))
         + 1)
@@ -48,24 +55,38 @@ const fn get_packeted_index_from_raw(index: usize, packet_size: usize) -> usize
 /// # Ok(())
 /// # }
 /// ```
+///
+/// With the feature `serde-serialize` the buffer is serialized in a compact form,
+/// as a list of [`NUMBER_KEY_LED_BUFFER`] colors. See [`crate::KeyedColorBuffer`] for a form
+/// keyed by [`KeyName`].
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
-// TODO serd
-// #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct ColorBuffer<T> {
     /// Array that contain the colors
     buffer: [T; NUMBER_KEY_LED_BUFFER],
 }
 
-/*#[cfg(feature = "serde-serialize")]
-impl<T: Serialize + Clone> Serialize for ColorBuffer<T> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let vec: Vec<_> = self.buffer.clone().into();
-        vec.serialize(serializer)
+#[cfg(feature = "serde-serialize")]
+impl<T: Serialize> Serialize for ColorBuffer<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(NUMBER_KEY_LED_BUFFER))?;
+        for color in self.iter() {
+            seq.serialize_element(color)?;
+        }
+        seq.end()
     }
-}*/
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for ColorBuffer<T> {
+    /// Deserialize from a list of exactly [`NUMBER_KEY_LED_BUFFER`] colors
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let vec = Vec::<T>::deserialize(deserializer)?;
+        let len = vec.len();
+        vec.try_into()
+            .map(Self::from_array)
+            .map_err(|_| de::Error::invalid_length(len, &"a list of 144 colors"))
+    }
+}
 
 impl<T> ColorBuffer<T> {
     /// Create a buffer from an array
@@ -93,6 +114,37 @@ impl<T> ColorBuffer<T> {
         self.buffer.get_mut(index)
     }
 
+    /// Get the color of a key using the light code given by `layout`.
+    ///
+    /// Returns [`None`] if the key is not in the layout or has no light, like the wheel.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{ColorBuffer, ColorRgb, KeyName, LayoutFrCh};
+    ///
+    /// let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+    /// let layout = LayoutFrCh::new();
+    /// *buffer.get_key_mut(&layout, KeyName::Escape).unwrap() = ColorRgb::new(255, 0, 0);
+    /// assert_eq!(buffer[0], ColorRgb::new(255, 0, 0));
+    /// assert_eq!(buffer.get_key(&layout, KeyName::WheelUp), None);
+    /// ```
+    pub fn get_key<L: Layout + ?Sized>(&self, layout: &L, key_name: KeyName) -> Option<&T> {
+        let info = layout.find_from_key_name(key_name)?;
+        self.buffer.get(usize::from(info.key_code_light().code()))
+    }
+
+    /// Get a mutable reference to the color of a key using the light code given by `layout`.
+    ///
+    /// Returns [`None`] if the key is not in the layout or has no light, like the wheel.
+    pub fn get_key_mut<L: Layout + ?Sized>(
+        &mut self,
+        layout: &L,
+        key_name: KeyName,
+    ) -> Option<&mut T> {
+        let info = layout.find_from_key_name(key_name)?;
+        self.buffer
+            .get_mut(usize::from(info.key_code_light().code()))
+    }
+
     /// Returns an iterator in the colors
     pub fn iter(
         &self,
@@ -131,6 +183,35 @@ impl<'a, T> IntoIterator for &'a mut ColorBuffer<T> {
 impl<C: Into<ColorRgb> + Clone> ColorBuffer<C> {
     /// Get an array of u8 that is ready to be send to the led device
     pub fn get_led_buffer(&self) -> [u8; BUFFER_SIZE_PACKETED] {
+        self.get_led_buffer_with(|color| color)
+    }
+
+    /// Get an array of u8 that is ready to be send to the led device where each color
+    /// is corrected by `correction`.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{ColorBuffer, ColorCorrection, ColorRgb, Value};
+    ///
+    /// let buffer = ColorBuffer::from_element(ColorRgb::new(255, 0, 255));
+    /// let correction = ColorCorrection::new().with_brightness(Value::new(0_f64).unwrap());
+    /// let black = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+    /// assert_eq!(
+    ///     buffer.get_led_buffer_corrected(&correction),
+    ///     black.get_led_buffer()
+    /// );
+    /// ```
+    pub fn get_led_buffer_corrected(
+        &self,
+        correction: &ColorCorrection,
+    ) -> [u8; BUFFER_SIZE_PACKETED] {
+        self.get_led_buffer_with(|color| correction.apply(color))
+    }
+
+    /// Encode the buffer for the led device, applying `map` on each color.
+    fn get_led_buffer_with(
+        &self,
+        map: impl Fn(ColorRgb) -> ColorRgb,
+    ) -> [u8; BUFFER_SIZE_PACKETED] {
         /// length of the led freature report header
         const LENGTH_HEAD: usize = reports::LED_FEATURE_REPORT_HEAD.len();
         let mut buffer_return = [0x00; BUFFER_SIZE_PACKETED];
@@ -142,7 +223,7 @@ impl<C: Into<ColorRgb> + Clone> ColorBuffer<C> {
             // we need to send the color by packet for 12 key
             let packet_number = index / KEY_PACKET_SIZE; // int division
             let buffer_index = (index % KEY_PACKET_SIZE) + KEY_PACKET_SIZE * 3 * packet_number;
-            let color = val.clone().into();
+            let color = map(val.clone().into());
             buffer_return
                 [get_packeted_index_from_raw(LENGTH_HEAD + buffer_index, BITE_PACKET_SIZE)] =
                 color.r();
@@ -159,6 +240,35 @@ impl<C: Into<ColorRgb> + Clone> ColorBuffer<C> {
     }
 }
 
+impl ColorBuffer<ColorRgb> {
+    /// Decode the data written on the led device by [`crate::KeyboardApi::render`],
+    /// the inverse of [`Self::get_led_buffer`].
+    ///
+    /// Returns [`None`] if `data` does not have the right length or header.
+    pub(crate) fn from_led_buffer(data: &[u8]) -> Option<Self> {
+        /// length of the led freature report header
+        const LENGTH_HEAD: usize = reports::LED_FEATURE_REPORT_HEAD.len();
+        let at = |raw_index| data[get_packeted_index_from_raw(raw_index, BITE_PACKET_SIZE)];
+        if data.len() != BUFFER_SIZE_PACKETED
+            || (0..LENGTH_HEAD).any(|index| at(index) != reports::LED_FEATURE_REPORT_HEAD[index])
+        {
+            return None;
+        }
+        let mut buffer = Self::new();
+        for (index, color) in buffer.iter_mut().enumerate() {
+            let packet_number = index / KEY_PACKET_SIZE;
+            let buffer_index =
+                LENGTH_HEAD + (index % KEY_PACKET_SIZE) + KEY_PACKET_SIZE * 3 * packet_number;
+            *color = ColorRgb::new(
+                at(buffer_index),
+                at(buffer_index + KEY_PACKET_SIZE),
+                at(buffer_index + KEY_PACKET_SIZE * 2),
+            );
+        }
+        Some(buffer)
+    }
+}
+
 impl<T: Copy> ColorBuffer<T> {
     /// Create the buffer with the same color for each key
     pub fn from_element(color: T) -> Self {
@@ -234,6 +344,8 @@ impl<T> From<ColorBuffer<T>> for [T; NUMBER_KEY_LED_BUFFER] {
 #[cfg(test)]
 mod test {
     use super::*;
+    #[cfg(feature = "serde-serialize")]
+    use crate::layout::LayoutFrCh;
 
     /// test the definitions of key that can be sent in packets
     #[test]
@@ -260,4 +372,45 @@ mod test {
         assert_eq!(get_packeted_index_from_raw(0, 32), 1);
         assert_eq!(get_packeted_index_from_raw(4, 2), 7);
     }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn decode_led_buffer() {
+        let mut buffer = ColorBuffer::new();
+        for (index, color) in buffer.iter_mut().enumerate() {
+            *color = ColorRgb::new(index as u8, 255 - index as u8, (index * 7) as u8);
+        }
+        let data = buffer.get_led_buffer();
+        assert_eq!(ColorBuffer::from_led_buffer(&data), Some(buffer));
+        assert_eq!(ColorBuffer::from_led_buffer(&data[1..]), None);
+        assert_eq!(
+            ColorBuffer::from_led_buffer(&[0; BUFFER_SIZE_PACKETED]),
+            None
+        );
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn serde_compact() {
+        let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+        *buffer.get_key_mut(&LayoutFrCh::new(), KeyName::A).unwrap() = ColorRgb::new(255, 0, 16);
+        buffer[143] = ColorRgb::new(1, 2, 3);
+        let string = serde_json::to_string(&buffer).unwrap();
+        assert!(string.starts_with("[\"#000000\","));
+        assert!(string.ends_with(",\"#010203\"]"));
+        assert_eq!(
+            serde_json::from_str::<ColorBuffer<ColorRgb>>(&string).unwrap(),
+            buffer
+        );
+
+        let short = format!("[{}]", vec!["\"red\""; 143].join(","));
+        assert!(serde_json::from_str::<ColorBuffer<ColorRgb>>(&short).is_err());
+
+        let values = ColorBuffer::from_element(42_u8);
+        let string = serde_json::to_string(&values).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ColorBuffer<u8>>(&string).unwrap(),
+            values
+        );
+    }
 }
@@ -0,0 +1,320 @@
+//! Contains the color correction stage [`ColorCorrection`] applied before sending colors to
+//! the LED device.
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use super::{ColorRgb, Value};
+
+/// Gamma exponent, gauranties that the value is finite and strictly positive.
+///
+/// Used by [`ColorCorrection::with_gamma`].
+///
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::Gamma;
+///
+/// assert_eq!(Gamma::new(2.2_f64).unwrap().value(), 2.2_f64);
+/// assert_eq!(Gamma::new(0_f64), None);
+/// assert_eq!(Gamma::new(f64::INFINITY), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gamma(f64);
+
+impl Gamma {
+    /// Create a new gamma if `g` is finite and strictly positive. Otherwise return [`None`]
+    pub fn new(g: f64) -> Option<Self> {
+        if g.is_finite() && g > 0_f64 {
+            Some(Self(g))
+        } else {
+            None
+        }
+    }
+
+    /// Get the value warpped
+    pub const fn value(self) -> f64 {
+        self.0
+    }
+}
+
+impl Eq for Gamma {}
+
+impl PartialOrd for Gamma {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Gamma {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.0.partial_cmp(&other.0) {
+            Some(ord) => ord,
+            None => unreachable!(),
+        }
+    }
+}
+
+impl Display for Gamma {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Default for Gamma {
+    /// Returns a gamma of 1, i.e. no correction
+    fn default() -> Self {
+        Self(1_f64)
+    }
+}
+
+/// Color correction applied on each channel when the colors are encoded for the LED device.
+///
+/// For each channel the output is `brightness * gain * gamma_table[value]`.
+/// Effects can then work with linear values and the correction is done only once at the end.
+/// The default correction does not change the colors.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{ColorCorrection, ColorRgb, Gamma, Value};
+///
+/// let correction = ColorCorrection::new()
+///     .with_brightness(Value::new(0.5_f64).unwrap())
+///     .with_gamma(Gamma::new(2_f64).unwrap());
+/// assert_eq!(
+///     correction.apply(ColorRgb::new(255, 0, 0)),
+///     ColorRgb::new(128, 0, 0)
+/// );
+/// assert_eq!(
+///     correction.apply(ColorRgb::new(0, 128, 0)),
+///     ColorRgb::new(0, 32, 0)
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorCorrection {
+    /// Global brightness
+    brightness: Value,
+    /// Gain per channel in the order red, green, blue
+    gain: [Value; 3],
+    /// Gamma lookup table applied before the brightness and gain
+    gamma_table: [u8; 256],
+    /// Final lookup table per channel combining every stage
+    lookup: [[u8; 256]; 3],
+}
+
+impl ColorCorrection {
+    /// Create a correction that does not change the colors
+    pub fn new() -> Self {
+        Self::from_gamma_table(Self::identity_table())
+    }
+
+    /// Create a correction from a custom gamma lookup table.
+    ///
+    /// The brightness and the gains are set to 1.
+    pub fn from_gamma_table(gamma_table: [u8; 256]) -> Self {
+        let mut correction = Self {
+            brightness: Self::one(),
+            gain: [Self::one(); 3],
+            gamma_table,
+            lookup: [[0; 256]; 3],
+        };
+        correction.update_lookup();
+        correction
+    }
+
+    /// Set the global brightness
+    pub fn with_brightness(mut self, brightness: Value) -> Self {
+        self.set_brightness(brightness);
+        self
+    }
+
+    /// Set the gain of each channel, used for white balance
+    pub fn with_gain(mut self, red: Value, green: Value, blue: Value) -> Self {
+        self.set_gain(red, green, blue);
+        self
+    }
+
+    /// Replace the gamma lookup table by the curve `255 * (v / 255) ^ gamma`
+    pub fn with_gamma(mut self, gamma: Gamma) -> Self {
+        self.set_gamma(gamma);
+        self
+    }
+
+    /// Get the global brightness
+    pub const fn brightness(&self) -> Value {
+        self.brightness
+    }
+
+    /// Set the global brightness
+    pub fn set_brightness(&mut self, brightness: Value) {
+        self.brightness = brightness;
+        self.update_lookup();
+    }
+
+    /// Get the gain of each channel in the order red, green, blue
+    pub const fn gain(&self) -> [Value; 3] {
+        self.gain
+    }
+
+    /// Set the gain of each channel
+    pub fn set_gain(&mut self, red: Value, green: Value, blue: Value) {
+        self.gain = [red, green, blue];
+        self.update_lookup();
+    }
+
+    /// Get the gamma lookup table
+    pub const fn gamma_table(&self) -> &[u8; 256] {
+        &self.gamma_table
+    }
+
+    /// Set a custom gamma lookup table
+    pub fn set_gamma_table(&mut self, gamma_table: [u8; 256]) {
+        self.gamma_table = gamma_table;
+        self.update_lookup();
+    }
+
+    /// Replace the gamma lookup table by the curve `255 * (v / 255) ^ gamma`
+    pub fn set_gamma(&mut self, gamma: Gamma) {
+        let mut table = [0_u8; 256];
+        for (value, out) in table.iter_mut().enumerate() {
+            *out = Self::convert_to_u8((value as f64 / 255_f64).powf(gamma.value()));
+        }
+        self.set_gamma_table(table);
+    }
+
+    /// Apply the correction on a color
+    pub const fn apply(&self, color: ColorRgb) -> ColorRgb {
+        ColorRgb::new(
+            self.lookup[0][color.r() as usize],
+            self.lookup[1][color.g() as usize],
+            self.lookup[2][color.b() as usize],
+        )
+    }
+
+    /// Returns whether the correction does not change any color
+    pub fn is_identity(&self) -> bool {
+        self.lookup
+            .iter()
+            .all(|table| *table == Self::identity_table())
+    }
+
+    /// Recompute the final lookup tables
+    fn update_lookup(&mut self) {
+        for (table, gain) in self.lookup.iter_mut().zip(self.gain.iter()) {
+            let factor = self.brightness.value() * gain.value();
+            for (out, value) in table.iter_mut().zip(self.gamma_table.iter()) {
+                *out = Self::convert_to_u8(f64::from(*value) / 255_f64 * factor);
+            }
+        }
+    }
+
+    /// Table that maps each value to itself
+    #[allow(clippy::cast_possible_truncation)]
+    fn identity_table() -> [u8; 256] {
+        let mut table = [0_u8; 256];
+        for (value, out) in table.iter_mut().enumerate() {
+            // the cast is safe as value < 256
+            *out = value as u8;
+        }
+        table
+    }
+
+    /// Convert a value between 0 and 1 into a [`u8`] between 0 and 255
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn convert_to_u8(v: f64) -> u8 {
+        // the cast is safe
+        (v * 255_f64).round().clamp(0_f64, 255_f64) as u8
+    }
+
+    /// Value of 1
+    fn one() -> Value {
+        match Value::new(1_f64) {
+            Some(v) => v,
+            None => unreachable!(),
+        }
+    }
+}
+
+impl Default for ColorCorrection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for ColorCorrection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "brightness: {}, gain: ({}, {}, {})",
+            self.brightness, self.gain[0], self.gain[1], self.gain[2]
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn identity() {
+        let correction = ColorCorrection::new();
+        assert!(correction.is_identity());
+        for v in 0..=255_u8 {
+            let color = ColorRgb::new(v, 255 - v, v / 2);
+            assert_eq!(correction.apply(color), color);
+        }
+        let correction = ColorCorrection::new().with_gamma(Gamma::new(1_f64).unwrap());
+        assert!(correction.is_identity());
+    }
+
+    #[test]
+    fn brightness_and_gain() {
+        let correction = ColorCorrection::new()
+            .with_brightness(Value::new(0.5_f64).unwrap())
+            .with_gain(
+                Value::new(1_f64).unwrap(),
+                Value::new(0.5_f64).unwrap(),
+                Value::new(0_f64).unwrap(),
+            );
+        assert!(!correction.is_identity());
+        assert_eq!(
+            correction.apply(ColorRgb::new(255, 255, 255)),
+            ColorRgb::new(128, 64, 0)
+        );
+        assert_eq!(
+            correction.apply(ColorRgb::new(0, 0, 0)),
+            ColorRgb::new(0, 0, 0)
+        );
+
+        let off = ColorCorrection::new().with_brightness(Value::new(0_f64).unwrap());
+        assert_eq!(
+            off.apply(ColorRgb::new(255, 255, 255)),
+            ColorRgb::new(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn gamma() {
+        let correction = ColorCorrection::new().with_gamma(Gamma::new(2.2_f64).unwrap());
+        assert_eq!(
+            correction.apply(ColorRgb::new(0, 0, 0)),
+            ColorRgb::new(0, 0, 0)
+        );
+        assert_eq!(
+            correction.apply(ColorRgb::new(255, 255, 255)),
+            ColorRgb::new(255, 255, 255)
+        );
+        // low values are dimmed
+        assert!(correction.apply(ColorRgb::new(32, 0, 0)).r() < 5);
+        // the table is monotonic
+        let table = correction.gamma_table();
+        assert!(table.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut custom = [0_u8; 256];
+        custom[255] = 255;
+        let correction = ColorCorrection::from_gamma_table(custom);
+        assert_eq!(
+            correction.apply(ColorRgb::new(254, 255, 1)),
+            ColorRgb::new(0, 255, 0)
+        );
+    }
+}
@@ -0,0 +1,711 @@
+//! Contains the color interpolation ([`ColorRgb::lerp`]) and the [`Gradient`]
+
+#[cfg(feature = "serde-serialize")]
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::space::{unit, wrap_hue};
+use super::{
+    Chroma, ColorBuffer, ColorHsl, ColorLinearRgb, ColorOklab, ColorOklch, ColorRgb,
+    NUMBER_KEY_LED_BUFFER,
+};
+use crate::{Layout, Position};
+
+/// Color space in which colors are interpolated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum ColorSpace {
+    /// sRGB values, fast but muddy in the middle
+    Rgb,
+    /// Linear sRGB, physically correct light mixing
+    LinearRgb,
+    /// Hue saturation value, the hue goes the shortest way around the wheel
+    Hsv,
+    /// Hue saturation lightness, the hue goes the shortest way around the wheel
+    Hsl,
+    /// Perceptual OKLab
+    Oklab,
+    /// Perceptual OKLCH, the hue goes the shortest way around the wheel
+    Oklch,
+}
+
+impl Default for ColorSpace {
+    /// Returns [`Self::Oklab`]
+    fn default() -> Self {
+        Self::Oklab
+    }
+}
+
+impl Display for ColorSpace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rgb => write!(f, "rgb"),
+            Self::LinearRgb => write!(f, "linear rgb"),
+            Self::Hsv => write!(f, "hsv"),
+            Self::Hsl => write!(f, "hsl"),
+            Self::Oklab => write!(f, "oklab"),
+            Self::Oklch => write!(f, "oklch"),
+        }
+    }
+}
+
+impl ColorSpace {
+    /// Index of the hue in the components, if any
+    const fn hue_index(self) -> Option<usize> {
+        match self {
+            Self::Hsv | Self::Hsl => Some(0),
+            Self::Oklch => Some(2),
+            Self::Rgb | Self::LinearRgb | Self::Oklab => None,
+        }
+    }
+
+    /// Index of the component that makes the hue meaningless when it is 0, if any
+    const fn colorfulness_index(self) -> Option<usize> {
+        match self {
+            Self::Hsv | Self::Hsl | Self::Oklch => Some(1),
+            Self::Rgb | Self::LinearRgb | Self::Oklab => None,
+        }
+    }
+
+    /// Decompose a color into the components of the space
+    fn components(self, color: ColorRgb) -> [f64; 3] {
+        match self {
+            Self::Rgb => [
+                f64::from(color.r()),
+                f64::from(color.g()),
+                f64::from(color.b()),
+            ],
+            Self::LinearRgb => {
+                let c = ColorLinearRgb::from(color);
+                [c.r().value(), c.g().value(), c.b().value()]
+            }
+            Self::Hsv => {
+                let (h, s, v) = color.into_hsv();
+                [h.value(), s.value(), v.value()]
+            }
+            Self::Hsl => {
+                let c = ColorHsl::from(color);
+                [
+                    c.hue().value(),
+                    c.saturation().value(),
+                    c.lightness().value(),
+                ]
+            }
+            Self::Oklab => {
+                let c = ColorOklab::from(color);
+                [c.l().value(), c.a(), c.b()]
+            }
+            Self::Oklch => {
+                let c = ColorOklch::from(color);
+                [c.l().value(), c.chroma().value(), c.hue().value()]
+            }
+        }
+    }
+
+    /// Build back a color from the components of the space
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn color(self, c: [f64; 3]) -> ColorRgb {
+        match self {
+            Self::Rgb => ColorRgb::new(
+                // the cast is safe
+                c[0].round().clamp(0_f64, 255_f64) as u8,
+                c[1].round().clamp(0_f64, 255_f64) as u8,
+                c[2].round().clamp(0_f64, 255_f64) as u8,
+            ),
+            Self::LinearRgb => ColorLinearRgb::new(unit(c[0]), unit(c[1]), unit(c[2])).into(),
+            Self::Hsv => ColorRgb::new_hsv(wrap_hue(c[0]), unit(c[1]), unit(c[2])),
+            Self::Hsl => ColorHsl::new(wrap_hue(c[0]), unit(c[1]), unit(c[2])).into(),
+            Self::Oklab => ColorOklab::new(unit(c[0]), c[1], c[2])
+                .unwrap_or_default()
+                .into(),
+            Self::Oklch => ColorOklch::new(
+                unit(c[0]),
+                Chroma::new(c[1].max(0_f64)).unwrap_or_default(),
+                wrap_hue(c[2]),
+            )
+            .into(),
+        }
+    }
+
+    /// Interpolate the components of two colors
+    fn lerp(self, mut from: [f64; 3], mut to: [f64; 3], t: f64) -> [f64; 3] {
+        if let (Some(hue), Some(colorfulness)) = (self.hue_index(), self.colorfulness_index()) {
+            // a gray has no meaningful hue, we take the one of the other color
+            if from[colorfulness] == 0_f64 {
+                from[hue] = to[hue];
+            } else if to[colorfulness] == 0_f64 {
+                to[hue] = from[hue];
+            }
+            // take the shortest way around the wheel
+            if to[hue] - from[hue] > 0.5_f64 {
+                from[hue] += 1_f64;
+            } else if from[hue] - to[hue] > 0.5_f64 {
+                to[hue] += 1_f64;
+            }
+        }
+        [
+            (to[0] - from[0]).mul_add(t, from[0]),
+            (to[1] - from[1]).mul_add(t, from[1]),
+            (to[2] - from[2]).mul_add(t, from[2]),
+        ]
+    }
+}
+
+impl ColorRgb {
+    /// Interpolate between `self` (`t = 0`) and `other` (`t = 1`) in the given color space.
+    ///
+    /// `t` is clamped between 0 and 1.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{ColorRgb, ColorSpace};
+    ///
+    /// let red = ColorRgb::new(255, 0, 0);
+    /// let blue = ColorRgb::new(0, 0, 255);
+    /// assert_eq!(
+    ///     red.lerp(blue, 0.5_f64, ColorSpace::Rgb),
+    ///     ColorRgb::new(128, 0, 128)
+    /// );
+    /// assert_eq!(red.lerp(blue, 0_f64, ColorSpace::Oklab), red);
+    /// assert_eq!(red.lerp(blue, 2_f64, ColorSpace::Oklch), blue);
+    /// // the hue goes from red to blue through magenta
+    /// assert_eq!(
+    ///     red.lerp(blue, 0.5_f64, ColorSpace::Hsv),
+    ///     ColorRgb::new(255, 0, 255)
+    /// );
+    /// ```
+    pub fn lerp(self, other: Self, t: f64, space: ColorSpace) -> Self {
+        let t = if t.is_nan() {
+            0_f64
+        } else {
+            t.clamp(0_f64, 1_f64)
+        };
+        if t == 0_f64 {
+            return self;
+        } else if t == 1_f64 {
+            return other;
+        }
+        space.color(space.lerp(space.components(self), space.components(other), t))
+    }
+}
+
+/// Easing function applied on the interpolation parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum Easing {
+    /// No easing
+    Linear,
+    /// Start slowly (quadratic)
+    EaseIn,
+    /// End slowly (quadratic)
+    EaseOut,
+    /// Start and end slowly (cubic)
+    EaseInOut,
+    /// Jump at the middle
+    Step,
+}
+
+impl Default for Easing {
+    /// Returns [`Self::Linear`]
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl Easing {
+    /// Apply the easing on `t` between 0 and 1
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::Easing;
+    ///
+    /// assert_eq!(Easing::Linear.apply(0.25_f64), 0.25_f64);
+    /// assert_eq!(Easing::EaseIn.apply(0.5_f64), 0.25_f64);
+    /// assert_eq!(Easing::EaseInOut.apply(0.5_f64), 0.5_f64);
+    /// ```
+    pub fn apply(self, t: f64) -> f64 {
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2_f64 - t),
+            Self::EaseInOut => t * t * 2_f64.mul_add(-t, 3_f64),
+            Self::Step => {
+                if t < 0.5_f64 {
+                    0_f64
+                } else {
+                    1_f64
+                }
+            }
+        }
+    }
+}
+
+/// Behavior of a [`Gradient`] outside of `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum GradientMode {
+    /// Use the color of the closest end
+    Clamp,
+    /// Start again from the beginning
+    Repeat,
+    /// Go back and forth
+    Mirror,
+}
+
+impl Default for GradientMode {
+    /// Returns [`Self::Clamp`]
+    fn default() -> Self {
+        Self::Clamp
+    }
+}
+
+impl GradientMode {
+    /// Map any `t` to `[0, 1]`
+    fn apply(self, t: f64) -> f64 {
+        if !t.is_finite() {
+            return 0_f64;
+        }
+        match self {
+            Self::Clamp => t.clamp(0_f64, 1_f64),
+            Self::Repeat => {
+                if t != 0_f64 && t.rem_euclid(1_f64) == 0_f64 {
+                    // so that the last stop is reached for t = 1, 2, ...
+                    1_f64
+                } else {
+                    t.rem_euclid(1_f64)
+                }
+            }
+            Self::Mirror => {
+                let t = t.rem_euclid(2_f64);
+                if t > 1_f64 {
+                    2_f64 - t
+                } else {
+                    t
+                }
+            }
+        }
+    }
+}
+
+/// A color stop of a [`Gradient`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct GradientStop {
+    /// Position of the stop, usualy between 0 and 1
+    position: f64,
+    /// Color at the stop
+    color: ColorRgb,
+}
+
+impl GradientStop {
+    /// Create a new stop
+    pub const fn new(position: f64, color: ColorRgb) -> Self {
+        Self { position, color }
+    }
+
+    /// Get the position of the stop
+    pub const fn position(&self) -> f64 {
+        self.position
+    }
+
+    /// Get the color of the stop
+    pub const fn color(&self) -> ColorRgb {
+        self.color
+    }
+}
+
+/// Multi stop color gradient.
+///
+/// The easing is applied between each pair of consecutive stops.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{ColorRgb, ColorSpace, Gradient, GradientMode, GradientStop};
+///
+/// let gradient = Gradient::new(vec![
+///     GradientStop::new(0_f64, ColorRgb::new(255, 0, 0)),
+///     GradientStop::new(0.5_f64, ColorRgb::new(0, 255, 0)),
+///     GradientStop::new(1_f64, ColorRgb::new(0, 0, 255)),
+/// ])
+/// .unwrap()
+/// .with_space(ColorSpace::Rgb)
+/// .with_mode(GradientMode::Mirror);
+/// assert_eq!(gradient.sample(0.25_f64), ColorRgb::new(128, 128, 0));
+/// assert_eq!(gradient.sample(1.5_f64), ColorRgb::new(0, 255, 0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(try_from = "GradientDef"))]
+pub struct Gradient {
+    /// Stops sorted by position
+    stops: Vec<GradientStop>,
+    /// Space used for interpolation
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    space: ColorSpace,
+    /// Easing between two stops
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    easing: Easing,
+    /// Behavior outside of `[0, 1]`
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    mode: GradientMode,
+}
+
+/// Unchecked form of [`Gradient`] read by serde, converted with [`Gradient::new`]
+#[cfg(feature = "serde-serialize")]
+#[derive(Deserialize)]
+struct GradientDef {
+    /// Stops in any order
+    stops: Vec<GradientStop>,
+    /// Space used for interpolation
+    #[serde(default)]
+    space: ColorSpace,
+    /// Easing between two stops
+    #[serde(default)]
+    easing: Easing,
+    /// Behavior outside of `[0, 1]`
+    #[serde(default)]
+    mode: GradientMode,
+}
+
+#[cfg(feature = "serde-serialize")]
+impl TryFrom<GradientDef> for Gradient {
+    type Error = &'static str;
+
+    fn try_from(def: GradientDef) -> Result<Self, Self::Error> {
+        let GradientDef {
+            stops,
+            space,
+            easing,
+            mode,
+        } = def;
+        Self::new(stops)
+            .map(|gradient| {
+                gradient
+                    .with_space(space)
+                    .with_easing(easing)
+                    .with_mode(mode)
+            })
+            .ok_or("a gradient needs at least one stop with a finite position")
+    }
+}
+
+impl Gradient {
+    /// Create a gradient from stops, they do not need to be sorted.
+    ///
+    /// Returns [`None`] if there is no stop or a position is not finite.
+    pub fn new(mut stops: Vec<GradientStop>) -> Option<Self> {
+        if stops.is_empty() || stops.iter().any(|stop| !stop.position().is_finite()) {
+            return None;
+        }
+        stops.sort_by(|a, b| a.position().total_cmp(&b.position()));
+        Some(Self {
+            stops,
+            space: ColorSpace::default(),
+            easing: Easing::default(),
+            mode: GradientMode::default(),
+        })
+    }
+
+    /// Create a gradient with colors evenly spaced between 0 and 1.
+    ///
+    /// Returns [`None`] if there is no color.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{ColorRgb, Gradient};
+    ///
+    /// let gradient =
+    ///     Gradient::new_evenly_spaced(&[ColorRgb::new(0, 0, 0), ColorRgb::new(255, 255, 255)])
+    ///         .unwrap();
+    /// assert_eq!(gradient.stops()[1].position(), 1_f64);
+    /// ```
+    pub fn new_evenly_spaced(colors: &[ColorRgb]) -> Option<Self> {
+        let last = colors.len().saturating_sub(1).max(1) as f64;
+        Self::new(
+            colors
+                .iter()
+                .enumerate()
+                .map(|(index, color)| GradientStop::new(index as f64 / last, *color))
+                .collect(),
+        )
+    }
+
+    /// Set the color space used for the interpolation
+    pub const fn with_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Set the easing between two stops
+    pub const fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Set the behavior outside of `[0, 1]`
+    pub const fn with_mode(mut self, mode: GradientMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Get the stops sorted by position
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    /// Get the color space used for the interpolation
+    pub const fn space(&self) -> ColorSpace {
+        self.space
+    }
+
+    /// Get the easing between two stops
+    pub const fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    /// Get the behavior outside of `[0, 1]`
+    pub const fn mode(&self) -> GradientMode {
+        self.mode
+    }
+
+    /// Sample the gradient at `t`.
+    pub fn sample(&self, t: f64) -> ColorRgb {
+        let t = self.mode.apply(t);
+        let next = self.stops.iter().position(|stop| stop.position() > t);
+        match next {
+            // before the first stop
+            Some(0) => self.stops[0].color(),
+            // after the last stop
+            None => self.stops[self.stops.len() - 1].color(),
+            Some(index) => {
+                let from = self.stops[index - 1];
+                let to = self.stops[index];
+                let local = (t - from.position()) / (to.position() - from.position());
+                from.color()
+                    .lerp(to.color(), self.easing.apply(local), self.space)
+            }
+        }
+    }
+
+    /// Fill the buffer along a direction.
+    ///
+    /// Each key of the layout is projected on `direction`, the key with the lowest projection
+    /// gets `t = 0` and the one with the highest `t = 1`.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{
+    ///     ColorBuffer, ColorRgb, Gradient, KeyName, Layout, LayoutFrCh, Position,
+    /// };
+    ///
+    /// let layout = LayoutFrCh::new();
+    /// let gradient =
+    ///     Gradient::new_evenly_spaced(&[ColorRgb::new(255, 0, 0), ColorRgb::new(0, 0, 255)]).unwrap();
+    /// let mut buffer = ColorBuffer::new();
+    /// // from left to right
+    /// gradient.fill(&mut buffer, &layout, Position::new(1_f64, 0_f64));
+    /// let escape = layout.find_from_key_name(KeyName::Escape).unwrap();
+    /// assert_eq!(
+    ///     buffer[escape.key_code_light().code() as usize],
+    ///     ColorRgb::new(255, 0, 0)
+    /// );
+    /// ```
+    pub fn fill<L: Layout + ?Sized>(
+        &self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &L,
+        direction: Position,
+    ) {
+        self.fill_with_offset(buffer, layout, direction, 0_f64);
+    }
+
+    /// Fill the buffer along a direction like [`Self::fill`], with `offset` added to `t`.
+    ///
+    /// Used to animate the gradient, best used with [`GradientMode::Repeat`]
+    /// or [`GradientMode::Mirror`].
+    pub fn fill_with_offset<L: Layout + ?Sized>(
+        &self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &L,
+        direction: Position,
+        offset: f64,
+    ) {
+        let project = |position: &Position| {
+            position
+                .x()
+                .mul_add(direction.x(), position.y() * direction.y())
+        };
+        let keys = || {
+            layout
+                .layout()
+                .iter()
+                .filter(|key| usize::from(key.key_code_light().code()) < NUMBER_KEY_LED_BUFFER)
+        };
+        let (min, max) = keys().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), key| {
+            let p = project(key.key_pos());
+            (min.min(p), max.max(p))
+        });
+        let range = if max > min { max - min } else { 1_f64 };
+        for key in keys() {
+            let t = (project(key.key_pos()) - min) / range;
+            buffer[usize::from(key.key_code_light().code())] = self.sample(t + offset);
+        }
+    }
+}
+
+impl Display for Gradient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "gradient in {} [", self.space)?;
+        for (index, stop) in self.stops.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", stop.position(), stop.color())?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyName, LayoutFrCh};
+
+    const RED: ColorRgb = ColorRgb::new(255, 0, 0);
+    const GREEN: ColorRgb = ColorRgb::new(0, 255, 0);
+    const BLUE: ColorRgb = ColorRgb::new(0, 0, 255);
+    const WHITE: ColorRgb = ColorRgb::new(255, 255, 255);
+
+    const SPACES: [ColorSpace; 6] = [
+        ColorSpace::Rgb,
+        ColorSpace::LinearRgb,
+        ColorSpace::Hsv,
+        ColorSpace::Hsl,
+        ColorSpace::Oklab,
+        ColorSpace::Oklch,
+    ];
+
+    #[test]
+    fn lerp_ends() {
+        for space in SPACES {
+            for (a, b) in [(RED, BLUE), (WHITE, GREEN), (BLUE, BLUE)] {
+                assert_eq!(a.lerp(b, 0_f64, space), a, "{}", space);
+                assert_eq!(a.lerp(b, 1_f64, space), b, "{}", space);
+                assert_eq!(a.lerp(b, -1_f64, space), a, "{}", space);
+                assert_eq!(a.lerp(b, f64::NAN, space), a, "{}", space);
+            }
+            assert_eq!(BLUE.lerp(BLUE, 0.3_f64, space), BLUE, "{}", space);
+        }
+    }
+
+    #[test]
+    fn lerp_spaces() {
+        assert_eq!(
+            RED.lerp(GREEN, 0.5_f64, ColorSpace::Rgb),
+            ColorRgb::new(128, 128, 0)
+        );
+        // linear is brighter in the middle
+        assert_eq!(
+            RED.lerp(GREEN, 0.5_f64, ColorSpace::LinearRgb),
+            ColorRgb::new(188, 188, 0)
+        );
+        assert_eq!(
+            RED.lerp(GREEN, 0.5_f64, ColorSpace::Hsv),
+            ColorRgb::new(255, 255, 0)
+        );
+        assert_eq!(
+            RED.lerp(GREEN, 0.5_f64, ColorSpace::Hsl),
+            ColorRgb::new(255, 255, 0)
+        );
+        // hue of a gray is ignored
+        let c = WHITE.lerp(RED, 0.5_f64, ColorSpace::Hsv);
+        assert_eq!(c, ColorRgb::new(255, 128, 128));
+        // shortest way from magenta to red
+        let magenta = ColorRgb::new(255, 0, 255);
+        assert_eq!(
+            magenta.lerp(RED, 0.5_f64, ColorSpace::Hsv),
+            ColorRgb::new(255, 0, 128)
+        );
+    }
+
+    #[test]
+    fn gradient_sample() {
+        assert!(Gradient::new(vec![]).is_none());
+        assert!(Gradient::new(vec![GradientStop::new(f64::NAN, RED)]).is_none());
+
+        let single = Gradient::new(vec![GradientStop::new(0.5_f64, RED)]).unwrap();
+        assert_eq!(single.sample(0_f64), RED);
+        assert_eq!(single.sample(1_f64), RED);
+
+        // unsorted stops
+        let gradient = Gradient::new(vec![
+            GradientStop::new(1_f64, BLUE),
+            GradientStop::new(0_f64, RED),
+        ])
+        .unwrap()
+        .with_space(ColorSpace::Rgb);
+        assert_eq!(gradient.stops()[0].color(), RED);
+        assert_eq!(gradient.sample(0.5_f64), ColorRgb::new(128, 0, 128));
+        assert_eq!(gradient.sample(-3_f64), RED);
+        assert_eq!(gradient.sample(3_f64), BLUE);
+
+        let eased = gradient.clone().with_easing(Easing::Step);
+        assert_eq!(eased.sample(0.4_f64), RED);
+        assert_eq!(eased.sample(0.6_f64), BLUE);
+    }
+
+    #[test]
+    fn gradient_mode() {
+        let gradient = Gradient::new_evenly_spaced(&[RED, BLUE])
+            .unwrap()
+            .with_space(ColorSpace::Rgb);
+        let repeat = gradient.clone().with_mode(GradientMode::Repeat);
+        assert_eq!(repeat.sample(1.25_f64), gradient.sample(0.25_f64));
+        assert_eq!(repeat.sample(-0.75_f64), gradient.sample(0.25_f64));
+        assert_eq!(repeat.sample(1_f64), BLUE);
+        assert_eq!(repeat.sample(2_f64), BLUE);
+        let mirror = gradient.clone().with_mode(GradientMode::Mirror);
+        assert_eq!(mirror.sample(1.25_f64), gradient.sample(0.75_f64));
+        assert_eq!(mirror.sample(-0.25_f64), gradient.sample(0.25_f64));
+        assert_eq!(mirror.sample(2_f64), RED);
+    }
+
+    #[test]
+    fn gradient_fill() {
+        let layout = LayoutFrCh::new();
+        let gradient = Gradient::new_evenly_spaced(&[RED, BLUE]).unwrap();
+        let mut buffer = ColorBuffer::from_element(GREEN);
+        // from bottom to top
+        gradient.fill(&mut buffer, &layout, Position::new(0_f64, 1_f64));
+        let color_of = |key_name| {
+            let key = layout.find_from_key_name(key_name).unwrap();
+            buffer[usize::from(key.key_code_light().code())]
+        };
+        assert_eq!(color_of(KeyName::Space), RED);
+        assert_eq!(color_of(KeyName::Escape), BLUE);
+        assert_eq!(color_of(KeyName::F1), BLUE);
+        assert_ne!(color_of(KeyName::A), RED);
+        assert_ne!(color_of(KeyName::A), BLUE);
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn gradient_serde() {
+        let gradient = Gradient::new_evenly_spaced(&[RED, BLUE])
+            .unwrap()
+            .with_mode(GradientMode::Mirror);
+        let json = serde_json::to_string(&gradient).unwrap();
+        assert_eq!(serde_json::from_str::<Gradient>(&json).unwrap(), gradient);
+
+        // the stops go through `Gradient::new`
+        let mut value = serde_json::to_value(&gradient).unwrap();
+        value["stops"].as_array_mut().unwrap().reverse();
+        let unsorted: Gradient = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(unsorted, gradient);
+        value["stops"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<Gradient>(value).is_err());
+        assert!(serde_json::from_str::<Gradient>(r#"{"stops": []}"#).is_err());
+    }
+}
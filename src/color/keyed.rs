@@ -0,0 +1,147 @@
+//! Contains [`KeyedColorBuffer`], a [`ColorBuffer`] serialized by [`KeyName`]
+
+#[cfg(feature = "serde-serialize")]
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{de, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::ColorBuffer;
+#[cfg(feature = "serde-serialize")]
+use crate::layout::{KeyName, Layout, LayoutFrCh};
+
+/// Wrapper around a [`ColorBuffer`] serialized as a map from [`KeyName`] to color
+/// using the layout [`LayoutFrCh`].
+///
+/// Only the keys with a light are serialized, the others light codes are lost.
+/// When deserializing, the keys missing in the map get the default color.
+/// # Example
+/// ```
+/// # #[cfg(feature = "serde-serialize")]
+/// # {
+/// use roccat_vulcan_api_rs::{ColorBuffer, ColorRgb, KeyName, KeyedColorBuffer, LayoutFrCh};
+///
+/// let keyed: KeyedColorBuffer<ColorRgb> =
+///     serde_json::from_str(r##"{"Escape": "red", "A": "#00ff00"}"##).unwrap();
+/// let buffer = keyed.into_buffer();
+/// let layout = LayoutFrCh::new();
+/// assert_eq!(
+///     buffer.get_key(&layout, KeyName::Escape),
+///     Some(&ColorRgb::new(255, 0, 0))
+/// );
+/// assert_eq!(
+///     buffer.get_key(&layout, KeyName::Space),
+///     Some(&ColorRgb::new(0, 0, 0))
+/// );
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct KeyedColorBuffer<T> {
+    /// Buffer wrapped
+    buffer: ColorBuffer<T>,
+}
+
+impl<T> KeyedColorBuffer<T> {
+    /// Wrap a buffer
+    pub const fn new(buffer: ColorBuffer<T>) -> Self {
+        Self { buffer }
+    }
+
+    /// Get the buffer wrapped
+    pub const fn buffer(&self) -> &ColorBuffer<T> {
+        &self.buffer
+    }
+
+    /// Get a mutable reference to the buffer wrapped
+    pub const fn buffer_mut(&mut self) -> &mut ColorBuffer<T> {
+        &mut self.buffer
+    }
+
+    /// Unwrap the buffer
+    #[allow(clippy::missing_const_for_fn)] // false positive
+    pub fn into_buffer(self) -> ColorBuffer<T> {
+        self.buffer
+    }
+}
+
+impl<T> From<ColorBuffer<T>> for KeyedColorBuffer<T> {
+    fn from(buffer: ColorBuffer<T>) -> Self {
+        Self::new(buffer)
+    }
+}
+
+impl<T> From<KeyedColorBuffer<T>> for ColorBuffer<T> {
+    fn from(keyed: KeyedColorBuffer<T>) -> Self {
+        keyed.into_buffer()
+    }
+}
+
+impl<T: Display> Display for KeyedColorBuffer<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.buffer)
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<T: Serialize> Serialize for KeyedColorBuffer<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let layout = LayoutFrCh::new();
+        let mut map = serializer.serialize_map(None)?;
+        for info in layout.layout() {
+            if let Some(color) = self.buffer.get_key(&layout, *info.key_name()) {
+                map.serialize_entry(info.key_name(), color)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<'de, T: Deserialize<'de> + Default + Copy> Deserialize<'de> for KeyedColorBuffer<T> {
+    /// Deserialize from a map from [`KeyName`] to color.
+    /// Returns an error if a key has no light in [`LayoutFrCh`].
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let layout = LayoutFrCh::new();
+        let map = BTreeMap::<KeyName, T>::deserialize(deserializer)?;
+        let mut buffer = ColorBuffer::new();
+        for (key_name, color) in map {
+            *buffer.get_key_mut(&layout, key_name).ok_or_else(|| {
+                de::Error::custom(format!("the key {:?} has no light in the layout", key_name))
+            })? = color;
+        }
+        Ok(Self::new(buffer))
+    }
+}
+
+#[cfg(all(test, feature = "serde-serialize"))]
+mod test {
+    use super::*;
+    use crate::color::ColorRgb;
+
+    #[test]
+    fn serde_keyed() {
+        let layout = LayoutFrCh::new();
+        let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+        for (index, info) in (0_u32..).zip(layout.layout()) {
+            if let Some(color) = buffer.get_key_mut(&layout, *info.key_name()) {
+                *color = ColorRgb::from_u32(index * 0x01_02_03);
+            }
+        }
+        let keyed = KeyedColorBuffer::new(buffer);
+        let string = serde_json::to_string(&keyed).unwrap();
+        assert!(string.starts_with("{\"CapsLock\":\"#000000\",\"Escape\":"));
+        assert!(!string.contains("Wheel"));
+        let back = serde_json::from_str::<KeyedColorBuffer<ColorRgb>>(&string).unwrap();
+        assert_eq!(back, keyed);
+
+        let error = serde_json::from_str::<KeyedColorBuffer<ColorRgb>>("{\"WheelUp\": \"red\"}")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("WheelUp"), "{}", error);
+        let error = serde_json::from_str::<KeyedColorBuffer<ColorRgb>>("{\"NotAKey\": \"red\"}")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("NotAKey"), "{}", error);
+    }
+}
@@ -0,0 +1,702 @@
+//! Parse colors from strings and the serde implementation of [`ColorRgb`] and [`ColorRgba`]
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{
+    de::{self, value::MapAccessDeserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{ColorHsl, ColorRgb, ColorRgba, Hue, Saturation};
+
+/// CSS named colors, sorted by name.
+// values like 0x32_cd_32 are grouped by channel, not a suffix
+#[allow(clippy::mistyped_literal_suffixes)]
+const CSS_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0_f8_ff),
+    ("antiquewhite", 0xfa_eb_d7),
+    ("aqua", 0x00_ff_ff),
+    ("aquamarine", 0x7f_ff_d4),
+    ("azure", 0xf0_ff_ff),
+    ("beige", 0xf5_f5_dc),
+    ("bisque", 0xff_e4_c4),
+    ("black", 0x00_00_00),
+    ("blanchedalmond", 0xff_eb_cd),
+    ("blue", 0x00_00_ff),
+    ("blueviolet", 0x8a_2b_e2),
+    ("brown", 0xa5_2a_2a),
+    ("burlywood", 0xde_b8_87),
+    ("cadetblue", 0x5f_9e_a0),
+    ("chartreuse", 0x7f_ff_00),
+    ("chocolate", 0xd2_69_1e),
+    ("coral", 0xff_7f_50),
+    ("cornflowerblue", 0x64_95_ed),
+    ("cornsilk", 0xff_f8_dc),
+    ("crimson", 0xdc_14_3c),
+    ("cyan", 0x00_ff_ff),
+    ("darkblue", 0x00_00_8b),
+    ("darkcyan", 0x00_8b_8b),
+    ("darkgoldenrod", 0xb8_86_0b),
+    ("darkgray", 0xa9_a9_a9),
+    ("darkgreen", 0x00_64_00),
+    ("darkgrey", 0xa9_a9_a9),
+    ("darkkhaki", 0xbd_b7_6b),
+    ("darkmagenta", 0x8b_00_8b),
+    ("darkolivegreen", 0x55_6b_2f),
+    ("darkorange", 0xff_8c_00),
+    ("darkorchid", 0x99_32_cc),
+    ("darkred", 0x8b_00_00),
+    ("darksalmon", 0xe9_96_7a),
+    ("darkseagreen", 0x8f_bc_8f),
+    ("darkslateblue", 0x48_3d_8b),
+    ("darkslategray", 0x2f_4f_4f),
+    ("darkslategrey", 0x2f_4f_4f),
+    ("darkturquoise", 0x00_ce_d1),
+    ("darkviolet", 0x94_00_d3),
+    ("deeppink", 0xff_14_93),
+    ("deepskyblue", 0x00_bf_ff),
+    ("dimgray", 0x69_69_69),
+    ("dimgrey", 0x69_69_69),
+    ("dodgerblue", 0x1e_90_ff),
+    ("firebrick", 0xb2_22_22),
+    ("floralwhite", 0xff_fa_f0),
+    ("forestgreen", 0x22_8b_22),
+    ("fuchsia", 0xff_00_ff),
+    ("gainsboro", 0xdc_dc_dc),
+    ("ghostwhite", 0xf8_f8_ff),
+    ("gold", 0xff_d7_00),
+    ("goldenrod", 0xda_a5_20),
+    ("gray", 0x80_80_80),
+    ("green", 0x00_80_00),
+    ("greenyellow", 0xad_ff_2f),
+    ("grey", 0x80_80_80),
+    ("honeydew", 0xf0_ff_f0),
+    ("hotpink", 0xff_69_b4),
+    ("indianred", 0xcd_5c_5c),
+    ("indigo", 0x4b_00_82),
+    ("ivory", 0xff_ff_f0),
+    ("khaki", 0xf0_e6_8c),
+    ("lavender", 0xe6_e6_fa),
+    ("lavenderblush", 0xff_f0_f5),
+    ("lawngreen", 0x7c_fc_00),
+    ("lemonchiffon", 0xff_fa_cd),
+    ("lightblue", 0xad_d8_e6),
+    ("lightcoral", 0xf0_80_80),
+    ("lightcyan", 0xe0_ff_ff),
+    ("lightgoldenrodyellow", 0xfa_fa_d2),
+    ("lightgray", 0xd3_d3_d3),
+    ("lightgreen", 0x90_ee_90),
+    ("lightgrey", 0xd3_d3_d3),
+    ("lightpink", 0xff_b6_c1),
+    ("lightsalmon", 0xff_a0_7a),
+    ("lightseagreen", 0x20_b2_aa),
+    ("lightskyblue", 0x87_ce_fa),
+    ("lightslategray", 0x77_88_99),
+    ("lightslategrey", 0x77_88_99),
+    ("lightsteelblue", 0xb0_c4_de),
+    ("lightyellow", 0xff_ff_e0),
+    ("lime", 0x00_ff_00),
+    ("limegreen", 0x32_cd_32),
+    ("linen", 0xfa_f0_e6),
+    ("magenta", 0xff_00_ff),
+    ("maroon", 0x80_00_00),
+    ("mediumaquamarine", 0x66_cd_aa),
+    ("mediumblue", 0x00_00_cd),
+    ("mediumorchid", 0xba_55_d3),
+    ("mediumpurple", 0x93_70_db),
+    ("mediumseagreen", 0x3c_b3_71),
+    ("mediumslateblue", 0x7b_68_ee),
+    ("mediumspringgreen", 0x00_fa_9a),
+    ("mediumturquoise", 0x48_d1_cc),
+    ("mediumvioletred", 0xc7_15_85),
+    ("midnightblue", 0x19_19_70),
+    ("mintcream", 0xf5_ff_fa),
+    ("mistyrose", 0xff_e4_e1),
+    ("moccasin", 0xff_e4_b5),
+    ("navajowhite", 0xff_de_ad),
+    ("navy", 0x00_00_80),
+    ("oldlace", 0xfd_f5_e6),
+    ("olive", 0x80_80_00),
+    ("olivedrab", 0x6b_8e_23),
+    ("orange", 0xff_a5_00),
+    ("orangered", 0xff_45_00),
+    ("orchid", 0xda_70_d6),
+    ("palegoldenrod", 0xee_e8_aa),
+    ("palegreen", 0x98_fb_98),
+    ("paleturquoise", 0xaf_ee_ee),
+    ("palevioletred", 0xdb_70_93),
+    ("papayawhip", 0xff_ef_d5),
+    ("peachpuff", 0xff_da_b9),
+    ("peru", 0xcd_85_3f),
+    ("pink", 0xff_c0_cb),
+    ("plum", 0xdd_a0_dd),
+    ("powderblue", 0xb0_e0_e6),
+    ("purple", 0x80_00_80),
+    ("rebeccapurple", 0x66_33_99),
+    ("red", 0xff_00_00),
+    ("rosybrown", 0xbc_8f_8f),
+    ("royalblue", 0x41_69_e1),
+    ("saddlebrown", 0x8b_45_13),
+    ("salmon", 0xfa_80_72),
+    ("sandybrown", 0xf4_a4_60),
+    ("seagreen", 0x2e_8b_57),
+    ("seashell", 0xff_f5_ee),
+    ("sienna", 0xa0_52_2d),
+    ("silver", 0xc0_c0_c0),
+    ("skyblue", 0x87_ce_eb),
+    ("slateblue", 0x6a_5a_cd),
+    ("slategray", 0x70_80_90),
+    ("slategrey", 0x70_80_90),
+    ("snow", 0xff_fa_fa),
+    ("springgreen", 0x00_ff_7f),
+    ("steelblue", 0x46_82_b4),
+    ("tan", 0xd2_b4_8c),
+    ("teal", 0x00_80_80),
+    ("thistle", 0xd8_bf_d8),
+    ("tomato", 0xff_63_47),
+    ("turquoise", 0x40_e0_d0),
+    ("violet", 0xee_82_ee),
+    ("wheat", 0xf5_de_b3),
+    ("white", 0xff_ff_ff),
+    ("whitesmoke", 0xf5_f5_f5),
+    ("yellow", 0xff_ff_00),
+    ("yellowgreen", 0x9a_cd_32),
+];
+
+/// Error returned when parsing a color from a string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ColorParseError {
+    /// The string is empty
+    Empty,
+    /// The hexadecimal notation is malformed
+    InvalidHex(String),
+    /// The notation has an alpha channel but the color type does not support it
+    UnexpectedAlpha(String),
+    /// The function notation (like `rgb(...)`) is malformed
+    InvalidFunction(String),
+    /// A component is not a number or is out of range
+    InvalidComponent(String),
+    /// The string is not a known notation nor a CSS color name
+    UnknownName(String),
+}
+
+impl Display for ColorParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty color"),
+            Self::InvalidHex(s) => write!(f, "invalid hexadecimal color \"{}\"", s),
+            Self::UnexpectedAlpha(s) => write!(f, "unexpected alpha channel in \"{}\"", s),
+            Self::InvalidFunction(s) => write!(f, "invalid color function \"{}\"", s),
+            Self::InvalidComponent(s) => write!(f, "invalid color component \"{}\"", s),
+            Self::UnknownName(s) => write!(f, "unknown color name \"{}\"", s),
+        }
+    }
+}
+
+impl Error for ColorParseError {}
+
+/// Parse a string as a hexadecimal color, with or without `#`.
+///
+/// Returns the color and the alpha if any, or [`None`] if it is not hexadecimal.
+#[allow(clippy::cast_possible_truncation)]
+fn parse_hex(s: &str) -> Option<Result<(ColorRgb, Option<u8>), ColorParseError>> {
+    let (hex, has_hash) = match s.strip_prefix('#') {
+        Some(hex) => (hex, true),
+        None => (s, false),
+    };
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return has_hash.then(|| Err(ColorParseError::InvalidHex(s.to_owned())));
+    }
+    let number = match u32::from_str_radix(hex, 16) {
+        Ok(number) => number,
+        Err(_) => return Some(Err(ColorParseError::InvalidHex(s.to_owned()))),
+    };
+    // short notation, each digit is doubled
+    let expand = |n: u32| (n & 0xf) as u8 * 0x11;
+    let result = match hex.len() {
+        3 => (
+            ColorRgb::new(expand(number >> 8), expand(number >> 4), expand(number)),
+            None,
+        ),
+        4 => (
+            ColorRgb::new(
+                expand(number >> 12),
+                expand(number >> 8),
+                expand(number >> 4),
+            ),
+            Some(expand(number)),
+        ),
+        6 => (ColorRgb::from_u32(number), None),
+        8 => (ColorRgb::from_u32(number >> 8), Some(number as u8)),
+        _ => return Some(Err(ColorParseError::InvalidHex(s.to_owned()))),
+    };
+    Some(Ok(result))
+}
+
+/// Parse a function notation like `rgb(255, 0, 0)`.
+///
+/// Returns the name and the arguments, or [`None`] if it is not a function.
+fn parse_function(s: &str) -> Option<Result<(&str, Vec<&str>), ColorParseError>> {
+    let open = s.find('(')?;
+    let name = s[..open].trim();
+    let arguments = match s[open + 1..].strip_suffix(')') {
+        Some(arguments) => arguments,
+        None => return Some(Err(ColorParseError::InvalidFunction(s.to_owned()))),
+    };
+    let arguments = arguments
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+    Some(Ok((name, arguments)))
+}
+
+/// Parse a channel between 0 and 255.
+fn parse_channel(s: &str) -> Result<u8, ColorParseError> {
+    s.parse()
+        .map_err(|_| ColorParseError::InvalidComponent(s.to_owned()))
+}
+
+/// Parse a fraction between 0 and 1, also accept a percentage.
+fn parse_unit(s: &str) -> Result<Saturation, ColorParseError> {
+    let value = match s.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().map(|v| v / 100_f64),
+        None => s.parse::<f64>(),
+    };
+    value
+        .ok()
+        .and_then(Saturation::new)
+        .ok_or_else(|| ColorParseError::InvalidComponent(s.to_owned()))
+}
+
+/// Parse a hue in degree, with or without the `deg` suffix.
+fn parse_hue(s: &str) -> Result<Hue, ColorParseError> {
+    s.strip_suffix("deg")
+        .unwrap_or(s)
+        .parse::<f64>()
+        .ok()
+        .filter(|degree| degree.is_finite())
+        .and_then(|degree| {
+            if degree == 360_f64 {
+                Hue::new_from_degree(degree)
+            } else {
+                Hue::new_from_degree(degree.rem_euclid(360_f64))
+            }
+        })
+        .ok_or_else(|| ColorParseError::InvalidComponent(s.to_owned()))
+}
+
+/// Parse any notation, return the color and the alpha if there is one.
+fn parse_color(s: &str) -> Result<(ColorRgb, Option<u8>), ColorParseError> {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        return Err(ColorParseError::Empty);
+    }
+    let lower = trimmed.to_ascii_lowercase();
+    if let Some(result) = parse_hex(&lower) {
+        return result;
+    }
+    if let Some(function) = parse_function(&lower) {
+        let (name, arguments) = function?;
+        let invalid = || ColorParseError::InvalidFunction(trimmed.to_owned());
+        let alpha = match arguments.get(3) {
+            Some(alpha) if arguments.len() == 4 => {
+                Some(ColorRgba::alpha_from_float(parse_unit(alpha)?.value()))
+            }
+            None if arguments.len() == 3 => None,
+            _ => return Err(invalid()),
+        };
+        let color = match name {
+            "rgb" | "rgba" => ColorRgb::new(
+                parse_channel(arguments[0])?,
+                parse_channel(arguments[1])?,
+                parse_channel(arguments[2])?,
+            ),
+            "hsv" | "hsva" => ColorRgb::new_hsv(
+                parse_hue(arguments[0])?,
+                parse_unit(arguments[1])?,
+                parse_unit(arguments[2])?,
+            ),
+            "hsl" | "hsla" => ColorHsl::new(
+                parse_hue(arguments[0])?,
+                parse_unit(arguments[1])?,
+                parse_unit(arguments[2])?,
+            )
+            .into(),
+            _ => return Err(invalid()),
+        };
+        return Ok((color, alpha));
+    }
+    ColorRgb::from_css_name(&lower)
+        .map(|color| (color, None))
+        .ok_or_else(|| ColorParseError::UnknownName(trimmed.to_owned()))
+}
+
+impl ColorRgb {
+    /// Get a color from its CSS name (case insensitive).
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::ColorRgb;
+    ///
+    /// assert_eq!(
+    ///     ColorRgb::from_css_name("RebeccaPurple"),
+    ///     Some(ColorRgb::new(0x66, 0x33, 0x99))
+    /// );
+    /// assert_eq!(ColorRgb::from_css_name("not a color"), None);
+    /// ```
+    pub fn from_css_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        CSS_COLORS
+            .binary_search_by(|(css_name, _)| (*css_name).cmp(name.as_str()))
+            .ok()
+            .map(|index| Self::from_u32(CSS_COLORS[index].1))
+    }
+
+    /// Format the color as `#rrggbb`.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::ColorRgb;
+    ///
+    /// assert_eq!(ColorRgb::new(255, 0, 10).to_hex_string(), "#ff000a");
+    /// ```
+    pub fn to_hex_string(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r(), self.g(), self.b())
+    }
+}
+
+impl FromStr for ColorRgb {
+    type Err = ColorParseError;
+
+    /// Parse a color from `#ff00ff`, `ff00ff`, `#f0f`, `rgb(255, 0, 255)`,
+    /// `hsv(300, 1, 1)`, `hsl(300, 100%, 50%)` or a CSS name like `rebeccapurple`.
+    /// The hue is in degree, the other components of hsv and hsl are fractions or percentages.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{ColorParseError, ColorRgb};
+    ///
+    /// let magenta = ColorRgb::new(255, 0, 255);
+    /// assert_eq!("#ff00ff".parse(), Ok(magenta));
+    /// assert_eq!("FF00FF".parse(), Ok(magenta));
+    /// assert_eq!("rgb(255, 0, 255)".parse(), Ok(magenta));
+    /// assert_eq!("hsv(300, 1, 1)".parse(), Ok(magenta));
+    /// assert_eq!("fuchsia".parse(), Ok(magenta));
+    /// assert_eq!(
+    ///     "#ff00ff80".parse::<ColorRgb>(),
+    ///     Err(ColorParseError::UnexpectedAlpha("#ff00ff80".to_owned()))
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_color(s)? {
+            (color, None) => Ok(color),
+            (_, Some(_)) => Err(ColorParseError::UnexpectedAlpha(s.trim().to_owned())),
+        }
+    }
+}
+
+impl ColorRgba {
+    /// Convert an alpha between 0 and 1 into a [`u8`]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn alpha_from_float(alpha: f64) -> u8 {
+        // the cast is safe
+        (alpha.clamp(0_f64, 1_f64) * 255_f64).round() as u8
+    }
+
+    /// Format the color as `#rrggbbaa`.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::ColorRgba;
+    ///
+    /// assert_eq!(ColorRgba::new(255, 0, 10, 128).to_hex_string(), "#ff000a80");
+    /// ```
+    pub fn to_hex_string(self) -> String {
+        format!("{}{:02x}", self.color().to_hex_string(), self.alpha())
+    }
+}
+
+impl FromStr for ColorRgba {
+    type Err = ColorParseError;
+
+    /// Parse the same notations as [`ColorRgb`] plus `#ff00ff80`, `ff00ff80`, `#f0f8`
+    /// and a fourth argument for the functions like `rgba(255, 0, 255, 0.5)`.
+    /// Without alpha channel the color is opaque.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::ColorRgba;
+    ///
+    /// assert_eq!("#ff00ff80".parse(), Ok(ColorRgba::new(255, 0, 255, 128)));
+    /// assert_eq!(
+    ///     "rgba(255, 0, 255, 0.5)".parse(),
+    ///     Ok(ColorRgba::new(255, 0, 255, 128))
+    /// );
+    /// assert_eq!("red".parse(), Ok(ColorRgba::new(255, 0, 0, 255)));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (color, alpha) = parse_color(s)?;
+        Ok(Self::new(
+            color.r(),
+            color.g(),
+            color.b(),
+            alpha.unwrap_or(u8::MAX),
+        ))
+    }
+}
+
+/// Struct form of [`ColorRgb`] accepted by the deserializer.
+#[cfg(feature = "serde-serialize")]
+#[derive(Deserialize)]
+struct ColorRgbFields {
+    /// Red
+    r: u8,
+    /// Green
+    g: u8,
+    /// Blue
+    b: u8,
+}
+
+/// Struct form of [`ColorRgba`] accepted by the deserializer.
+#[cfg(feature = "serde-serialize")]
+#[derive(Deserialize)]
+struct ColorRgbaFields {
+    /// base color
+    color: ColorRgb,
+    /// opacity
+    alpha: u8,
+}
+
+/// Visitor accepting the string and the struct forms of a color.
+#[cfg(feature = "serde-serialize")]
+struct ColorVisitor<T>(std::marker::PhantomData<T>);
+
+#[cfg(feature = "serde-serialize")]
+impl<'de> Visitor<'de> for ColorVisitor<ColorRgb> {
+    type Value = ColorRgb;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a color string like \"#ff00ff\" or a struct {{r, g, b}}")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let fields = ColorRgbFields::deserialize(MapAccessDeserializer::new(map))?;
+        Ok(ColorRgb::new(fields.r, fields.g, fields.b))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut next = |index| {
+            seq.next_element()?
+                .ok_or_else(|| de::Error::invalid_length(index, &self))
+        };
+        Ok(ColorRgb::new(next(0)?, next(1)?, next(2)?))
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<'de> Visitor<'de> for ColorVisitor<ColorRgba> {
+    type Value = ColorRgba;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "a color string like \"#ff00ff80\" or a struct {{color, alpha}}"
+        )
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let fields = ColorRgbaFields::deserialize(MapAccessDeserializer::new(map))?;
+        let color = fields.color;
+        Ok(ColorRgba::new(
+            color.r(),
+            color.g(),
+            color.b(),
+            fields.alpha,
+        ))
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl Serialize for ColorRgb {
+    /// Serialize as `"#rrggbb"`
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex_string())
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<'de> Deserialize<'de> for ColorRgb {
+    /// Deserialize from any string accepted by [`FromStr`], from the struct form `{r, g, b}`
+    /// or from a sequence `[r, g, b]`.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ColorVisitor::<Self>(std::marker::PhantomData))
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl Serialize for ColorRgba {
+    /// Serialize as `"#rrggbbaa"`
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex_string())
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<'de> Deserialize<'de> for ColorRgba {
+    /// Deserialize from any string accepted by [`FromStr`] or from the struct form
+    /// `{color, alpha}`.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ColorVisitor::<Self>(std::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn css_names_sorted() {
+        assert!(CSS_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+        for (name, value) in CSS_COLORS {
+            assert_eq!(name.parse::<ColorRgb>(), Ok(ColorRgb::from_u32(value)));
+        }
+    }
+
+    #[test]
+    fn parse_rgb() {
+        let expected = ColorRgb::new(0x12, 0xab, 0xef);
+        for s in [
+            "#12abef",
+            "12abef",
+            "#12ABEF",
+            "  #12abef ",
+            "rgb(18, 171, 239)",
+            "rgb(18 171 239)",
+            "RGB(18,171,239)",
+        ] {
+            assert_eq!(s.parse::<ColorRgb>(), Ok(expected), "{}", s);
+        }
+        assert_eq!("#fa0".parse(), Ok(ColorRgb::new(0xff, 0xaa, 0x00)));
+        assert_eq!("hsv(120, 1, 0.5)".parse(), Ok(ColorRgb::new(0, 128, 0)));
+        assert_eq!(
+            "hsv(480deg, 100%, 50%)".parse(),
+            Ok(ColorRgb::new(0, 128, 0))
+        );
+        assert_eq!("hsv(360, 1, 1)".parse(), Ok(ColorRgb::new(255, 0, 0)));
+        assert_eq!("hsl(120, 1, 0.25)".parse(), Ok(ColorRgb::new(0, 128, 0)));
+        assert_eq!("Red".parse(), Ok(ColorRgb::new(255, 0, 0)));
+        // "add" is valid hexadecimal
+        assert_eq!("add".parse(), Ok(ColorRgb::new(0xaa, 0xdd, 0xdd)));
+    }
+
+    #[test]
+    fn parse_rgba() {
+        let expected = ColorRgba::new(0x12, 0xab, 0xef, 0x80);
+        for s in [
+            "#12abef80",
+            "12abef80",
+            "rgba(18, 171, 239, 0.5)",
+            "rgb(18 171 239 / 50%)",
+        ] {
+            assert_eq!(s.parse::<ColorRgba>(), Ok(expected), "{}", s);
+        }
+        assert_eq!("#fa08".parse(), Ok(ColorRgba::new(0xff, 0xaa, 0x00, 0x88)));
+        assert_eq!("#12abef".parse(), Ok(ColorRgba::new(0x12, 0xab, 0xef, 255)));
+        assert_eq!("hsva(0, 1, 1, 0)".parse(), Ok(ColorRgba::new(255, 0, 0, 0)));
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!("".parse::<ColorRgb>(), Err(ColorParseError::Empty));
+        assert_eq!("   ".parse::<ColorRgba>(), Err(ColorParseError::Empty));
+        assert_eq!(
+            "#12abe".parse::<ColorRgb>(),
+            Err(ColorParseError::InvalidHex("#12abe".to_owned()))
+        );
+        assert_eq!(
+            "#12abzz".parse::<ColorRgb>(),
+            Err(ColorParseError::InvalidHex("#12abzz".to_owned()))
+        );
+        assert_eq!(
+            "12abef80".parse::<ColorRgb>(),
+            Err(ColorParseError::UnexpectedAlpha("12abef80".to_owned()))
+        );
+        assert_eq!(
+            "rgb(256, 0, 0)".parse::<ColorRgb>(),
+            Err(ColorParseError::InvalidComponent("256".to_owned()))
+        );
+        assert_eq!(
+            "rgb(255, 0)".parse::<ColorRgb>(),
+            Err(ColorParseError::InvalidFunction("rgb(255, 0)".to_owned()))
+        );
+        assert_eq!(
+            "rgb(255, 0, 0".parse::<ColorRgb>(),
+            Err(ColorParseError::InvalidFunction("rgb(255, 0, 0".to_owned()))
+        );
+        assert_eq!(
+            "cmyk(0, 0, 0)".parse::<ColorRgb>(),
+            Err(ColorParseError::InvalidFunction("cmyk(0, 0, 0)".to_owned()))
+        );
+        assert_eq!(
+            "hsv(0, 2, 1)".parse::<ColorRgb>(),
+            Err(ColorParseError::InvalidComponent("2".to_owned()))
+        );
+        assert_eq!(
+            "Not A Color".parse::<ColorRgb>(),
+            Err(ColorParseError::UnknownName("Not A Color".to_owned()))
+        );
+    }
+
+    #[test]
+    fn format_round_trip() {
+        for value in (0..=0xff_ff_ff_u32).step_by(0x01_03_07) {
+            let color = ColorRgb::from_u32(value);
+            assert_eq!(color.to_hex_string().parse(), Ok(color));
+            let color = ColorRgba::new(color.r(), color.g(), color.b(), color.r() ^ 0x5a);
+            assert_eq!(color.to_hex_string().parse(), Ok(color));
+        }
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn serde() {
+        let color = ColorRgb::new(255, 0, 255);
+        assert_eq!(serde_json::to_string(&color).unwrap(), "\"#ff00ff\"");
+        assert_eq!(
+            serde_json::from_str::<ColorRgb>("\"rebeccapurple\"").unwrap(),
+            ColorRgb::new(0x66, 0x33, 0x99)
+        );
+        assert_eq!(
+            serde_json::from_str::<ColorRgb>("{\"r\": 255, \"g\": 0, \"b\": 255}").unwrap(),
+            color
+        );
+        assert_eq!(
+            serde_json::from_str::<ColorRgb>("[255, 0, 255]").unwrap(),
+            color
+        );
+        assert!(serde_json::from_str::<ColorRgb>("\"#ff00ff80\"").is_err());
+        assert!(serde_json::from_str::<ColorRgb>("{\"r\": 255}").is_err());
+
+        let color = ColorRgba::new(255, 0, 255, 128);
+        assert_eq!(serde_json::to_string(&color).unwrap(), "\"#ff00ff80\"");
+        assert_eq!(
+            serde_json::from_str::<ColorRgba>("\"#ff00ff80\"").unwrap(),
+            color
+        );
+        assert_eq!(
+            serde_json::from_str::<ColorRgba>(
+                "{\"color\": {\"r\": 255, \"g\": 0, \"b\": 255}, \"alpha\": 128}"
+            )
+            .unwrap(),
+            color
+        );
+    }
+}
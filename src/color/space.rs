@@ -0,0 +1,687 @@
+//! Contains the color spaces other than RGB:
+//! [`ColorHsl`], [`ColorLinearRgb`], [`ColorOklab`] and [`ColorOklch`]
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use super::{ColorRgb, Hue, Saturation, Value};
+
+/// Color lightness. Gauranties that the value is between 0 and 1.
+///
+/// Used by [`ColorHsl`], [`ColorOklab`] and [`ColorOklch`].
+///
+/// # Examlpe
+/// ```
+/// use roccat_vulcan_api_rs::Lightness;
+///
+/// assert_eq!(Lightness::new(0.5_f64).unwrap().value(), 0.5_f64);
+/// assert_eq!(Lightness::new(1.5_f64), None);
+/// ```
+pub type Lightness = Saturation;
+
+/// Chroma of a [`ColorOklch`]. Gauranties that the value is finite and positive.
+///
+/// # Examlpe
+/// ```
+/// use roccat_vulcan_api_rs::Chroma;
+///
+/// assert_eq!(Chroma::new(0.1_f64).unwrap().value(), 0.1_f64);
+/// assert_eq!(Chroma::new(-0.1_f64), None);
+/// assert_eq!(Chroma::new(f64::NAN), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chroma(f64);
+
+impl Chroma {
+    /// Create a new value if `c` is finite and positive. Otherwise return [`None`]
+    pub fn new(c: f64) -> Option<Self> {
+        if c.is_finite() && c >= 0_f64 {
+            Some(Self(c))
+        } else {
+            None
+        }
+    }
+
+    /// Get the value warpped
+    pub const fn value(self) -> f64 {
+        self.0
+    }
+}
+
+impl Eq for Chroma {}
+
+impl PartialOrd for Chroma {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Chroma {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.0.partial_cmp(&other.0) {
+            Some(ord) => ord,
+            None => unreachable!(),
+        }
+    }
+}
+
+impl Display for Chroma {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Default for Chroma {
+    fn default() -> Self {
+        Self(0_f64)
+    }
+}
+
+/// Clamp a value to a [`Saturation`] (or any alias like [`Value`] and [`Lightness`]).
+///
+/// `NaN` is mapped to 0.
+pub(super) fn unit(v: f64) -> Saturation {
+    let v = if v.is_nan() {
+        0_f64
+    } else {
+        v.clamp(0_f64, 1_f64)
+    };
+    match Saturation::new(v) {
+        Some(s) => s,
+        None => unreachable!(),
+    }
+}
+
+/// Create a [`Hue`] from any value, wrapping it between 0 and 1.
+pub(super) fn wrap_hue(h: f64) -> Hue {
+    let h = if h.is_finite() {
+        h.rem_euclid(1_f64)
+    } else {
+        0_f64
+    };
+    match Hue::new(h) {
+        Some(hue) => hue,
+        None => unreachable!(),
+    }
+}
+
+/// Compute `row . v` using fused multiply add.
+fn dot(row: [f64; 3], v: [f64; 3]) -> f64 {
+    row[0].mul_add(v[0], row[1].mul_add(v[1], row[2] * v[2]))
+}
+
+/// Hue, saturation and lightness representation of a color.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{ColorHsl, ColorRgb, Hue, Lightness, Saturation};
+///
+/// let hsl = ColorHsl::new(
+///     Hue::new_from_degree(120_f64).unwrap(),
+///     Saturation::new(1_f64).unwrap(),
+///     Lightness::new(0.5_f64).unwrap(),
+/// );
+/// assert_eq!(ColorRgb::from(hsl), ColorRgb::new(0, 255, 0));
+/// let color = ColorRgb::new(12, 200, 87);
+/// assert_eq!(ColorRgb::from(ColorHsl::from(color)), color);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct ColorHsl {
+    /// Hue
+    hue: Hue,
+    /// Saturation
+    saturation: Saturation,
+    /// Lightness
+    lightness: Lightness,
+}
+
+impl ColorHsl {
+    /// Create a new HSL color
+    pub const fn new(hue: Hue, saturation: Saturation, lightness: Lightness) -> Self {
+        Self {
+            hue,
+            saturation,
+            lightness,
+        }
+    }
+
+    /// Get the hue
+    pub const fn hue(self) -> Hue {
+        self.hue
+    }
+
+    /// Get the saturation
+    pub const fn saturation(self) -> Saturation {
+        self.saturation
+    }
+
+    /// Get the lightness
+    pub const fn lightness(self) -> Lightness {
+        self.lightness
+    }
+
+    /// Get the hue as mutable
+    pub const fn hue_mut(&mut self) -> &mut Hue {
+        &mut self.hue
+    }
+
+    /// Get the saturation as mutable
+    pub const fn saturation_mut(&mut self) -> &mut Saturation {
+        &mut self.saturation
+    }
+
+    /// Get the lightness as mutable
+    pub const fn lightness_mut(&mut self) -> &mut Lightness {
+        &mut self.lightness
+    }
+}
+
+impl From<ColorRgb> for ColorHsl {
+    fn from(color: ColorRgb) -> Self {
+        let (hue, _, _) = color.into_hsv();
+        let max = f64::from(color.r().max(color.g()).max(color.b())) / 255_f64;
+        let min = f64::from(color.r().min(color.g()).min(color.b())) / 255_f64;
+        let lightness = (max + min) / 2_f64;
+        let delta = max - min;
+        let saturation = if delta == 0_f64 {
+            0_f64
+        } else {
+            delta / (1_f64 - 2_f64.mul_add(lightness, -1_f64).abs())
+        };
+        Self::new(hue, unit(saturation), unit(lightness))
+    }
+}
+
+impl From<ColorHsl> for ColorRgb {
+    fn from(color: ColorHsl) -> Self {
+        let lightness = color.lightness().value();
+        let chroma = (1_f64 - 2_f64.mul_add(lightness, -1_f64).abs()) * color.saturation().value();
+        let value = lightness + chroma / 2_f64;
+        let saturation_v = if value == 0_f64 {
+            0_f64
+        } else {
+            chroma / value
+        };
+        Self::new_hsv(color.hue(), unit(saturation_v), unit(value))
+    }
+}
+
+impl Display for ColorHsl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "(h: {}, s: {}, l: {})",
+            self.hue(),
+            self.saturation(),
+            self.lightness()
+        )
+    }
+}
+
+/// Linear sRGB color, i.e. sRGB without the transfer function. Each channel is between 0 and 1.
+///
+/// Mixing light should be done in this space.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{ColorLinearRgb, ColorRgb};
+///
+/// let linear = ColorLinearRgb::from(ColorRgb::new(255, 128, 0));
+/// assert_eq!(linear.r().value(), 1_f64);
+/// assert!(linear.g().value() < 0.5_f64);
+/// assert_eq!(ColorRgb::from(linear), ColorRgb::new(255, 128, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct ColorLinearRgb {
+    /// Red
+    r: Value,
+    /// Green
+    g: Value,
+    /// Blue
+    b: Value,
+}
+
+impl ColorLinearRgb {
+    /// Create a new linear color
+    pub const fn new(r: Value, g: Value, b: Value) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Get the red intensity
+    pub const fn r(self) -> Value {
+        self.r
+    }
+
+    /// Get the green intensity
+    pub const fn g(self) -> Value {
+        self.g
+    }
+
+    /// Get the blue intensity
+    pub const fn b(self) -> Value {
+        self.b
+    }
+
+    /// Get the red intensity as mutable
+    pub const fn r_mut(&mut self) -> &mut Value {
+        &mut self.r
+    }
+
+    /// Get the green intensity as mutable
+    pub const fn g_mut(&mut self) -> &mut Value {
+        &mut self.g
+    }
+
+    /// Get the blue intensity as mutable
+    pub const fn b_mut(&mut self) -> &mut Value {
+        &mut self.b
+    }
+
+    /// Get the channels as an array `[r, g, b]`
+    const fn into_array(self) -> [f64; 3] {
+        [self.r.value(), self.g.value(), self.b.value()]
+    }
+
+    /// Create a color from an array, values out of bounds are clamped
+    fn from_array_clamped(array: [f64; 3]) -> Self {
+        Self::new(unit(array[0]), unit(array[1]), unit(array[2]))
+    }
+
+    /// Apply the inverse of the sRGB transfer function
+    fn decode(c: u8) -> f64 {
+        let c = f64::from(c) / 255_f64;
+        if c <= 0.040_45_f64 {
+            c / 12.92_f64
+        } else {
+            ((c + 0.055_f64) / 1.055_f64).powf(2.4_f64)
+        }
+    }
+
+    /// Apply the sRGB transfer function
+    fn encode(l: f64) -> f64 {
+        if l <= 0.003_130_8_f64 {
+            l * 12.92_f64
+        } else {
+            1.055_f64.mul_add(l.powf(1_f64 / 2.4_f64), -0.055_f64)
+        }
+    }
+}
+
+impl From<ColorRgb> for ColorLinearRgb {
+    fn from(color: ColorRgb) -> Self {
+        Self::from_array_clamped([
+            Self::decode(color.r()),
+            Self::decode(color.g()),
+            Self::decode(color.b()),
+        ])
+    }
+}
+
+impl From<ColorLinearRgb> for ColorRgb {
+    fn from(color: ColorLinearRgb) -> Self {
+        let [r, g, b] = color.into_array();
+        Self::new(
+            Self::convert_to_u8(ColorLinearRgb::encode(r)),
+            Self::convert_to_u8(ColorLinearRgb::encode(g)),
+            Self::convert_to_u8(ColorLinearRgb::encode(b)),
+        )
+    }
+}
+
+impl Display for ColorLinearRgb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(r: {}, g: {}, b: {})", self.r(), self.g(), self.b())
+    }
+}
+
+/// Matrix from linear sRGB to LMS.
+const LINEAR_TO_LMS: [[f64; 3]; 3] = [
+    [
+        0.412_221_470_8_f64,
+        0.536_332_536_3_f64,
+        0.051_445_992_9_f64,
+    ],
+    [
+        0.211_903_498_2_f64,
+        0.680_699_545_1_f64,
+        0.107_396_956_6_f64,
+    ],
+    [
+        0.088_302_461_9_f64,
+        0.281_718_837_6_f64,
+        0.629_978_700_5_f64,
+    ],
+];
+/// Matrix from non linear LMS to OKLab.
+const LMS_TO_OKLAB: [[f64; 3]; 3] = [
+    [
+        0.210_454_255_3_f64,
+        0.793_617_785_0_f64,
+        -0.004_072_046_8_f64,
+    ],
+    [
+        1.977_998_495_1_f64,
+        -2.428_592_205_0_f64,
+        0.450_593_709_9_f64,
+    ],
+    [
+        0.025_904_037_1_f64,
+        0.782_771_766_2_f64,
+        -0.808_675_766_0_f64,
+    ],
+];
+/// Matrix from OKLab to non linear LMS.
+const OKLAB_TO_LMS: [[f64; 3]; 3] = [
+    [1_f64, 0.396_337_777_4_f64, 0.215_803_757_3_f64],
+    [1_f64, -0.105_561_345_8_f64, -0.063_854_172_8_f64],
+    [1_f64, -0.089_484_177_5_f64, -1.291_485_548_0_f64],
+];
+/// Matrix from LMS to linear sRGB.
+const LMS_TO_LINEAR: [[f64; 3]; 3] = [
+    [
+        4.076_741_662_1_f64,
+        -3.307_711_591_3_f64,
+        0.230_969_929_2_f64,
+    ],
+    [
+        -1.268_438_004_6_f64,
+        2.609_757_401_1_f64,
+        -0.341_319_396_5_f64,
+    ],
+    [
+        -0.004_196_086_3_f64,
+        -0.703_418_614_7_f64,
+        1.707_614_701_0_f64,
+    ],
+];
+
+/// Perceptual OKLab color.
+///
+/// Distances and interpolations in this space match the perceived difference between colors.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{ColorOklab, ColorRgb};
+///
+/// let white = ColorOklab::from(ColorRgb::new(255, 255, 255));
+/// assert!((white.l().value() - 1_f64).abs() < 1E-6_f64);
+/// assert!(white.a().abs() < 1E-6_f64);
+/// let color = ColorRgb::new(100, 20, 250);
+/// assert_eq!(ColorRgb::from(ColorOklab::from(color)), color);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ColorOklab {
+    /// Perceived lightness
+    l: Lightness,
+    /// Green-red axis
+    a: f64,
+    /// Blue-yellow axis
+    b: f64,
+}
+
+impl ColorOklab {
+    /// Create a new OKLab color if `a` and `b` are finite. Otherwise return [`None`]
+    pub const fn new(l: Lightness, a: f64, b: f64) -> Option<Self> {
+        if a.is_finite() && b.is_finite() {
+            Some(Self { l, a, b })
+        } else {
+            None
+        }
+    }
+
+    /// Get the perceived lightness
+    pub const fn l(self) -> Lightness {
+        self.l
+    }
+
+    /// Get the green-red axis
+    pub const fn a(self) -> f64 {
+        self.a
+    }
+
+    /// Get the blue-yellow axis
+    pub const fn b(self) -> f64 {
+        self.b
+    }
+}
+
+impl From<ColorLinearRgb> for ColorOklab {
+    fn from(color: ColorLinearRgb) -> Self {
+        let linear = color.into_array();
+        let lms = [
+            dot(LINEAR_TO_LMS[0], linear).cbrt(),
+            dot(LINEAR_TO_LMS[1], linear).cbrt(),
+            dot(LINEAR_TO_LMS[2], linear).cbrt(),
+        ];
+        Self {
+            l: unit(dot(LMS_TO_OKLAB[0], lms)),
+            a: dot(LMS_TO_OKLAB[1], lms),
+            b: dot(LMS_TO_OKLAB[2], lms),
+        }
+    }
+}
+
+impl From<ColorOklab> for ColorLinearRgb {
+    fn from(color: ColorOklab) -> Self {
+        let lab = [color.l.value(), color.a, color.b];
+        let lms = [
+            dot(OKLAB_TO_LMS[0], lab).powi(3),
+            dot(OKLAB_TO_LMS[1], lab).powi(3),
+            dot(OKLAB_TO_LMS[2], lab).powi(3),
+        ];
+        // out of gamut colors are clamped
+        Self::from_array_clamped([
+            dot(LMS_TO_LINEAR[0], lms),
+            dot(LMS_TO_LINEAR[1], lms),
+            dot(LMS_TO_LINEAR[2], lms),
+        ])
+    }
+}
+
+impl From<ColorRgb> for ColorOklab {
+    fn from(color: ColorRgb) -> Self {
+        ColorLinearRgb::from(color).into()
+    }
+}
+
+impl From<ColorOklab> for ColorRgb {
+    fn from(color: ColorOklab) -> Self {
+        ColorLinearRgb::from(color).into()
+    }
+}
+
+impl Display for ColorOklab {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(l: {}, a: {}, b: {})", self.l(), self.a(), self.b())
+    }
+}
+
+/// Perceptual OKLCH color, the polar form of [`ColorOklab`].
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{Chroma, ColorOklch, ColorRgb, Hue, Lightness};
+///
+/// let color = ColorRgb::new(255, 0, 0);
+/// let lch = ColorOklch::from(color);
+/// assert!(lch.chroma().value() > 0.2_f64);
+/// assert_eq!(ColorRgb::from(lch), color);
+///
+/// let gray = ColorOklch::new(
+///     Lightness::new(0.5_f64).unwrap(),
+///     Chroma::new(0_f64).unwrap(),
+///     Hue::new(0.3_f64).unwrap(),
+/// );
+/// let gray = ColorRgb::from(gray);
+/// assert!(gray.r() == gray.g() && gray.g() == gray.b());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct ColorOklch {
+    /// Perceived lightness
+    l: Lightness,
+    /// Chroma
+    chroma: Chroma,
+    /// Hue
+    hue: Hue,
+}
+
+impl ColorOklch {
+    /// Create a new OKLCH color
+    pub const fn new(l: Lightness, chroma: Chroma, hue: Hue) -> Self {
+        Self { l, chroma, hue }
+    }
+
+    /// Get the perceived lightness
+    pub const fn l(self) -> Lightness {
+        self.l
+    }
+
+    /// Get the chroma
+    pub const fn chroma(self) -> Chroma {
+        self.chroma
+    }
+
+    /// Get the hue
+    pub const fn hue(self) -> Hue {
+        self.hue
+    }
+
+    /// Get the perceived lightness as mutable
+    pub const fn l_mut(&mut self) -> &mut Lightness {
+        &mut self.l
+    }
+
+    /// Get the chroma as mutable
+    pub const fn chroma_mut(&mut self) -> &mut Chroma {
+        &mut self.chroma
+    }
+
+    /// Get the hue as mutable
+    pub const fn hue_mut(&mut self) -> &mut Hue {
+        &mut self.hue
+    }
+}
+
+impl From<ColorOklab> for ColorOklch {
+    fn from(color: ColorOklab) -> Self {
+        let chroma = color.a().hypot(color.b());
+        let hue = wrap_hue(color.b().atan2(color.a()) / (2_f64 * std::f64::consts::PI));
+        Self::new(color.l(), Chroma::new(chroma).unwrap_or_default(), hue)
+    }
+}
+
+impl From<ColorOklch> for ColorOklab {
+    fn from(color: ColorOklch) -> Self {
+        let (sin, cos) = color.hue().radiant().sin_cos();
+        Self {
+            l: color.l(),
+            a: color.chroma().value() * cos,
+            b: color.chroma().value() * sin,
+        }
+    }
+}
+
+impl From<ColorRgb> for ColorOklch {
+    fn from(color: ColorRgb) -> Self {
+        ColorOklab::from(color).into()
+    }
+}
+
+impl From<ColorOklch> for ColorRgb {
+    fn from(color: ColorOklch) -> Self {
+        ColorOklab::from(color).into()
+    }
+}
+
+impl Display for ColorOklch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "(l: {}, c: {}, h: {})",
+            self.l(),
+            self.chroma(),
+            self.hue()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_abs_diff_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    /// Number of random colors tested by each property test
+    const SAMPLES: usize = 20_000;
+
+    /// Iterate on random colors and every gray.
+    fn colors() -> impl Iterator<Item = ColorRgb> {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        (0..SAMPLES)
+            .map(move |_| ColorRgb::new(rng.gen(), rng.gen(), rng.gen()))
+            .chain((0..=255_u8).map(|v| ColorRgb::new(v, v, v)))
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        for color in colors() {
+            let (h, s, v) = color.into_hsv();
+            assert_eq!(ColorRgb::new_hsv(h, s, v), color);
+        }
+    }
+
+    #[test]
+    fn hsl_round_trip() {
+        for color in colors() {
+            assert_eq!(ColorRgb::from(ColorHsl::from(color)), color, "{}", color);
+        }
+    }
+
+    #[test]
+    fn linear_round_trip() {
+        for color in colors() {
+            assert_eq!(ColorRgb::from(ColorLinearRgb::from(color)), color);
+        }
+    }
+
+    #[test]
+    fn oklab_round_trip() {
+        for color in colors() {
+            let lab = ColorOklab::from(color);
+            assert_eq!(ColorRgb::from(lab), color);
+            let lch = ColorOklch::from(lab);
+            assert_eq!(ColorRgb::from(lch), color);
+            let back = ColorOklab::from(lch);
+            assert_abs_diff_eq!(back.a(), lab.a(), epsilon = 1E-9_f64);
+            assert_abs_diff_eq!(back.b(), lab.b(), epsilon = 1E-9_f64);
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        let hsl = ColorHsl::from(ColorRgb::new(255, 0, 0));
+        assert_abs_diff_eq!(hsl.hue().value(), 0_f64);
+        assert_abs_diff_eq!(hsl.saturation().value(), 1_f64);
+        assert_abs_diff_eq!(hsl.lightness().value(), 0.5_f64);
+
+        let linear = ColorLinearRgb::from(ColorRgb::new(0, 128, 255));
+        assert_abs_diff_eq!(linear.r().value(), 0_f64);
+        assert_abs_diff_eq!(linear.g().value(), 0.215_861_f64, epsilon = 1E-6_f64);
+        assert_abs_diff_eq!(linear.b().value(), 1_f64);
+
+        // reference values from https://bottosson.github.io/posts/oklab/
+        let lab = ColorOklab::from(ColorRgb::new(255, 0, 0));
+        assert_abs_diff_eq!(lab.l().value(), 0.627_955_f64, epsilon = 1E-4_f64);
+        assert_abs_diff_eq!(lab.a(), 0.224_863_f64, epsilon = 1E-4_f64);
+        assert_abs_diff_eq!(lab.b(), 0.125_846_f64, epsilon = 1E-4_f64);
+
+        let lab = ColorOklab::from(ColorRgb::new(0, 0, 0));
+        assert_abs_diff_eq!(lab.l().value(), 0_f64);
+        assert_eq!(ColorOklab::new(lab.l(), f64::NAN, 0_f64), None);
+    }
+
+    #[test]
+    fn out_of_gamut() {
+        let lab = ColorOklab::new(Lightness::new(1_f64).unwrap(), 0.4_f64, -0.4_f64).unwrap();
+        let color = ColorRgb::from(lab);
+        assert_eq!(color.r(), 255);
+        assert_eq!(color.b(), 255);
+    }
+}
@@ -0,0 +1,20 @@
+//! Daemon owning the keyboard and sharing it between several programs.
+//!
+//! Only one process can open the devices of the keyboard. The [`Daemon`] holds the
+//! [`crate::KeyboardApi`] and listens on a Unix domain socket. Each client draws on its own
+//! layer and the layers are composited by priority.
+//!
+//! The clients exchange JSON lines with the daemon, the protocol is described on [`Daemon`].
+//! [`DaemonClient`] implements it for Rust programs.
+
+mod client;
+pub use client::*;
+
+mod error;
+pub use error::*;
+
+mod protocol;
+pub use protocol::*;
+
+mod server;
+pub use server::*;
@@ -0,0 +1,454 @@
+//! Contains [`DaemonClient`]
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{default_socket_path, ClientMessage, DaemonMessage};
+use crate::{
+    color::{ColorBuffer, ColorCorrection, ColorRgb},
+    error::ErrorRoccatVulcanApi,
+    keyboard::{ControlerFeatureKind, Keyboard},
+    layout::{KeyPress, Layout, LayoutKind},
+    profile::KeyTarget,
+};
+
+/// Result returned by the client
+type Res<T> = Result<T, ErrorRoccatVulcanApi>;
+
+/// Maximum time waiting for the reply of the daemon
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Connection to a [`super::Daemon`], with the same methods as [`crate::KeyboardApi`].
+///
+/// The client draws on its own layer of the daemon, the layer is removed when the client
+/// is dropped. Both implement [`Keyboard`] so an application can use the daemon or the
+/// keyboard directly by changing only the constructor.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{ColorBuffer, ColorRgb, DaemonClient, ErrorRoccatVulcanApi};
+///
+/// # fn main() -> Result<(), ErrorRoccatVulcanApi> {
+/// # #[cfg(not(feature = "no-keyboard-test"))]
+/// # {
+/// let keyboard = DaemonClient::new()?;
+/// keyboard.hello("example", 1)?;
+/// keyboard.render(&ColorBuffer::from_element(ColorRgb::new(255, 255, 255)))?;
+/// println!("{:?}", keyboard.read_key_press(Duration::from_millis(400))?);
+/// # }
+/// # Ok(())
+/// # }
+/// ```
+pub struct DaemonClient {
+    /// Connection to the daemon
+    stream: UnixStream,
+    /// Replies of the daemon, read by a thread
+    replies: Receiver<DaemonMessage>,
+    /// Number of requests that timed out, their replies are skipped when they arrive
+    late_replies: Cell<usize>,
+    /// Key presses sent by the daemon, read by a thread
+    key_presses: Receiver<KeyPress>,
+    /// Whether the key presses were requested
+    subscribed: Cell<bool>,
+    /// Correction applied on the colors when rendering
+    color_correction: ColorCorrection,
+    /// Layout used to name the keys sent to the daemon
+    layout: &'static dyn Layout,
+}
+
+impl DaemonClient {
+    /// Connect to the daemon listening on [`default_socket_path`]
+    /// # Errors
+    /// [`ErrorRoccatVulcanApi::Io`] if the daemon cannot be reached
+    pub fn new() -> Res<Self> {
+        Self::connect(default_socket_path())
+    }
+
+    /// Connect to the daemon listening on the socket `path`
+    /// # Errors
+    /// [`ErrorRoccatVulcanApi::Io`] if the daemon cannot be reached
+    pub fn connect(path: impl AsRef<Path>) -> Res<Self> {
+        let stream = UnixStream::connect(path)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (reply_sender, replies) = mpsc::channel();
+        let (key_press_sender, key_presses) = mpsc::channel();
+        thread::Builder::new()
+            .name("daemon client".to_owned())
+            .spawn(move || {
+                for line in reader.lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => return,
+                    };
+                    // the messages that are not understood come from a newer daemon
+                    let sent = match serde_json::from_str(&line) {
+                        Ok(DaemonMessage::KeyPress {
+                            key_code, pressed, ..
+                        }) => key_press_sender
+                            .send(KeyPress::new(key_code, pressed))
+                            .is_ok(),
+                        Ok(message) => reply_sender.send(message).is_ok(),
+                        Err(_) => true,
+                    };
+                    if !sent {
+                        return;
+                    }
+                }
+            })?;
+        Ok(Self {
+            stream,
+            replies,
+            late_replies: Cell::new(0),
+            key_presses,
+            subscribed: Cell::new(false),
+            color_correction: ColorCorrection::new(),
+            layout: LayoutKind::default().layout(),
+        })
+    }
+
+    /// Send a message and wait for the reply of the daemon.
+    ///
+    /// The daemon answers every message in order, so the replies arriving after a timeout
+    /// are counted and skipped instead of being taken for the reply of the next message.
+    /// # Errors
+    /// - [`ErrorRoccatVulcanApi::Rejected`] if the daemon answered with an error
+    /// - [`ErrorRoccatVulcanApi::Io`] if the connection failed or the daemon did not answer
+    pub fn request(&self, message: &ClientMessage) -> Res<()> {
+        // serializing the messages never fails
+        let json = serde_json::to_string(message).unwrap_or_default();
+        writeln!(&self.stream, "{}", json)?;
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.replies.recv_timeout(timeout) {
+                Ok(_) if self.late_replies.get() > 0 => {
+                    self.late_replies.set(self.late_replies.get() - 1);
+                }
+                Ok(DaemonMessage::Error { message }) => {
+                    return Err(ErrorRoccatVulcanApi::Rejected(message))
+                }
+                Ok(_) => return Ok(()),
+                Err(RecvTimeoutError::Timeout) => {
+                    self.late_replies.set(self.late_replies.get() + 1);
+                    return Err(std::io::Error::new(
+                        ErrorKind::TimedOut,
+                        "the daemon did not answer",
+                    )
+                    .into());
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(closed()),
+            }
+        }
+    }
+
+    /// Name the client and set the priority of its layer
+    /// # Errors
+    /// see [`Self::request`]
+    pub fn hello(&self, name: &str, priority: i32) -> Res<()> {
+        self.request(&ClientMessage::Hello {
+            name: name.to_owned(),
+            priority,
+        })
+    }
+
+    /// Set the priority of the layer, the layers with a higher priority are drawn on top
+    /// # Errors
+    /// see [`Self::request`]
+    pub fn set_priority(&self, priority: i32) -> Res<()> {
+        self.request(&ClientMessage::SetPriority { priority })
+    }
+
+    /// Change some keys of the layer, leaving the other ones as they are
+    /// # Errors
+    /// see [`Self::request`]
+    pub fn set_keys(&self, colors: BTreeMap<KeyTarget, ColorRgb>) -> Res<()> {
+        self.request(&ClientMessage::SetKeys { colors })
+    }
+
+    /// Make the whole layer transparent
+    /// # Errors
+    /// see [`Self::request`]
+    pub fn clear_layer(&self) -> Res<()> {
+        self.request(&ClientMessage::ClearLayer)
+    }
+
+    /// Start receiving the key presses, done by the first read of the key presses.
+    ///
+    /// The key presses are kept from this point until they are read.
+    /// # Errors
+    /// see [`Self::request`]
+    pub fn subscribe(&self) -> Res<()> {
+        if !self.subscribed.get() {
+            self.request(&ClientMessage::Subscribe)?;
+            self.subscribed.set(true);
+        }
+        Ok(())
+    }
+
+    /// Get the color correction applied when rendering.
+    pub const fn color_correction(&self) -> &ColorCorrection {
+        &self.color_correction
+    }
+
+    /// Set the color correction applied when rendering.
+    pub const fn set_color_correction(&mut self, color_correction: ColorCorrection) {
+        self.color_correction = color_correction;
+    }
+
+    /// Get the color correction applied when rendering as a mutable reference.
+    pub const fn color_correction_mut(&mut self) -> &mut ColorCorrection {
+        &mut self.color_correction
+    }
+
+    /// Replace the layer with the colors of the keys, applying the [`ColorCorrection`].
+    ///
+    /// The lights that are not keys of the layout are ignored.
+    /// # Errors
+    /// see [`Self::request`]
+    pub fn render(&self, buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>) -> Res<()> {
+        let colors = self
+            .layout
+            .layout()
+            .iter()
+            .filter_map(|info| {
+                let color = buffer.get_key(self.layout, *info.key_name())?;
+                Some((
+                    KeyTarget::Key(*info.key_name()),
+                    self.color_correction.apply((*color).into()),
+                ))
+            })
+            .collect();
+        self.request(&ClientMessage::SetLayer { colors })
+    }
+
+    /// Read key press for a time of at least duration and return the key presses
+    /// that occured for this duration.
+    ///
+    /// The client subscribes to the key presses on the first call.
+    /// # Errors
+    /// [`ErrorRoccatVulcanApi::Io`] if the connection to the daemon is closed
+    /// and the errors of [`Self::subscribe`].
+    pub fn read_key_press(&self, duration: Duration) -> Res<Vec<KeyPress>> {
+        self.subscribe()?;
+        let start = Instant::now();
+        let mut key_presses = Vec::new();
+        loop {
+            let elapsed = start.elapsed();
+            if duration <= elapsed {
+                break;
+            }
+            match self.key_presses.recv_timeout(duration - elapsed) {
+                Ok(key_press) => key_presses.push(key_press),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Err(closed()),
+            }
+        }
+        Ok(key_presses)
+    }
+
+    /// Block the thread until a key press or an error occur
+    /// # Errors
+    /// see [`Self::read_key_press`]
+    pub fn wait_for_key_press(&self) -> Res<KeyPress> {
+        self.subscribe()?;
+        self.key_presses.recv().map_err(|_| closed())
+    }
+
+    /// Switch the mode of the keyboard, for all the clients of the daemon
+    /// # Errors
+    /// see [`Self::request`]
+    pub fn set_mode(&self, kind: ControlerFeatureKind) -> Res<()> {
+        self.request(&ClientMessage::SetMode { mode: kind })
+    }
+
+    /// Stop the daemon
+    /// # Errors
+    /// see [`Self::request`]
+    pub fn shutdown_daemon(&self) -> Res<()> {
+        self.request(&ClientMessage::Shutdown)
+    }
+}
+
+/// Error returned when the daemon closed the connection
+fn closed() -> ErrorRoccatVulcanApi {
+    std::io::Error::new(
+        ErrorKind::ConnectionAborted,
+        "the daemon closed the connection",
+    )
+    .into()
+}
+
+impl Keyboard for DaemonClient {
+    fn render(&self, buffer: &ColorBuffer<ColorRgb>) -> Res<()> {
+        Self::render(self, buffer)
+    }
+
+    fn read_key_press(&self, duration: Duration) -> Res<Vec<KeyPress>> {
+        Self::read_key_press(self, duration)
+    }
+
+    fn set_mode(&self, kind: ControlerFeatureKind) -> Res<()> {
+        Self::set_mode(self, kind)
+    }
+}
+
+impl Drop for DaemonClient {
+    fn drop(&mut self) {
+        // the reading thread stops and the daemon removes the layer
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+impl Debug for DaemonClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DaemonClient")
+            .field("stream", &self.stream)
+            .field("late_replies", &self.late_replies.get())
+            .field("subscribed", &self.subscribed.get())
+            .field("color_correction", &self.color_correction)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        daemon::{Daemon, DaemonError},
+        keyboard::KeyboardApi,
+        layout::{KeyCode, KeyName, LayoutFrCh},
+        transport::MockTransport,
+    };
+
+    /// Render through the shared trait and read the key presses
+    fn render_and_read(keyboard: &dyn Keyboard, color: ColorRgb) -> Res<Vec<KeyPress>> {
+        keyboard.render(&ColorBuffer::from_element(color))?;
+        keyboard.read_key_press(Duration::from_millis(200))
+    }
+
+    /// Wait until the last frame rendered on the transport has this color on Escape
+    fn wait_for(transport: &MockTransport, color: ColorRgb) {
+        let layout = LayoutFrCh::new();
+        let escape_color = || {
+            transport
+                .last_frame()
+                .and_then(|frame| frame.get_key(&layout, KeyName::Escape).copied())
+        };
+        for _ in 0_u32..400 {
+            if escape_color() == Some(color) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        panic!("expected {:?}, found {:?}", color, escape_color());
+    }
+
+    #[test]
+    fn direct_and_daemon() {
+        let escape = KeyPress::new(KeyCode::new(251, 17), true);
+        let red = ColorRgb::new(255, 0, 0);
+        let blue = ColorRgb::new(0, 0, 255);
+
+        // directly on the keyboard
+        let transport = MockTransport::new();
+        let keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+        transport.push_key_press(escape);
+        assert_eq!(render_and_read(&keyboard, red).unwrap(), vec![escape]);
+        wait_for(&transport, red);
+        drop(keyboard);
+
+        // through a daemon owning the keyboard
+        let path =
+            std::env::temp_dir().join(format!("daemon-client-test-{}.sock", std::process::id()));
+        let transport = MockTransport::new();
+        let keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+        let server_path = path.clone();
+        let server = thread::spawn(move || -> Result<(), DaemonError> {
+            Daemon::new(keyboard).serve(server_path, Duration::from_millis(5))
+        });
+        let client = loop {
+            match DaemonClient::connect(&path) {
+                Ok(client) => break client,
+                Err(_) => thread::sleep(Duration::from_millis(5)),
+            }
+        };
+        client.hello("test", 1_i32).unwrap();
+        client.subscribe().unwrap();
+        transport.push_key_press(escape);
+        assert_eq!(render_and_read(&client, blue).unwrap(), vec![escape]);
+        wait_for(&transport, blue);
+
+        // a second client drawn on top of the first one
+        let other = DaemonClient::connect(&path).unwrap();
+        let mut colors = BTreeMap::new();
+        colors.insert(KeyName::Escape.into(), red);
+        other.set_keys(colors).unwrap();
+        other.set_priority(2_i32).unwrap();
+        wait_for(&transport, red);
+        drop(other);
+        wait_for(&transport, blue);
+
+        let mut colors = BTreeMap::new();
+        colors.insert(KeyName::WheelUp.into(), red);
+        assert!(matches!(
+            client.set_keys(colors),
+            Err(ErrorRoccatVulcanApi::Rejected(_))
+        ));
+        Keyboard::set_mode(&client, ControlerFeatureKind::Rainbow).unwrap();
+        assert_eq!(transport.mode(), Some(ControlerFeatureKind::Rainbow));
+        client.set_mode(ControlerFeatureKind::Custom).unwrap();
+
+        client.shutdown_daemon().unwrap();
+        server.join().unwrap().unwrap();
+        assert!(matches!(
+            client.read_key_press(Duration::from_secs(1)),
+            Err(ErrorRoccatVulcanApi::Io(_))
+        ));
+        assert!(matches!(
+            client.clear_layer(),
+            Err(ErrorRoccatVulcanApi::Io(_))
+        ));
+    }
+
+    #[test]
+    fn late_reply() {
+        let path =
+            std::env::temp_dir().join(format!("daemon-late-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        // a daemon answering the first message after the timeout
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+            lines.next().unwrap().unwrap();
+            thread::sleep(REPLY_TIMEOUT + Duration::from_millis(200));
+            writeln!(&stream, r#"{{"type": "error", "message": "late"}}"#).unwrap();
+            lines.next().unwrap().unwrap();
+            writeln!(&stream, r#"{{"type": "ok"}}"#).unwrap();
+            lines.next().unwrap().unwrap();
+            writeln!(&stream, r#"{{"type": "error", "message": "rejected"}}"#).unwrap();
+        });
+        let client = DaemonClient::connect(&path).unwrap();
+        assert!(matches!(
+            client.hello("late", 0_i32),
+            Err(ErrorRoccatVulcanApi::Io(_))
+        ));
+        // the late error is skipped, the reply of the second message is taken
+        client.set_priority(1_i32).unwrap();
+        assert!(matches!(
+            client.clear_layer(),
+            Err(ErrorRoccatVulcanApi::Rejected(message)) if message == "rejected"
+        ));
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,46 @@
+//! Contains [`DaemonError`]
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::error::ErrorRoccatVulcanApi;
+
+/// Error stopping the [`super::Daemon`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DaemonError {
+    /// Error of the socket
+    Io(std::io::Error),
+    /// Error of the keyboard
+    Keyboard(ErrorRoccatVulcanApi),
+}
+
+impl Display for DaemonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "socket error : {}", error),
+            Self::Keyboard(error) => write!(f, "keyboard error : {}", error),
+        }
+    }
+}
+
+impl Error for DaemonError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Keyboard(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for DaemonError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ErrorRoccatVulcanApi> for DaemonError {
+    fn from(error: ErrorRoccatVulcanApi) -> Self {
+        Self::Keyboard(error)
+    }
+}
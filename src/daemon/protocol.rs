@@ -0,0 +1,137 @@
+//! Messages exchanged between the [`super::Daemon`] and its clients
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color::ColorRgb,
+    keyboard::ControlerFeatureKind,
+    layout::{KeyCode, KeyName},
+    profile::KeyTarget,
+};
+
+/// Message sent by a client to the daemon, see the [protocol](super::Daemon#protocol)
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::ClientMessage;
+///
+/// let message: ClientMessage =
+///     serde_json::from_str(r#"{"type": "set_priority", "priority": 3}"#).unwrap();
+/// assert_eq!(message, ClientMessage::SetPriority { priority: 3 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ClientMessage {
+    /// Name the client and set its priority
+    Hello {
+        /// Name of the client, for the logs
+        name: String,
+        /// Priority of the layer
+        #[serde(default)]
+        priority: i32,
+    },
+    /// Set the priority of the layer, the layers with a higher priority are drawn on top
+    SetPriority {
+        /// Priority of the layer
+        priority: i32,
+    },
+    /// Replace the layer, the keys not listed are transparent
+    SetLayer {
+        /// Colors of the keys
+        colors: BTreeMap<KeyTarget, ColorRgb>,
+    },
+    /// Change some keys of the layer
+    SetKeys {
+        /// Colors of the keys
+        colors: BTreeMap<KeyTarget, ColorRgb>,
+    },
+    /// Make the whole layer transparent
+    ClearLayer,
+    /// Receive the key presses
+    Subscribe,
+    /// Stop receiving the key presses
+    Unsubscribe,
+    /// Switch the mode of the keyboard
+    SetMode {
+        /// Mode to set
+        mode: ControlerFeatureKind,
+    },
+    /// Stop the daemon
+    Shutdown,
+}
+
+/// Message sent by the daemon to a client, see the [protocol](super::Daemon#protocol)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DaemonMessage {
+    /// The message of the client was applied
+    Ok,
+    /// The message of the client was invalid
+    Error {
+        /// Description of the problem
+        message: String,
+    },
+    /// A key was pressed or released, sent to the subscribed clients
+    KeyPress {
+        /// Name of the key, [`None`] if it is not in the layout
+        key: Option<KeyName>,
+        /// Code of the key
+        key_code: KeyCode,
+        /// Whether the key is pressed or released
+        pressed: bool,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::KeyGroup;
+
+    #[test]
+    fn json() {
+        let message: ClientMessage = serde_json::from_str(
+            r##"{"type": "set_layer", "colors": {"Escape": "#ff0000", "@Arrows": "blue"}}"##,
+        )
+        .unwrap();
+        let mut colors = BTreeMap::new();
+        colors.insert(KeyName::Escape.into(), ColorRgb::new(255, 0, 0));
+        colors.insert(KeyGroup::Arrows.into(), ColorRgb::new(0, 0, 255));
+        assert_eq!(message, ClientMessage::SetLayer { colors });
+
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type": "set_mode", "mode": "rainbow"}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::SetMode {
+                mode: ControlerFeatureKind::Rainbow
+            }
+        );
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type": "hello", "name": "a"}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Hello {
+                name: "a".to_owned(),
+                priority: 0_i32
+            }
+        );
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"type": "jump"}"#).is_err());
+
+        assert_eq!(
+            serde_json::to_string(&DaemonMessage::Ok).unwrap(),
+            r#"{"type":"ok"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&DaemonMessage::KeyPress {
+                key: Some(KeyName::Escape),
+                key_code: KeyCode::new(251, 17),
+                pressed: true,
+            })
+            .unwrap(),
+            r#"{"type":"key_press","key":"Escape","key_code":{"first_u8":251,"seconde_u8":17},"pressed":true}"#
+        );
+    }
+}
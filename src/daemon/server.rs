@@ -0,0 +1,738 @@
+//! Contains [`Daemon`]
+
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::fs::DirBuilder;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use super::{ClientMessage, DaemonError, DaemonMessage};
+use crate::{
+    color::{ColorBuffer, ColorRgb},
+    error::ErrorRoccatVulcanApi,
+    keyboard::{ControlerFeatureKind, KeyboardApi},
+    layout::{Layout, LayoutKind},
+    profile::{KeyTarget, ProfileError},
+    transport::{HidTransport, Transport},
+};
+
+/// Identifier of a client of the [`Daemon`]
+pub type ClientId = u64;
+
+/// Maximum time spent writing to a client before considering it dead
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Get the default path of the socket, `$XDG_RUNTIME_DIR/roccat-vulcan.sock` or
+/// `$HOME/.roccat-vulcan.sock` without runtime directory.
+///
+/// A directory of the user is used so that another user cannot create the socket first.
+pub fn default_socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => PathBuf::from(runtime_dir).join("roccat-vulcan.sock"),
+        None => std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(".roccat-vulcan.sock"),
+    }
+}
+
+/// A client connected to the [`Daemon`]
+struct Client {
+    /// Name given in [`ClientMessage::Hello`]
+    name: String,
+    /// Priority of the layer
+    priority: i32,
+    /// Layer of the client, [`None`] is transparent
+    layer: ColorBuffer<Option<ColorRgb>>,
+    /// Whether the key presses are sent to the client
+    subscribed: bool,
+    /// Where the messages to the client are written
+    sink: Box<dyn Write + Send>,
+}
+
+/// Event received by the main loop of [`Daemon::serve`]
+enum SocketEvent {
+    /// A line sent by a client
+    Line(ClientId, String),
+    /// The client closed the connection or the reading failed
+    Closed(ClientId),
+}
+
+/// Owns the keyboard and composites the layers of its clients.
+///
+/// [`Self::serve`] runs the daemon on a Unix domain socket. The other methods drive the
+/// daemon directly, which is used to test it with a [`crate::MockTransport`].
+/// # Protocol
+/// The messages are JSON objects, one per line, with a field `type`.
+///
+/// Messages sent by the clients, see [`ClientMessage`]:
+/// - `{"type": "hello", "name": "music", "priority": 10}` name the client and set its priority
+/// - `{"type": "set_priority", "priority": 5}` the layers with a higher priority are drawn on top,
+///   the default priority is 0
+/// - `{"type": "set_layer", "colors": {"@Letters": "blue", "Escape": "#ff0000"}}` replace the layer
+///   of the client, the keys not listed are transparent. The keys are written like in a
+///   [`crate::Profile`], a key takes precedence over a group.
+/// - `{"type": "set_keys", "colors": {"A": "red"}}` change some keys of the layer
+/// - `{"type": "clear_layer"}` make the whole layer transparent
+/// - `{"type": "subscribe"}` and `{"type": "unsubscribe"}` start or stop receiving the key presses
+/// - `{"type": "set_mode", "mode": "rainbow"}` switch the keyboard to `rainbow` or `custom` mode,
+///   the layers are only drawn in custom mode
+/// - `{"type": "shutdown"}` stop the daemon
+///
+/// The daemon answers each message with `{"type": "ok"}` or `{"type": "error", "message": "..."}`
+/// and sends the key presses to the subscribed clients as
+/// `{"type": "key_press", "key": "A", "key_code": {...}, "pressed": true}`,
+/// see [`DaemonMessage`].
+///
+/// When a client disconnects, for instance because it crashed, its layer is removed.
+///
+/// [`crate::DaemonClient`] implements this protocol for Rust programs.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorRgb, Daemon, KeyName, KeyboardApi, Layout, LayoutFrCh, MockTransport,
+/// };
+///
+/// let transport = MockTransport::new();
+/// let mut daemon = Daemon::new(KeyboardApi::with_transport(transport.clone()).unwrap());
+/// let background = daemon.connect(std::io::sink());
+/// let alert = daemon.connect(std::io::sink());
+/// daemon.handle_line(
+///     background,
+///     r#"{"type": "set_layer", "colors": {"@All": "blue"}}"#,
+/// );
+/// daemon.handle_line(alert, r#"{"type": "set_priority", "priority": 1}"#);
+/// daemon.handle_line(
+///     alert,
+///     r#"{"type": "set_keys", "colors": {"Escape": "red"}}"#,
+/// );
+/// daemon.tick(Duration::from_millis(1)).unwrap();
+///
+/// let layout = LayoutFrCh::new();
+/// let frame = transport.last_frame().unwrap();
+/// assert_eq!(
+///     frame.get_key(&layout, KeyName::Escape),
+///     Some(&ColorRgb::new(255, 0, 0))
+/// );
+/// assert_eq!(
+///     frame.get_key(&layout, KeyName::A),
+///     Some(&ColorRgb::new(0, 0, 255))
+/// );
+///
+/// // the alert client crashed
+/// daemon.disconnect(alert);
+/// daemon.tick(Duration::from_millis(1)).unwrap();
+/// let frame = transport.last_frame().unwrap();
+/// assert_eq!(
+///     frame.get_key(&layout, KeyName::Escape),
+///     Some(&ColorRgb::new(0, 0, 255))
+/// );
+/// ```
+pub struct Daemon<T: Transport = HidTransport> {
+    /// The keyboard
+    keyboard: KeyboardApi<T>,
+    /// Layout used to find the keys
+    layout: &'static dyn Layout,
+    /// Connected clients
+    clients: BTreeMap<ClientId, Client>,
+    /// Identifier given to the next client
+    next_id: ClientId,
+    /// Mode of the keyboard, the layers are only drawn in custom mode
+    mode: ControlerFeatureKind,
+    /// Whether the frame needs to be rendered again
+    changed: bool,
+    /// Whether a client asked to stop
+    stopped: bool,
+}
+
+impl<T: Transport> Daemon<T> {
+    /// Create a daemon without client
+    pub fn new(keyboard: KeyboardApi<T>) -> Self {
+        Self {
+            keyboard,
+            layout: LayoutKind::default().layout(),
+            clients: BTreeMap::new(),
+            next_id: 0,
+            mode: ControlerFeatureKind::Custom,
+            changed: true,
+            stopped: false,
+        }
+    }
+
+    /// Get the keyboard
+    pub const fn keyboard(&self) -> &KeyboardApi<T> {
+        &self.keyboard
+    }
+
+    /// Get the number of clients connected
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Returns whether a client sent [`ClientMessage::Shutdown`]
+    pub const fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Add a client, the messages to the client are written to `sink`
+    pub fn connect(&mut self, sink: impl Write + Send + 'static) -> ClientId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.clients.insert(
+            id,
+            Client {
+                name: format!("client {}", id),
+                priority: 0,
+                layer: ColorBuffer::new(),
+                subscribed: false,
+                sink: Box::new(sink),
+            },
+        );
+        id
+    }
+
+    /// Remove a client and its layer
+    pub fn disconnect(&mut self, client: ClientId) {
+        if self.clients.remove(&client).is_some() {
+            self.changed = true;
+        }
+    }
+
+    /// Handle a line of JSON sent by a client and send the reply
+    pub fn handle_line(&mut self, client: ClientId, line: &str) {
+        let reply = match serde_json::from_str(line) {
+            Ok(message) => self.handle(client, message),
+            Err(error) => DaemonMessage::Error {
+                message: error.to_string(),
+            },
+        };
+        self.send(client, &reply);
+    }
+
+    /// Apply a message of a client and return the reply
+    pub fn handle(&mut self, client: ClientId, message: ClientMessage) -> DaemonMessage {
+        match self.apply(client, message) {
+            Ok(()) => DaemonMessage::Ok,
+            Err(message) => DaemonMessage::Error { message },
+        }
+    }
+
+    /// Apply a message of a client
+    fn apply(&mut self, id: ClientId, message: ClientMessage) -> Result<(), String> {
+        let layout = self.layout;
+        let client = self
+            .clients
+            .get_mut(&id)
+            .ok_or_else(|| format!("unknown client {}", id))?;
+        match message {
+            ClientMessage::Hello { name, priority } => {
+                client.name = name;
+                client.priority = priority;
+            }
+            ClientMessage::SetPriority { priority } => client.priority = priority,
+            ClientMessage::SetLayer { colors } => {
+                let mut layer = ColorBuffer::new();
+                paint(&mut layer, layout, &colors)?;
+                client.layer = layer;
+            }
+            ClientMessage::SetKeys { colors } => paint(&mut client.layer, layout, &colors)?,
+            ClientMessage::ClearLayer => client.layer = ColorBuffer::new(),
+            ClientMessage::Subscribe => client.subscribed = true,
+            ClientMessage::Unsubscribe => client.subscribed = false,
+            ClientMessage::SetMode { mode } => {
+                self.keyboard
+                    .set_mode(mode)
+                    .map_err(|error| error.to_string())?;
+                self.mode = mode;
+            }
+            ClientMessage::Shutdown => self.stopped = true,
+        }
+        self.changed = true;
+        Ok(())
+    }
+
+    /// Write a message to a client, disconnecting it if it fails
+    fn send(&mut self, id: ClientId, message: &DaemonMessage) {
+        let sent = self.clients.get_mut(&id).map(|client| {
+            // serializing the messages never fails
+            let json = serde_json::to_string(message).unwrap_or_default();
+            writeln!(client.sink, "{}", json).and_then(|()| client.sink.flush())
+        });
+        if let Some(Err(_)) = sent {
+            self.disconnect(id);
+        }
+    }
+
+    /// Composite the layers, from the lowest priority to the highest.
+    ///
+    /// Two layers with the same priority are drawn in the order of connection.
+    pub fn frame(&self) -> ColorBuffer<ColorRgb> {
+        let mut clients: Vec<&Client> = self.clients.values().collect();
+        // the sort is stable so the order of connection is kept
+        clients.sort_by_key(|client| client.priority);
+        let mut frame = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+        for client in clients {
+            for (color, layer) in frame.iter_mut().zip(client.layer.iter()) {
+                if let Some(layer) = layer {
+                    *color = *layer;
+                }
+            }
+        }
+        frame
+    }
+
+    /// Render the frame if it changed, then read the key presses for `frame_duration`
+    /// and send them to the subscribed clients.
+    /// # Errors
+    /// Returns the errors of [`KeyboardApi::render`] and [`KeyboardApi::read_key_press`].
+    pub fn tick(&mut self, frame_duration: Duration) -> Result<(), ErrorRoccatVulcanApi> {
+        if self.changed && self.mode == ControlerFeatureKind::Custom {
+            self.keyboard.render(&self.frame())?;
+            self.changed = false;
+        }
+        for key_press in self.keyboard.read_key_press(frame_duration)? {
+            let message = DaemonMessage::KeyPress {
+                key: self
+                    .layout
+                    .find_from_key_code(*key_press.key_code())
+                    .map(|info| *info.key_name()),
+                key_code: *key_press.key_code(),
+                pressed: key_press.is_pressed(),
+            };
+            let subscribers: Vec<ClientId> = self
+                .clients
+                .iter()
+                .filter(|(_, client)| client.subscribed)
+                .map(|(id, _)| *id)
+                .collect();
+            for id in subscribers {
+                self.send(id, &message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Listen on the Unix domain socket `path` until a client sends
+    /// [`ClientMessage::Shutdown`] or the keyboard fails.
+    ///
+    /// Each frame lasts at least `frame_duration`. The socket is only accessible by the user
+    /// and is removed when the daemon stops.
+    /// # Errors
+    /// - [`DaemonError::Io`] if the socket cannot be created, for instance if another
+    ///   daemon is listening on it
+    /// - [`DaemonError::Keyboard`] if the keyboard encountered an error
+    pub fn serve(
+        &mut self,
+        path: impl AsRef<Path>,
+        frame_duration: Duration,
+    ) -> Result<(), DaemonError> {
+        let path = path.as_ref();
+        let listener = bind(path)?;
+        let (sender, receiver) = mpsc::channel();
+        let mut streams = BTreeMap::new();
+        let result = self.serve_loop(&listener, &sender, &receiver, &mut streams, frame_duration);
+        for stream in streams.values() {
+            // wake up the reading threads
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        let _ = std::fs::remove_file(path);
+        result
+    }
+
+    /// Main loop of [`Self::serve`]
+    fn serve_loop(
+        &mut self,
+        listener: &UnixListener,
+        sender: &Sender<SocketEvent>,
+        receiver: &Receiver<SocketEvent>,
+        streams: &mut BTreeMap<ClientId, UnixStream>,
+        frame_duration: Duration,
+    ) -> Result<(), DaemonError> {
+        while !self.stopped {
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if let Some((id, stream)) = self.accept(stream, sender)? {
+                            streams.insert(id, stream);
+                        }
+                    }
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            loop {
+                match receiver.try_recv() {
+                    Ok(SocketEvent::Line(id, line)) => self.handle_line(id, &line),
+                    Ok(SocketEvent::Closed(id)) => self.disconnect(id),
+                    Err(TryRecvError::Empty | TryRecvError::Disconnected) => break,
+                }
+            }
+            // the clients disconnected by a failed write
+            streams.retain(|id, stream| {
+                let connected = self.clients.contains_key(id);
+                if !connected {
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+                connected
+            });
+            self.tick(frame_duration)?;
+        }
+        Ok(())
+    }
+
+    /// Add a client connected to the socket and start reading its messages.
+    ///
+    /// Returns the id of the client and the stream, or [`None`] if the stream cannot be used.
+    fn accept(
+        &mut self,
+        stream: UnixStream,
+        sender: &Sender<SocketEvent>,
+    ) -> Result<Option<(ClientId, UnixStream)>, DaemonError> {
+        let setup = stream
+            .set_nonblocking(false)
+            .and_then(|()| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+            .and_then(|()| Ok((stream.try_clone()?, stream.try_clone()?)));
+        let (sink, reader) = match setup {
+            Ok(streams) => streams,
+            // the client is already gone
+            Err(_) => return Ok(None),
+        };
+        let id = self.connect(sink);
+        let sender = sender.clone();
+        thread::Builder::new()
+            .name(format!("daemon client {}", id))
+            .spawn(move || {
+                for line in BufReader::new(reader).lines() {
+                    match line {
+                        Ok(line) if line.trim().is_empty() => {}
+                        Ok(line) => {
+                            if sender.send(SocketEvent::Line(id, line)).is_err() {
+                                return;
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                }
+                let _ = sender.send(SocketEvent::Closed(id));
+            })?;
+        Ok(Some((id, stream)))
+    }
+}
+
+/// Paint the colors on a layer, the groups first so that the keys take precedence
+fn paint(
+    layer: &mut ColorBuffer<Option<ColorRgb>>,
+    layout: &dyn Layout,
+    colors: &BTreeMap<KeyTarget, ColorRgb>,
+) -> Result<(), String> {
+    let groups = colors
+        .iter()
+        .filter(|(target, _)| matches!(target, KeyTarget::Group(_)));
+    let keys = colors
+        .iter()
+        .filter(|(target, _)| !matches!(target, KeyTarget::Group(_)));
+    // the keys are checked before changing the layer
+    let painted = groups
+        .chain(keys)
+        .map(|(target, color)| Ok((target.light_indices(layout)?, *color)))
+        .collect::<Result<Vec<_>, ProfileError>>()
+        .map_err(|error| error.to_string())?;
+    for (indices, color) in painted {
+        for index in indices {
+            layer[index] = Some(color);
+        }
+    }
+    Ok(())
+}
+
+/// Create the socket, removing the file left by a daemon that did not stop properly
+fn bind(path: &Path) -> std::io::Result<UnixListener> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(std::io::Error::new(
+                ErrorKind::AddrInUse,
+                format!("a daemon is already listening on {}", path.display()),
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    // the socket is created in a directory only the user can enter, so that no other user
+    // connects before the permissions are set, then moved in place
+    let private_dir = path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(format!(".roccat-vulcan-{}", std::process::id()));
+    DirBuilder::new().mode(0o700).create(&private_dir)?;
+    let private_path = private_dir.join("daemon.sock");
+    let listener = UnixListener::bind(&private_path).and_then(|listener| {
+        std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&private_path, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_file(&private_path);
+    std::fs::remove_dir(&private_dir)?;
+    let listener = listener?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+impl<T: Transport> Debug for Daemon<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let clients: Vec<(&ClientId, &str, i32)> = self
+            .clients
+            .iter()
+            .map(|(id, client)| (id, client.name.as_str(), client.priority))
+            .collect();
+        f.debug_struct("Daemon")
+            .field("clients", &clients)
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{
+        layout::{KeyCode, KeyName, KeyPress, LayoutFrCh},
+        transport::MockTransport,
+    };
+
+    /// Sink keeping what is written, shared with the test
+    #[derive(Clone, Default)]
+    struct SharedSink(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedSink {
+        fn messages(&self) -> Vec<DaemonMessage> {
+            let data = self.0.lock().unwrap();
+            String::from_utf8_lossy(&data)
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    impl Write for SharedSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Sink of a client that crashed
+    struct BrokenSink;
+
+    impl Write for BrokenSink {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Err(ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    fn daemon() -> (Daemon<MockTransport>, MockTransport) {
+        let transport = MockTransport::new();
+        let keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+        (Daemon::new(keyboard), transport)
+    }
+
+    #[test]
+    fn priorities() {
+        let (mut daemon, _) = daemon();
+        let layout = LayoutFrCh::new();
+        let red = ColorRgb::new(255, 0, 0);
+        let blue = ColorRgb::new(0, 0, 255);
+        let high = daemon.connect(std::io::sink());
+        let low = daemon.connect(std::io::sink());
+        daemon.handle_line(high, r#"{"type": "hello", "name": "high", "priority": 2}"#);
+        daemon.handle_line(
+            high,
+            r#"{"type": "set_layer", "colors": {"@Letters": "red", "A": "blue"}}"#,
+        );
+        daemon.handle_line(low, r#"{"type": "set_layer", "colors": {"@All": "lime"}}"#);
+        let frame = daemon.frame();
+        assert_eq!(frame.get_key(&layout, KeyName::A), Some(&blue));
+        assert_eq!(frame.get_key(&layout, KeyName::B), Some(&red));
+        assert_eq!(
+            frame.get_key(&layout, KeyName::Escape),
+            Some(&ColorRgb::new(0, 255, 0))
+        );
+
+        daemon.handle(low, ClientMessage::SetPriority { priority: 3_i32 });
+        assert_eq!(
+            daemon.frame().get_key(&layout, KeyName::B),
+            Some(&ColorRgb::new(0, 255, 0))
+        );
+        daemon.handle(low, ClientMessage::ClearLayer);
+        daemon.handle_line(high, r#"{"type": "set_keys", "colors": {"B": "blue"}}"#);
+        assert_eq!(daemon.frame().get_key(&layout, KeyName::B), Some(&blue));
+        assert_eq!(daemon.frame().get_key(&layout, KeyName::A), Some(&blue));
+        daemon.disconnect(high);
+        assert_eq!(
+            daemon.frame().get_key(&layout, KeyName::A),
+            Some(&ColorRgb::new(0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn replies_and_events() {
+        let (mut daemon, transport) = daemon();
+        let sink = SharedSink::default();
+        let client = daemon.connect(sink.clone());
+        let other = daemon.connect(std::io::sink());
+        daemon.handle_line(client, "not json");
+        daemon.handle_line(
+            client,
+            r#"{"type": "set_keys", "colors": {"WheelUp": "red"}}"#,
+        );
+        daemon.handle_line(client, r#"{"type": "subscribe"}"#);
+        let escape = KeyPress::new(KeyCode::new(251, 17), true);
+        transport.push_key_press(escape);
+        daemon.tick(Duration::from_millis(1)).unwrap();
+        daemon.handle_line(client, r#"{"type": "unsubscribe"}"#);
+        transport.push_key_press(escape);
+        daemon.tick(Duration::from_millis(1)).unwrap();
+
+        let messages = sink.messages();
+        assert!(matches!(messages[0], DaemonMessage::Error { .. }));
+        assert!(matches!(messages[1], DaemonMessage::Error { .. }));
+        assert_eq!(messages[2], DaemonMessage::Ok);
+        assert_eq!(
+            messages[3],
+            DaemonMessage::KeyPress {
+                key: Some(KeyName::Escape),
+                key_code: KeyCode::new(251, 17),
+                pressed: true
+            }
+        );
+        assert_eq!(messages[4], DaemonMessage::Ok);
+        assert_eq!(messages.len(), 5);
+        assert_eq!(daemon.client_count(), 2);
+        daemon.disconnect(other);
+        assert_eq!(daemon.client_count(), 1);
+    }
+
+    #[test]
+    fn crashed_client() {
+        let (mut daemon, transport) = daemon();
+        let crashed = daemon.connect(BrokenSink);
+        daemon.handle_line(crashed, r#"{"type": "subscribe"}"#);
+        assert_eq!(daemon.client_count(), 0);
+        let client = daemon.connect(std::io::sink());
+        daemon.handle(client, ClientMessage::Subscribe);
+        let crashed = daemon.connect(BrokenSink);
+        daemon.handle(crashed, ClientMessage::Subscribe);
+        transport.push_key_press(KeyPress::new(KeyCode::new(251, 17), true));
+        daemon.tick(Duration::from_millis(1)).unwrap();
+        assert_eq!(daemon.client_count(), 1);
+    }
+
+    #[test]
+    fn modes() {
+        let (mut daemon, transport) = daemon();
+        let client = daemon.connect(std::io::sink());
+        daemon.tick(Duration::from_millis(1)).unwrap();
+        assert_eq!(transport.frames().len(), 1);
+        // nothing changed
+        daemon.tick(Duration::from_millis(1)).unwrap();
+        assert_eq!(transport.frames().len(), 1);
+
+        daemon.handle_line(client, r#"{"type": "set_mode", "mode": "rainbow"}"#);
+        assert_eq!(transport.mode(), Some(ControlerFeatureKind::Rainbow));
+        daemon.handle_line(client, r#"{"type": "set_layer", "colors": {"A": "red"}}"#);
+        daemon.tick(Duration::from_millis(1)).unwrap();
+        assert_eq!(transport.frames().len(), 1);
+        daemon.handle_line(client, r#"{"type": "set_mode", "mode": "custom"}"#);
+        daemon.tick(Duration::from_millis(1)).unwrap();
+        assert_eq!(transport.frames().len(), 2);
+
+        assert!(!daemon.is_stopped());
+        daemon.handle_line(client, r#"{"type": "shutdown"}"#);
+        assert!(daemon.is_stopped());
+    }
+
+    #[test]
+    fn socket() {
+        let path = std::env::temp_dir().join(format!("daemon-test-{}.sock", std::process::id()));
+        let transport = MockTransport::new();
+        let keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+        let server_path = path.clone();
+        let server = thread::spawn(move || {
+            Daemon::new(keyboard).serve(server_path, Duration::from_millis(5))
+        });
+        let mut stream = loop {
+            match UnixStream::connect(&path) {
+                Ok(stream) => break stream,
+                Err(_) => thread::sleep(Duration::from_millis(5)),
+            }
+        };
+        assert!(matches!(
+            bind(&path).map_err(|error| error.kind()),
+            Err(ErrorKind::AddrInUse)
+        ));
+        // only the user can connect, and the private directory is removed
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert!(!path
+            .with_file_name(format!(".roccat-vulcan-{}", std::process::id()))
+            .exists());
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        writeln!(
+            stream,
+            r#"{{"type": "set_layer", "colors": {{"@All": "red"}}}}"#
+        )
+        .unwrap();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line.trim(), r#"{"type":"ok"}"#);
+
+        let layout = LayoutFrCh::new();
+        let escape_color = || {
+            transport
+                .last_frame()
+                .and_then(|frame| frame.get_key(&layout, KeyName::Escape).copied())
+        };
+        let wait_for = |color| {
+            for _ in 0_u32..400 {
+                if escape_color() == Some(color) {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+            panic!("expected {:?}, found {:?}", color, escape_color());
+        };
+        wait_for(ColorRgb::new(255, 0, 0));
+
+        // a client that disconnects abruptly, its layer is on top while it is connected
+        let mut crashed = UnixStream::connect(&path).unwrap();
+        writeln!(
+            crashed,
+            r#"{{"type": "set_layer", "colors": {{"@All": "blue"}}}}"#
+        )
+        .unwrap();
+        wait_for(ColorRgb::new(0, 0, 255));
+        drop(crashed);
+        wait_for(ColorRgb::new(255, 0, 0));
+
+        writeln!(stream, r#"{{"type": "shutdown"}}"#).unwrap();
+        server.join().unwrap().unwrap();
+        assert!(!path.exists());
+        // the keyboard is dropped with the daemon
+        assert_eq!(transport.mode(), Some(ControlerFeatureKind::Rainbow));
+    }
+}
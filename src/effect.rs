@@ -0,0 +1,128 @@
+//! Contains the trait [`Effect`] and the effects provided by the library
+
+use std::time::Duration;
+
+use crate::{
+    color::{ColorBuffer, ColorRgb},
+    layout::{KeyPress, Layout},
+};
+
+mod breathing;
+pub use breathing::*;
+
+mod wave;
+pub use wave::*;
+
+mod sweep;
+pub use sweep::*;
+
+mod reactive;
+pub use reactive::*;
+
+mod ambilight;
+pub use ambilight::*;
+
+mod spectrum;
+pub use spectrum::*;
+
+mod marquee;
+pub use marquee::*;
+
+mod config;
+pub use config::*;
+
+/// An animated lighting effect.
+///
+/// Effects are stacked: each effect is rendered on top of the buffer produced by
+/// the base colors and the previous effects.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{ColorBuffer, ColorRgb, Effect, Layout, LayoutFrCh};
+///
+/// /// Blink every second
+/// struct Blink;
+///
+/// impl Effect for Blink {
+///     fn render(
+///         &mut self,
+///         buffer: &mut ColorBuffer<ColorRgb>,
+///         _layout: &dyn Layout,
+///         elapsed: Duration,
+///     ) {
+///         if elapsed.as_secs() % 2 == 1 {
+///             *buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+///         }
+///     }
+/// }
+///
+/// let mut buffer = ColorBuffer::from_element(ColorRgb::new(255, 255, 255));
+/// Blink.render(&mut buffer, &LayoutFrCh::new(), Duration::from_millis(1500));
+/// assert_eq!(buffer[0], ColorRgb::new(0, 0, 0));
+/// ```
+pub trait Effect {
+    /// Render the effect on top of `buffer`.
+    ///
+    /// `elapsed` is the time since the start of the rendering.
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    );
+
+    /// Called for each key press event. Does nothing by default.
+    fn key_press(&mut self, _key_press: KeyPress, _layout: &dyn Layout, _elapsed: Duration) {}
+}
+
+impl<E: Effect + ?Sized> Effect for Box<E> {
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        (**self).render(buffer, layout, elapsed);
+    }
+
+    fn key_press(&mut self, key_press: KeyPress, layout: &dyn Layout, elapsed: Duration) {
+        (**self).key_press(key_press, layout, elapsed);
+    }
+}
+
+/// Check that a number of seconds or a speed is finite, return `reason` otherwise.
+const fn check_finite(value: f64, reason: &'static str) -> Result<(), &'static str> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(reason)
+    }
+}
+
+/// Check that a value is finite and strictly positive, return `reason` otherwise.
+fn check_positive(value: f64, reason: &'static str) -> Result<(), &'static str> {
+    if value.is_finite() && value > 0_f64 {
+        Ok(())
+    } else {
+        Err(reason)
+    }
+}
+
+/// Check that a number of seconds is strictly positive and fits in a [`Duration`], return
+/// `reason` otherwise.
+fn check_duration(seconds: f64, reason: &'static str) -> Result<(), &'static str> {
+    check_positive(seconds, reason)?;
+    Duration::try_from_secs_f64(seconds)
+        .map(|_| ())
+        .map_err(|_| reason)
+}
+
+/// Check that a value is between 0 and 1, return `reason` otherwise.
+fn check_unit(value: f64, reason: &'static str) -> Result<(), &'static str> {
+    if (0_f64..=1_f64).contains(&value) {
+        Ok(())
+    } else {
+        Err(reason)
+    }
+}
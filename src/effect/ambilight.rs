@@ -0,0 +1,454 @@
+//! Contains the effect [`Ambilight`] and the trait [`ImageSource`]
+
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use super::Effect;
+use crate::{
+    color::{ColorBuffer, ColorRgb, NUMBER_KEY_LED_BUFFER},
+    layout::Layout,
+};
+
+/// Maximum number of pixels read per key along each axis
+const DEFAULT_SAMPLES: u32 = 8;
+
+/// Source of the images shown by [`Ambilight`], for instance a screen capture.
+///
+/// The coordinates start from the top left corner.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{ColorRgb, ImageSource};
+///
+/// /// Gradient from black to red, scrolling to the right
+/// struct Scroll {
+///     shift: u32,
+/// }
+///
+/// impl ImageSource for Scroll {
+///     fn size(&self) -> (u32, u32) {
+///         (256, 1)
+///     }
+///
+///     fn pixel(&self, x: u32, _y: u32) -> ColorRgb {
+///         ColorRgb::new(((x + self.shift) % 256) as u8, 0, 0)
+///     }
+///
+///     fn update(&mut self, elapsed: Duration) {
+///         self.shift = (elapsed.as_millis() % 256) as u32;
+///     }
+/// }
+/// ```
+pub trait ImageSource {
+    /// Get the width and the height of the current image in pixels
+    fn size(&self) -> (u32, u32);
+
+    /// Get the color of a pixel of the current image, `x` is smaller than the width
+    /// and `y` smaller than the height
+    fn pixel(&self, x: u32, y: u32) -> ColorRgb;
+
+    /// Capture a new image, called once per frame before reading the pixels.
+    /// Does nothing by default.
+    ///
+    /// `elapsed` is the time since the start of the rendering.
+    fn update(&mut self, _elapsed: Duration) {}
+}
+
+impl<S: ImageSource + ?Sized> ImageSource for Box<S> {
+    fn size(&self) -> (u32, u32) {
+        (**self).size()
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> ColorRgb {
+        (**self).pixel(x, y)
+    }
+
+    fn update(&mut self, elapsed: Duration) {
+        (**self).update(elapsed);
+    }
+}
+
+/// [`ImageSource`] that always returns the same image
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{ColorRgb, ImageSource, StaticImage};
+///
+/// let image = StaticImage::from_fn(2, 2, |x, y| ColorRgb::new(255 * x as u8, 255 * y as u8, 0));
+/// assert_eq!(image.size(), (2, 2));
+/// assert_eq!(image.pixel(1, 0), ColorRgb::new(255, 0, 0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StaticImage {
+    /// Width in pixels
+    width: u32,
+    /// Height in pixels
+    height: u32,
+    /// Pixels row by row from the top left corner
+    pixels: Vec<ColorRgb>,
+}
+
+impl StaticImage {
+    /// Create an image from its pixels row by row from the top left corner,
+    /// returns [`None`] if the number of pixels is not `width * height`.
+    pub fn new(width: u32, height: u32, pixels: Vec<ColorRgb>) -> Option<Self> {
+        let expected = usize::try_from(u64::from(width) * u64::from(height)).ok()?;
+        if pixels.len() == expected {
+            Some(Self {
+                width,
+                height,
+                pixels,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Create an image with the color of each pixel given by `pixel(x, y)`
+    pub fn from_fn(width: u32, height: u32, pixel: impl Fn(u32, u32) -> ColorRgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| pixel(x, y))
+                .collect(),
+        }
+    }
+}
+
+impl ImageSource for StaticImage {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn pixel(&self, x: u32, y: u32) -> ColorRgb {
+        // the image fits in memory so the index fits in usize
+        self.pixels[(u64::from(y) * u64::from(self.width) + u64::from(x)) as usize]
+    }
+}
+
+/// Ambient light: each key takes the average color of the region of an image at its place.
+///
+/// The image covers the keys of the [`Layout`], scaled from their positions, the top left
+/// corner of the image being the top left key. The colors follow the image with an
+/// exponential smoothing so that a flickering source does not flicker on the keyboard.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     Ambilight, ColorBuffer, ColorRgb, Effect, KeyName, LayoutFrCh, StaticImage,
+/// };
+///
+/// // left half in red, right half in blue
+/// let image = StaticImage::from_fn(64, 36, |x, _| {
+///     if x < 32 {
+///         ColorRgb::new(255, 0, 0)
+///     } else {
+///         ColorRgb::new(0, 0, 255)
+///     }
+/// });
+/// let layout = LayoutFrCh::new();
+/// let mut effect = Ambilight::new(image).with_smoothing(Duration::from_millis(200));
+/// let mut buffer = ColorBuffer::new();
+/// effect.render(&mut buffer, &layout, Duration::ZERO);
+/// assert_eq!(
+///     buffer.get_key(&layout, KeyName::Escape),
+///     Some(&ColorRgb::new(255, 0, 0))
+/// );
+/// assert_eq!(
+///     buffer.get_key(&layout, KeyName::NumEnter),
+///     Some(&ColorRgb::new(0, 0, 255))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ambilight<S: ImageSource> {
+    /// Source of the images
+    source: S,
+    /// Time constant of the smoothing in seconds, zero to follow the image immediately
+    smoothing: f64,
+    /// Maximum number of pixels read per key along each axis
+    samples: u32,
+    /// Smoothed colors of the lights
+    colors: Vec<[f64; 3]>,
+    /// Time of the previous frame
+    last_frame: Option<Duration>,
+}
+
+impl<S: ImageSource> Ambilight<S> {
+    /// Create the effect following the image of `source` immediately
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            smoothing: 0_f64,
+            samples: DEFAULT_SAMPLES,
+            colors: vec![[0_f64; 3]; NUMBER_KEY_LED_BUFFER],
+            last_frame: None,
+        }
+    }
+
+    /// Set the time constant of the smoothing: after this duration the keys have done
+    /// about two thirds of the change to a new image
+    pub const fn with_smoothing(mut self, smoothing: Duration) -> Self {
+        self.smoothing = smoothing.as_secs_f64();
+        self
+    }
+
+    /// Set the maximum number of pixels read per key along each axis, at least 1.
+    ///
+    /// The pixels read are spread evenly on the region of the key, a large source
+    /// like a screen is then not read entirely on each frame.
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    /// Get the source of the images
+    pub const fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Get a mutable reference to the source of the images
+    pub const fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Get the time constant of the smoothing
+    pub fn smoothing(&self) -> Duration {
+        Duration::from_secs_f64(self.smoothing)
+    }
+
+    /// Get the maximum number of pixels read per key along each axis
+    pub const fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Get the source back
+    pub fn into_source(self) -> S {
+        self.source
+    }
+
+    /// Get the average color of the region of the image between the fractions
+    /// `left..right` of the width and `top..bottom` of the height
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn average(
+        &self,
+        (width, height): (u32, u32),
+        horizontal: [f64; 2],
+        vertical: [f64; 2],
+    ) -> [f64; 3] {
+        // pixel coordinates of the evenly spread samples
+        let samples = |[start, end]: [f64; 2], size: u32| {
+            let start = start * f64::from(size);
+            let end = end * f64::from(size);
+            let count = ((end - start).ceil().max(1_f64) as u32).min(self.samples);
+            let step = (end - start) / f64::from(count);
+            (0..count)
+                .map(move |i| ((f64::from(i) + 0.5_f64).mul_add(step, start) as u32).min(size - 1))
+        };
+        let mut sum = [0_f64; 3];
+        let mut count = 0_f64;
+        for y in samples(vertical, height) {
+            for x in samples(horizontal, width) {
+                let color = self.source.pixel(x, y);
+                sum[0] += f64::from(color.r());
+                sum[1] += f64::from(color.g());
+                sum[2] += f64::from(color.b());
+                count += 1_f64;
+            }
+        }
+        sum.map(|channel| channel / count)
+    }
+}
+
+impl<S: ImageSource> Effect for Ambilight<S> {
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        self.source.update(elapsed);
+        let size = self.source.size();
+        let keys: Vec<_> = layout
+            .layout()
+            .iter()
+            .filter(|key| usize::from(key.key_code_light().code()) < NUMBER_KEY_LED_BUFFER)
+            .collect();
+        if size.0 == 0 || size.1 == 0 || keys.is_empty() {
+            return;
+        }
+        // bounds of the keys, each key covers one unit from its position
+        let min_x = keys
+            .iter()
+            .map(|key| key.key_pos().x())
+            .fold(f64::INFINITY, f64::min);
+        let max_x = keys
+            .iter()
+            .map(|key| key.key_pos().x())
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min_y = keys
+            .iter()
+            .map(|key| key.key_pos().y())
+            .fold(f64::INFINITY, f64::min);
+        let max_y = keys
+            .iter()
+            .map(|key| key.key_pos().y())
+            .fold(f64::NEG_INFINITY, f64::max);
+        let span_x = max_x - min_x + 1_f64;
+        let span_y = max_y - min_y + 1_f64;
+
+        // fraction of the way to the new colors
+        let factor = match self.last_frame {
+            Some(last) if self.smoothing > 0_f64 => {
+                let delta = elapsed.saturating_sub(last).as_secs_f64();
+                1_f64 - (-delta / self.smoothing).exp()
+            }
+            _ => 1_f64,
+        };
+        self.last_frame = Some(elapsed);
+
+        for key in keys {
+            let index = usize::from(key.key_code_light().code());
+            let position = key.key_pos();
+            let left = (position.x() - min_x) / span_x;
+            // the y axis of the layout goes up and the one of the image goes down
+            let top = (max_y - position.y()) / span_y;
+            let target = self.average(
+                size,
+                [left, left + 1_f64 / span_x],
+                [top, top + 1_f64 / span_y],
+            );
+            let current = &mut self.colors[index];
+            for (channel, target) in current.iter_mut().zip(target.iter()) {
+                *channel += (target - *channel) * factor;
+            }
+            let [r, g, b] = current.map(|channel| channel.round().clamp(0_f64, 255_f64) as u8);
+            buffer[index] = ColorRgb::new(r, g, b);
+        }
+    }
+}
+
+impl<S: ImageSource> Display for Ambilight<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ambilight smoothed over {} s", self.smoothing)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::{KeyName, LayoutFrCh};
+
+    /// Source switching from black to white after one second
+    struct Switch {
+        /// Whether the image is white
+        white: bool,
+    }
+
+    impl ImageSource for Switch {
+        fn size(&self) -> (u32, u32) {
+            (4, 4)
+        }
+
+        fn pixel(&self, _x: u32, _y: u32) -> ColorRgb {
+            if self.white {
+                ColorRgb::new(255, 255, 255)
+            } else {
+                ColorRgb::new(0, 0, 0)
+            }
+        }
+
+        fn update(&mut self, elapsed: Duration) {
+            self.white = elapsed >= Duration::from_secs(1);
+        }
+    }
+
+    #[test]
+    fn regions() {
+        let layout = LayoutFrCh::new();
+        // top half green, bottom half red
+        let image = StaticImage::from_fn(230, 60, |_, y| {
+            if y < 30 {
+                ColorRgb::new(0, 255, 0)
+            } else {
+                ColorRgb::new(255, 0, 0)
+            }
+        });
+        let mut effect = Ambilight::new(image);
+        let mut buffer = ColorBuffer::new();
+        effect.render(&mut buffer, &layout, Duration::ZERO);
+        let color = |key| *buffer.get_key(&layout, key).unwrap();
+        assert_eq!(color(KeyName::Escape), ColorRgb::new(0, 255, 0));
+        assert_eq!(color(KeyName::Tab), ColorRgb::new(0, 255, 0));
+        assert_eq!(color(KeyName::LeftShift), ColorRgb::new(255, 0, 0));
+        assert_eq!(color(KeyName::LeftControl), ColorRgb::new(255, 0, 0));
+
+        assert_eq!(
+            StaticImage::new(2, 2, vec![ColorRgb::new(0, 0, 0); 3]),
+            None
+        );
+        let empty = StaticImage::new(0, 0, Vec::new()).unwrap();
+        let mut buffer = ColorBuffer::from_element(ColorRgb::new(1, 2, 3));
+        Ambilight::new(empty).render(&mut buffer, &layout, Duration::ZERO);
+        assert_eq!(buffer, ColorBuffer::from_element(ColorRgb::new(1, 2, 3)));
+    }
+
+    #[test]
+    fn average() {
+        // a checkerboard averages to grey
+        let image = StaticImage::from_fn(16, 16, |x, y| {
+            if (x + y) % 2 == 0 {
+                ColorRgb::new(255, 255, 255)
+            } else {
+                ColorRgb::new(0, 0, 0)
+            }
+        });
+        let effect = Ambilight::new(image).with_samples(16);
+        assert_eq!(effect.samples(), 16);
+        let average = effect.average((16, 16), [0_f64, 1_f64], [0_f64, 1_f64]);
+        assert_eq!(average, [127.5_f64; 3]);
+        // a region smaller than a pixel reads one pixel
+        let average = effect.average((16, 16), [0_f64, 0.01_f64], [0_f64, 0.01_f64]);
+        assert_eq!(average, [255_f64; 3]);
+        assert_eq!(effect.with_samples(0).samples(), 1);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn smoothing() {
+        let layout = LayoutFrCh::new();
+        let mut effect =
+            Ambilight::new(Switch { white: false }).with_smoothing(Duration::from_secs(1));
+        assert_eq!(effect.smoothing(), Duration::from_secs(1));
+        let mut buffer = ColorBuffer::new();
+        let escape =
+            |buffer: &ColorBuffer<ColorRgb>| *buffer.get_key(&layout, KeyName::Escape).unwrap();
+
+        effect.render(&mut buffer, &layout, Duration::ZERO);
+        assert_eq!(escape(&buffer), ColorRgb::new(0, 0, 0));
+        // white from one second, about 63 % of the way after one time constant
+        effect.render(&mut buffer, &layout, Duration::from_secs(1));
+        assert!(effect.source().white);
+        let expected = (255_f64 * (1_f64 - (-1_f64).exp())).round() as u8;
+        assert_eq!(escape(&buffer), ColorRgb::new(expected, expected, expected));
+        // converges to white
+        effect.render(&mut buffer, &layout, Duration::from_secs(60));
+        assert_eq!(escape(&buffer), ColorRgb::new(255, 255, 255));
+
+        // without smoothing the colors follow immediately
+        effect.source_mut().white = false;
+        let mut effect = Ambilight::new(effect.into_source());
+        effect.render(&mut buffer, &layout, Duration::from_secs(2));
+        assert_eq!(escape(&buffer), ColorRgb::new(255, 255, 255));
+        effect.render(&mut buffer, &layout, Duration::ZERO);
+        assert_eq!(escape(&buffer), ColorRgb::new(0, 0, 0));
+    }
+}
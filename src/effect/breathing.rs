@@ -0,0 +1,133 @@
+//! Contains the effect [`Breathing`]
+
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{check_duration, check_unit, Effect};
+use crate::{
+    color::{ColorBuffer, ColorRgb, ColorSpace, Value},
+    layout::Layout,
+};
+
+/// Fade the brightness in and out.
+///
+/// The brightness starts at its maximum, goes down to `min_brightness` at the half of the period
+/// and goes back up.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{Breathing, ColorBuffer, ColorRgb, Effect, LayoutFrCh, Value};
+///
+/// let mut effect = Breathing::new(Duration::from_secs(4), Value::new(0_f64).unwrap()).unwrap();
+/// let mut buffer = ColorBuffer::from_element(ColorRgb::new(200, 100, 0));
+/// effect.render(&mut buffer, &LayoutFrCh::new(), Duration::from_secs(2));
+/// assert_eq!(buffer[0], ColorRgb::new(0, 0, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(default, deny_unknown_fields))]
+pub struct Breathing {
+    /// Duration of a cycle in seconds
+    period: f64,
+    /// Brightness at the lowest point, between 0 and 1
+    min_brightness: f64,
+}
+
+impl Breathing {
+    /// Create a new effect, returns [`None`] if the period is zero.
+    pub fn new(period: Duration, min_brightness: Value) -> Option<Self> {
+        let effect = Self {
+            period: period.as_secs_f64(),
+            min_brightness: min_brightness.value(),
+        };
+        effect.validate().ok().map(|_| effect)
+    }
+
+    /// Get the duration of a cycle
+    pub fn period(&self) -> Duration {
+        Duration::from_secs_f64(self.period)
+    }
+
+    /// Get the brightness at the lowest point
+    pub fn min_brightness(&self) -> Value {
+        Value::new(self.min_brightness).unwrap_or_default()
+    }
+
+    /// Check the parameters, used after deserialization.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        check_duration(
+            self.period,
+            "the period must be a strictly positive duration",
+        )?;
+        check_unit(
+            self.min_brightness,
+            "the minimum brightness must be between 0 and 1",
+        )
+    }
+
+    /// Get the brightness factor at `elapsed`
+    fn brightness(&self, elapsed: Duration) -> f64 {
+        let phase = elapsed.as_secs_f64() / self.period;
+        let wave = (2_f64 * PI * phase).cos().mul_add(0.5_f64, 0.5_f64);
+        (1_f64 - self.min_brightness).mul_add(wave, self.min_brightness)
+    }
+}
+
+impl Default for Breathing {
+    /// Cycle of 4 seconds, fading down to black
+    fn default() -> Self {
+        Self {
+            period: 4_f64,
+            min_brightness: 0_f64,
+        }
+    }
+}
+
+impl Effect for Breathing {
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        _layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        let darkness = 1_f64 - self.brightness(elapsed);
+        for color in buffer.iter_mut() {
+            *color = color.lerp(ColorRgb::new(0, 0, 0), darkness, ColorSpace::Rgb);
+        }
+    }
+}
+
+impl Display for Breathing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "breathing every {} s down to {}",
+            self.period, self.min_brightness
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn brightness() {
+        let effect = Breathing::new(Duration::from_secs(2), Value::new(0.5_f64).unwrap()).unwrap();
+        assert_abs_diff_eq!(effect.brightness(Duration::ZERO), 1_f64);
+        assert_abs_diff_eq!(effect.brightness(Duration::from_secs(1)), 0.5_f64);
+        assert_abs_diff_eq!(effect.brightness(Duration::from_millis(500)), 0.75_f64);
+        assert_abs_diff_eq!(effect.brightness(Duration::from_secs(2)), 1_f64);
+        assert_eq!(
+            Breathing::new(Duration::ZERO, Value::new(0.5_f64).unwrap()),
+            None
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Contains [`EffectConfig`], the effects that can be described in a configuration file
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{Breathing, Effect, GradientSweep, Marquee, Reactive, Wave};
+use crate::{
+    color::{ColorBuffer, ColorRgb},
+    layout::{KeyPress, Layout},
+};
+
+/// One of the effects provided by the library with its parameters.
+///
+/// Serialized with a field `type` containing the name of the effect.
+/// # Example
+/// ```
+/// # #[cfg(feature = "serde-serialize")]
+/// # {
+/// use roccat_vulcan_api_rs::{EffectConfig, Wave};
+///
+/// let effect: EffectConfig = serde_json::from_str(r#"{"type": "Wave", "speed": 1.0}"#).unwrap();
+/// assert!(matches!(effect, EffectConfig::Wave(_)));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(tag = "type"))]
+#[non_exhaustive]
+pub enum EffectConfig {
+    /// See [`Breathing`]
+    Breathing(Breathing),
+    /// See [`Wave`]
+    Wave(Wave),
+    /// See [`GradientSweep`]
+    Gradient(GradientSweep),
+    /// See [`Reactive`]
+    Reactive(Reactive),
+    /// See [`Marquee`]
+    Marquee(Marquee),
+}
+
+impl EffectConfig {
+    /// Check the parameters of the effect, returns the reason if they are invalid.
+    ///
+    /// The constructors of the effects already check the parameters,
+    /// this is needed when the effect is deserialized.
+    /// # Errors
+    /// Returns a message describing the invalid parameter.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match self {
+            Self::Breathing(effect) => effect.validate(),
+            Self::Wave(effect) => effect.validate(),
+            Self::Gradient(effect) => effect.validate(),
+            Self::Reactive(effect) => effect.validate(),
+            Self::Marquee(effect) => effect.validate(),
+        }
+    }
+
+    /// Get the effect as a trait object
+    fn effect_mut(&mut self) -> &mut dyn Effect {
+        match self {
+            Self::Breathing(effect) => effect,
+            Self::Wave(effect) => effect,
+            Self::Gradient(effect) => effect,
+            Self::Reactive(effect) => effect,
+            Self::Marquee(effect) => effect,
+        }
+    }
+}
+
+impl Effect for EffectConfig {
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        self.effect_mut().render(buffer, layout, elapsed);
+    }
+
+    fn key_press(&mut self, key_press: KeyPress, layout: &dyn Layout, elapsed: Duration) {
+        self.effect_mut().key_press(key_press, layout, elapsed);
+    }
+}
+
+impl From<Breathing> for EffectConfig {
+    fn from(effect: Breathing) -> Self {
+        Self::Breathing(effect)
+    }
+}
+
+impl From<Wave> for EffectConfig {
+    fn from(effect: Wave) -> Self {
+        Self::Wave(effect)
+    }
+}
+
+impl From<GradientSweep> for EffectConfig {
+    fn from(effect: GradientSweep) -> Self {
+        Self::Gradient(effect)
+    }
+}
+
+impl From<Reactive> for EffectConfig {
+    fn from(effect: Reactive) -> Self {
+        Self::Reactive(effect)
+    }
+}
+
+impl From<Marquee> for EffectConfig {
+    fn from(effect: Marquee) -> Self {
+        Self::Marquee(effect)
+    }
+}
+
+impl Display for EffectConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Breathing(effect) => write!(f, "{}", effect),
+            Self::Wave(effect) => write!(f, "{}", effect),
+            Self::Gradient(effect) => write!(f, "{}", effect),
+            Self::Reactive(effect) => write!(f, "{}", effect),
+            Self::Marquee(effect) => write!(f, "{}", effect),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde-serialize"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serde() {
+        let effect: EffectConfig = serde_json::from_str(r#"{"type": "Breathing"}"#).unwrap();
+        assert_eq!(effect, EffectConfig::Breathing(Breathing::default()));
+        assert!(effect.validate().is_ok());
+
+        let effect: EffectConfig =
+            serde_json::from_str(r#"{"type": "Breathing", "period": 0}"#).unwrap();
+        assert!(effect.validate().is_err());
+        // too long to be a duration
+        let effect: EffectConfig =
+            serde_json::from_str(r#"{"type": "Breathing", "period": 1e30}"#).unwrap();
+        assert!(effect.validate().is_err());
+        let effect: EffectConfig =
+            serde_json::from_str(r#"{"type": "Reactive", "fade": 1e30}"#).unwrap();
+        assert!(effect.validate().is_err());
+
+        let effect: EffectConfig = serde_json::from_str(
+            r##"{
+                "type": "Gradient",
+                "gradient": {
+                    "stops": [{"position": 0, "color": "red"}, {"position": 1, "color": "#0000ff"}]
+                }
+            }"##,
+        )
+        .unwrap();
+        assert!(effect.validate().is_ok());
+        let string = serde_json::to_string(&effect).unwrap();
+        assert_eq!(
+            serde_json::from_str::<EffectConfig>(&string).unwrap(),
+            effect
+        );
+
+        let effect: EffectConfig = serde_json::from_str(
+            r#"{"type": "Marquee", "text": "BUILD OK", "color": "lime", "background": "black"}"#,
+        )
+        .unwrap();
+        assert!(effect.validate().is_ok());
+        assert!(
+            matches!(effect, EffectConfig::Marquee(ref marquee) if marquee.text() == "BUILD OK")
+        );
+
+        assert!(serde_json::from_str::<EffectConfig>(r#"{"type": "Wave", "sped": 1}"#).is_err());
+        assert!(serde_json::from_str::<EffectConfig>(r#"{"type": "Sparkle"}"#).is_err());
+    }
+}
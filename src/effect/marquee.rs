@@ -0,0 +1,416 @@
+//! Contains the effect [`Marquee`] and the rasterizer [`TextBitmap`]
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{check_finite, Effect};
+use crate::{
+    color::{ColorBuffer, ColorRgb, NUMBER_KEY_LED_BUFFER},
+    layout::Layout,
+};
+
+mod font;
+use font::{glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+/// Text drawn with the built-in pixel font, as columns of pixels.
+///
+/// The glyphs are three pixels wide and [`Self::HEIGHT`] pixels high, separated by an empty
+/// column. The lowercase letters are drawn in uppercase and the characters missing from the
+/// font as a question mark.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::TextBitmap;
+///
+/// let bitmap = TextBitmap::new("OK");
+/// assert_eq!(bitmap.width(), 7);
+/// // the top of the O
+/// assert!(!bitmap.pixel(0, 0));
+/// assert!(bitmap.pixel(1, 0));
+/// // the space between the letters
+/// assert!(!bitmap.pixel(3, 2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TextBitmap {
+    /// Columns from left to right, the bit `n` is the pixel of the row `n` from the top
+    columns: Vec<u8>,
+}
+
+impl TextBitmap {
+    /// Height of the text in pixels
+    pub const HEIGHT: usize = GLYPH_HEIGHT;
+
+    /// Draw `text`
+    pub fn new(text: &str) -> Self {
+        let mut columns = Vec::new();
+        for (index, character) in text.chars().enumerate() {
+            if index > 0 {
+                columns.push(0);
+            }
+            let rows = glyph(character);
+            for column in 0..GLYPH_WIDTH {
+                let mask = 1_u8 << (GLYPH_WIDTH - 1 - column);
+                columns.push(
+                    rows.iter()
+                        .enumerate()
+                        .filter(|(_, row)| *row & mask != 0)
+                        .fold(0_u8, |acc, (row, _)| acc | 1_u8 << row),
+                );
+            }
+        }
+        Self { columns }
+    }
+
+    /// Get the width of the text in pixels
+    pub const fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Get whether the pixel at the column `x` and the row `y` from the top is lit,
+    /// `false` outside of the text
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        y < Self::HEIGHT && self.columns.get(x).copied().unwrap_or(0) & 1_u8 << y != 0
+    }
+}
+
+/// Position of a key on the grid of the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    /// Index of the light
+    index: usize,
+    /// Column from the left
+    column: usize,
+    /// Row of the text from the top, [`None`] for the rows above the text
+    row: Option<usize>,
+}
+
+/// Get the keys of `layout` with a light on the grid of columns and rows, and the number of
+/// columns. The text covers the bottom [`TextBitmap::HEIGHT`] rows.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn grid(layout: &dyn Layout) -> (Vec<Cell>, usize) {
+    let keys: Vec<_> = layout
+        .layout()
+        .iter()
+        .filter(|key| usize::from(key.key_code_light().code()) < NUMBER_KEY_LED_BUFFER)
+        .collect();
+    let (min_x, min_y, max_y) = keys.iter().fold(
+        (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+        |(min_x, min_y, max_y), key| {
+            let position = key.key_pos();
+            (
+                min_x.min(position.x().round()),
+                min_y.min(position.y().round()),
+                max_y.max(position.y().round()),
+            )
+        },
+    );
+    let rows = (max_y - min_y) as usize + 1;
+    let top = rows.saturating_sub(TextBitmap::HEIGHT);
+    let cells: Vec<Cell> = keys
+        .iter()
+        .map(|key| {
+            let position = key.key_pos();
+            let row = (max_y - position.y().round()) as usize;
+            Cell {
+                index: usize::from(key.key_code_light().code()),
+                column: (position.x().round() - min_x) as usize,
+                row: row.checked_sub(top),
+            }
+        })
+        .collect();
+    let columns = cells.iter().map(|cell| cell.column + 1).max().unwrap_or(0);
+    (cells, columns)
+}
+
+/// Scroll a text across the keys from right to left, drawn with the built-in pixel font.
+///
+/// The keys are placed on a grid by rounding their [`crate::Position`], and the text covers
+/// the bottom five rows. The text enters from the right edge, leaves by the left edge, and
+/// starts again.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{ColorBuffer, ColorRgb, Effect, KeyName, LayoutFrCh, Marquee};
+///
+/// let red = ColorRgb::new(255, 0, 0);
+/// let black = ColorRgb::new(0, 0, 0);
+/// let mut effect = Marquee::new("I", red, 10_f64)
+///     .unwrap()
+///     .with_background(black);
+/// let layout = LayoutFrCh::new();
+///
+/// // the text has moved by 23 columns, the I is on the left keys
+/// let mut buffer = ColorBuffer::new();
+/// effect.render(&mut buffer, &layout, Duration::from_millis(2350));
+/// assert_eq!(buffer.get_key(&layout, KeyName::Section), Some(&red));
+/// assert_eq!(buffer.get_key(&layout, KeyName::Tab), Some(&black));
+/// assert_eq!(buffer.get_key(&layout, KeyName::Q), Some(&red));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(default, deny_unknown_fields))]
+pub struct Marquee {
+    /// Text scrolled
+    text: String,
+    /// Color of the text
+    color: ColorRgb,
+    /// Color of the other keys, [`None`] to keep the colors below the effect
+    background: Option<ColorRgb>,
+    /// Number of columns scrolled per second
+    speed: f64,
+}
+
+impl Marquee {
+    /// Lowest speed, one column every 100 seconds
+    pub const MIN_SPEED: f64 = 0.01_f64;
+
+    /// Create a new effect scrolling at `speed` columns per second, returns [`None`] if `speed`
+    /// is not finite or is below [`Self::MIN_SPEED`].
+    pub fn new(text: impl Into<String>, color: ColorRgb, speed: f64) -> Option<Self> {
+        let effect = Self {
+            text: text.into(),
+            color,
+            background: None,
+            speed,
+        };
+        effect.validate().ok().map(|_| effect)
+    }
+
+    /// Set the color of the keys outside of the text, by default the colors below the effect
+    /// are kept
+    pub const fn with_background(mut self, background: ColorRgb) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Get the text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the color of the text
+    pub const fn color(&self) -> ColorRgb {
+        self.color
+    }
+
+    /// Get the color of the keys outside of the text
+    pub const fn background(&self) -> Option<ColorRgb> {
+        self.background
+    }
+
+    /// Get the number of columns scrolled per second
+    pub const fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Get the number of steps of one pass of the text on `layout`, one step per column
+    pub fn steps(&self, layout: &dyn Layout) -> usize {
+        (TextBitmap::new(&self.text).width() + grid(layout).1).max(1)
+    }
+
+    /// Get the duration of one pass of the text on `layout`
+    pub fn period(&self, layout: &dyn Layout) -> Duration {
+        Duration::try_from_secs_f64(self.steps(layout) as f64 / self.speed).unwrap_or(Duration::MAX)
+    }
+
+    /// Draw the step `step` of the scrolling, the text starts outside on the right at the
+    /// step 0 and the steps repeat after [`Self::steps`].
+    pub fn rasterize(&self, buffer: &mut ColorBuffer<ColorRgb>, layout: &dyn Layout, step: usize) {
+        let bitmap = TextBitmap::new(&self.text);
+        let (cells, columns) = grid(layout);
+        let step = step % (bitmap.width() + columns).max(1);
+        for cell in cells {
+            let lit = match (cell.row, (cell.column + step).checked_sub(columns)) {
+                (Some(row), Some(x)) => bitmap.pixel(x, row),
+                _ => false,
+            };
+            if lit {
+                buffer[cell.index] = self.color;
+            } else if let Some(background) = self.background {
+                buffer[cell.index] = background;
+            }
+        }
+    }
+
+    /// Get the frames of one pass of the text on `layout`, one frame per step. The keys outside
+    /// of the text are black if there is no background.
+    pub fn frames(&self, layout: &dyn Layout) -> Vec<ColorBuffer<ColorRgb>> {
+        (0..self.steps(layout))
+            .map(|step| {
+                let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+                self.rasterize(&mut buffer, layout, step);
+                buffer
+            })
+            .collect()
+    }
+
+    /// Check the parameters, used after deserialization.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        check_finite(self.speed, "the speed must be finite")?;
+        if self.speed < Self::MIN_SPEED {
+            return Err("the speed must be at least Marquee::MIN_SPEED");
+        }
+        Ok(())
+    }
+}
+
+impl Default for Marquee {
+    /// Empty white text scrolling at 8 columns per second
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            color: ColorRgb::new(255, 255, 255),
+            background: None,
+            speed: 8_f64,
+        }
+    }
+}
+
+impl Effect for Marquee {
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        let step = (elapsed.as_secs_f64() * self.speed) as u64;
+        let steps = self.steps(layout) as u64;
+        self.rasterize(buffer, layout, (step % steps) as usize);
+    }
+}
+
+impl Display for Marquee {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\"{}\" scrolling at {} columns per second",
+            self.text, self.speed
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::{KeyName, LayoutFrCh};
+
+    #[test]
+    fn bitmap() {
+        let bitmap = TextBitmap::new("Hi!");
+        assert_eq!(bitmap, TextBitmap::new("HI!"));
+        assert_eq!(bitmap.width(), 11);
+        let rows: Vec<String> = (0..TextBitmap::HEIGHT)
+            .map(|y| {
+                (0..bitmap.width())
+                    .map(|x| if bitmap.pixel(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                "#.#.###..#.",
+                "#.#..#...#.",
+                "###..#...#.",
+                "#.#..#.....",
+                "#.#.###..#.",
+            ]
+        );
+        assert!(!bitmap.pixel(11, 0));
+        assert!(!bitmap.pixel(0, TextBitmap::HEIGHT));
+        assert_eq!(TextBitmap::new("").width(), 0);
+        assert_eq!(TextBitmap::new("\u{e9}"), TextBitmap::new("?"));
+        // every glyph fits in its width
+        for character in (' '..='~').chain(std::iter::once('\u{e9}')) {
+            assert!(glyph(character).iter().all(|row| *row < 1 << GLYPH_WIDTH));
+        }
+    }
+
+    #[test]
+    fn grid_of_keys() {
+        let layout = LayoutFrCh::new();
+        let (cells, columns) = grid(&layout);
+        assert_eq!(columns, 23);
+        let cell = |key| {
+            let index = usize::from(
+                layout
+                    .find_from_key_name(key)
+                    .unwrap()
+                    .key_code_light()
+                    .code(),
+            );
+            *cells.iter().find(|cell| cell.index == index).unwrap()
+        };
+        assert_eq!(cell(KeyName::Escape).row, None);
+        assert_eq!(cell(KeyName::Section).row, Some(0));
+        assert_eq!(cell(KeyName::Section).column, 0);
+        assert_eq!(cell(KeyName::LeftControl).row, Some(4));
+        assert_eq!(cell(KeyName::NumEnter).column, columns - 1);
+    }
+
+    #[test]
+    fn frames() {
+        let layout = LayoutFrCh::new();
+        let red = ColorRgb::new(255, 0, 0);
+        let black = ColorRgb::new(0, 0, 0);
+        let effect = Marquee::new("BUILD OK", red, 4_f64).unwrap();
+        let width = TextBitmap::new("BUILD OK").width();
+        assert_eq!(effect.steps(&layout), width + 23);
+        assert_eq!(
+            effect.period(&layout),
+            Duration::from_secs_f64((width + 23) as f64 / 4_f64)
+        );
+        let frames = effect.frames(&layout);
+        assert_eq!(frames.len(), width + 23);
+        // the text is outside of the keys at the start
+        assert!(frames[0].iter().all(|color| *color == black));
+        // the first column of the B on the right edge
+        let lit: Vec<KeyName> = layout
+            .layout()
+            .iter()
+            .filter(|key| frames[1].get(usize::from(key.key_code_light().code())) == Some(&red))
+            .map(|key| *key.key_name())
+            .collect();
+        assert_eq!(
+            lit,
+            vec![KeyName::NumMinus, KeyName::NumPlus, KeyName::NumEnter]
+        );
+
+        // the effect loops over the frames
+        let mut effect = effect.with_background(black);
+        for (step, frame) in frames.iter().enumerate().step_by(7) {
+            let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 255, 0));
+            let elapsed = Duration::from_secs_f64((step + frames.len()) as f64 / 4_f64 + 0.01_f64);
+            effect.render(&mut buffer, &layout, elapsed);
+            for key in layout.layout() {
+                let index = usize::from(key.key_code_light().code());
+                if index < NUMBER_KEY_LED_BUFFER {
+                    assert_eq!(buffer[index], frame[index]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parameters() {
+        assert!(Marquee::new("text", ColorRgb::new(0, 0, 0), 0_f64).is_none());
+        assert!(Marquee::new("text", ColorRgb::new(0, 0, 0), f64::NAN).is_none());
+        assert!(Marquee::new("text", ColorRgb::new(0, 0, 0), 1E-300_f64).is_none());
+        let slowest = Marquee::new("text", ColorRgb::new(0, 0, 0), Marquee::MIN_SPEED).unwrap();
+        assert!(slowest.period(&LayoutFrCh::new()) > Duration::from_secs(100));
+        let effect = Marquee::new("text", ColorRgb::new(0, 0, 0), 2_f64).unwrap();
+        assert!(effect.validate().is_ok());
+        assert_eq!(effect.text(), "text");
+        assert_eq!(effect.background(), None);
+        assert_eq!(
+            effect.to_string(),
+            "\"text\" scrolling at 2 columns per second"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Contains the effect [`Reactive`]
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{check_duration, Effect};
+use crate::{
+    color::{ColorBuffer, ColorRgb, ColorSpace, NUMBER_KEY_LED_BUFFER},
+    layout::{KeyPress, Layout},
+};
+
+/// Light up the keys when they are pressed, then fade back when they are released.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorBuffer, ColorRgb, Effect, KeyCode, KeyName, KeyPress, LayoutFrCh, Reactive,
+/// };
+///
+/// let layout = LayoutFrCh::new();
+/// let mut effect = Reactive::new(ColorRgb::new(255, 0, 0), Duration::from_secs(1)).unwrap();
+/// // the key A
+/// let key_code = KeyCode::new(251, 28);
+/// effect.key_press(KeyPress::new(key_code, true), &layout, Duration::ZERO);
+///
+/// let mut buffer = ColorBuffer::new();
+/// effect.render(&mut buffer, &layout, Duration::from_secs(5));
+/// assert_eq!(
+///     buffer.get_key(&layout, KeyName::A),
+///     Some(&ColorRgb::new(255, 0, 0))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(default, deny_unknown_fields))]
+pub struct Reactive {
+    /// Color of the pressed keys
+    color: ColorRgb,
+    /// Duration of the fade in seconds
+    fade: f64,
+    /// Light code of the keys lit, with the time of release or [`None`] if still pressed
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    lit: HashMap<usize, Option<Duration>>,
+}
+
+impl Reactive {
+    /// Create a new effect, returns [`None`] if `fade` is zero.
+    pub fn new(color: ColorRgb, fade: Duration) -> Option<Self> {
+        let effect = Self {
+            color,
+            fade: fade.as_secs_f64(),
+            lit: HashMap::new(),
+        };
+        effect.validate().ok().map(|_| effect)
+    }
+
+    /// Get the color of the pressed keys
+    pub const fn color(&self) -> ColorRgb {
+        self.color
+    }
+
+    /// Get the duration of the fade
+    pub fn fade(&self) -> Duration {
+        Duration::from_secs_f64(self.fade)
+    }
+
+    /// Check the parameters, used after deserialization.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        check_duration(self.fade, "the fade must be a strictly positive duration")
+    }
+}
+
+impl Default for Reactive {
+    /// White fading in half a second
+    fn default() -> Self {
+        Self {
+            color: ColorRgb::new(255, 255, 255),
+            fade: 0.5_f64,
+            lit: HashMap::new(),
+        }
+    }
+}
+
+impl Effect for Reactive {
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        _layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        let fade = self.fade;
+        let color = self.color;
+        self.lit.retain(|index, released| {
+            let strength = match released {
+                None => 1_f64,
+                Some(time) => 1_f64 - elapsed.saturating_sub(*time).as_secs_f64() / fade,
+            };
+            if strength <= 0_f64 {
+                return false;
+            }
+            let current = &mut buffer[*index];
+            *current = current.lerp(color, strength, ColorSpace::Rgb);
+            true
+        });
+    }
+
+    fn key_press(&mut self, key_press: KeyPress, layout: &dyn Layout, elapsed: Duration) {
+        let index = match layout.find_from_key_code(*key_press.key_code()) {
+            Some(info) => usize::from(info.key_code_light().code()),
+            None => return,
+        };
+        if index < NUMBER_KEY_LED_BUFFER {
+            let released = if key_press.is_pressed() {
+                None
+            } else {
+                Some(elapsed)
+            };
+            self.lit.insert(index, released);
+        }
+    }
+}
+
+impl Display for Reactive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "reactive {} fading in {} s", self.color, self.fade)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::{KeyName, LayoutFrCh};
+
+    #[test]
+    fn fade() {
+        let layout = LayoutFrCh::new();
+        let info = layout.find_from_key_name(KeyName::Space).unwrap();
+        let key_code = *info.key_code_press();
+        let index = usize::from(info.key_code_light().code());
+        let mut effect =
+            Reactive::new(ColorRgb::new(255, 255, 255), Duration::from_secs(2)).unwrap();
+        let render = |effect: &mut Reactive, millis| {
+            let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+            effect.render(&mut buffer, &layout, Duration::from_millis(millis));
+            buffer[index]
+        };
+
+        assert_eq!(render(&mut effect, 0), ColorRgb::new(0, 0, 0));
+        effect.key_press(
+            KeyPress::new(key_code, true),
+            &layout,
+            Duration::from_millis(100),
+        );
+        assert_eq!(render(&mut effect, 1_000), ColorRgb::new(255, 255, 255));
+        effect.key_press(
+            KeyPress::new(key_code, false),
+            &layout,
+            Duration::from_millis(1_000),
+        );
+        assert_eq!(render(&mut effect, 2_000), ColorRgb::new(128, 128, 128));
+        assert_eq!(render(&mut effect, 3_000), ColorRgb::new(0, 0, 0));
+        assert!(effect.lit.is_empty());
+    }
+}
@@ -0,0 +1,387 @@
+//! Contains the effect [`Spectrum`] and its sources of samples
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use super::{check_positive, Effect};
+use crate::{
+    color::{ColorBuffer, ColorRgb, Gradient, NUMBER_KEY_LED_BUFFER},
+    layout::Layout,
+};
+
+mod fft;
+
+mod source;
+pub use source::*;
+
+/// Default number of samples analysed on each frame
+const DEFAULT_WINDOW: usize = 2048;
+/// Default lowest frequency shown in Hz
+const DEFAULT_MIN_FREQUENCY: f64 = 40_f64;
+/// Default highest frequency shown in Hz
+const DEFAULT_MAX_FREQUENCY: f64 = 16_000_f64;
+/// Default range of amplitude shown in decibels, below full scale
+const DEFAULT_RANGE: f64 = 60_f64;
+
+/// A column of keys, showing one frequency band
+#[derive(Debug, Clone, PartialEq)]
+struct Column {
+    /// Light and position along the height between 0 and 1 of the keys, from the bottom
+    keys: Vec<(usize, f64)>,
+}
+
+/// Audio spectrum: each column of keys shows the level of a frequency band.
+///
+/// The samples of a [`SampleSource`] are analysed with a fast Fourier transform. The columns
+/// of keys, ordered by [`crate::Position::x`], show the bands from the lowest frequency on
+/// the left to the highest on the right, spaced logarithmically. The keys of a column light up
+/// from the bottom in the colors of the gradient with the level of the band, the other keys
+/// are left as they are. The levels fall at `decay` per second so that the peaks stay visible.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorBuffer, ColorRgb, Gradient, LayoutKind, ProfileRenderer, SineSweep, Spectrum,
+/// };
+///
+/// let gradient =
+///     Gradient::new_evenly_spaced(&[ColorRgb::new(0, 255, 0), ColorRgb::new(255, 0, 0)]).unwrap();
+/// let sweep = SineSweep::new(44_100, 40_f64, 16_000_f64, Duration::from_secs(10)).unwrap();
+/// let layout = LayoutKind::FrCh.layout();
+/// let mut renderer =
+///     ProfileRenderer::new(layout, ColorBuffer::from_element(ColorRgb::new(0, 0, 0)));
+/// renderer.push_effect(Spectrum::new(sweep, gradient, 2_f64).unwrap(), None);
+/// let frame = renderer.frame(Duration::from_secs(1));
+/// // the buffer can then be sent to the keyboard with `KeyboardApi::render`
+/// assert_ne!(frame, ColorBuffer::from_element(ColorRgb::new(0, 0, 0)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum<S: SampleSource> {
+    /// Source of the samples
+    source: S,
+    /// Colors from the bottom to the top of the keyboard
+    gradient: Gradient,
+    /// Fall of the levels per second, a full level being 1
+    decay: f64,
+    /// Number of samples analysed, a power of two
+    window: usize,
+    /// Lowest frequency shown in Hz
+    min_frequency: f64,
+    /// Highest frequency shown in Hz
+    max_frequency: f64,
+    /// Range of amplitude shown in decibels
+    range: f64,
+    /// Levels of the bands between 0 and 1
+    levels: Vec<f64>,
+    /// Time of the previous frame
+    last_frame: Option<Duration>,
+    /// Buffer of the samples
+    samples: Vec<f32>,
+}
+
+impl<S: SampleSource> Spectrum<S> {
+    /// Create the effect, the levels falling by `decay` per second, a full level being 1.
+    ///
+    /// Returns [`None`] if `decay` is not strictly positive.
+    pub fn new(source: S, gradient: Gradient, decay: f64) -> Option<Self> {
+        check_positive(decay, "the decay must be strictly positive").ok()?;
+        Some(Self {
+            source,
+            gradient,
+            decay,
+            window: DEFAULT_WINDOW,
+            min_frequency: DEFAULT_MIN_FREQUENCY,
+            max_frequency: DEFAULT_MAX_FREQUENCY,
+            range: DEFAULT_RANGE,
+            levels: Vec::new(),
+            last_frame: None,
+            samples: Vec::new(),
+        })
+    }
+
+    /// Set the range of frequencies shown in Hz, the highest frequency is also limited
+    /// by half of the sample rate.
+    ///
+    /// Returns [`None`] if `min` is not strictly positive or if `max` is not greater than `min`.
+    pub fn with_frequencies(mut self, min: f64, max: f64) -> Option<Self> {
+        check_positive(min, "the lowest frequency must be strictly positive").ok()?;
+        check_positive(max - min, "the highest frequency must be above the lowest").ok()?;
+        self.min_frequency = min;
+        self.max_frequency = max;
+        Some(self)
+    }
+
+    /// Set the number of samples analysed on each frame, 2048 by default.
+    /// A larger window separates better the low frequencies but reacts slower.
+    ///
+    /// Returns [`None`] if `window` is not a power of two of at least 2.
+    pub fn with_window(mut self, window: usize) -> Option<Self> {
+        if window < 2 || !window.is_power_of_two() {
+            return None;
+        }
+        self.window = window;
+        Some(self)
+    }
+
+    /// Set the range of amplitude shown in decibels, 60 by default:
+    /// a sine of full amplitude gives a full level and one 60 dB quieter a level of zero.
+    ///
+    /// Returns [`None`] if `range` is not strictly positive.
+    pub fn with_range(mut self, range: f64) -> Option<Self> {
+        check_positive(range, "the range must be strictly positive").ok()?;
+        self.range = range;
+        Some(self)
+    }
+
+    /// Get the source of the samples
+    pub const fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Get a mutable reference to the source of the samples
+    pub const fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Get the gradient of the colors from the bottom to the top of the keyboard
+    pub const fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+
+    /// Get the fall of the levels per second
+    pub const fn decay(&self) -> f64 {
+        self.decay
+    }
+
+    /// Get the number of samples analysed on each frame
+    pub const fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Get the levels between 0 and 1 of the bands shown by the last frame,
+    /// from the lowest frequency to the highest
+    pub fn levels(&self) -> &[f64] {
+        &self.levels
+    }
+
+    /// Get the level between 0 and 1 of `band_count` bands logarithmically spaced
+    /// of the last samples
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn band_levels(&self, band_count: usize) -> Vec<f64> {
+        let amplitudes = fft::amplitudes(&self.samples);
+        let bin_width = f64::from(self.source.sample_rate()) / self.window as f64;
+        let max = self
+            .max_frequency
+            .min(f64::from(self.source.sample_rate()) / 2_f64);
+        let ratio = (max / self.min_frequency).max(1_f64);
+        let edge = |band: usize| {
+            self.min_frequency * ratio.powf(band as f64 / band_count as f64) / bin_width
+        };
+        (0..band_count)
+            .map(|band| {
+                let (low, high) = (edge(band), edge(band + 1));
+                let first = (low.ceil() as usize).min(amplitudes.len() - 1);
+                let last = (high.floor() as usize).min(amplitudes.len() - 1);
+                let amplitude = if first <= last {
+                    amplitudes[first..=last]
+                        .iter()
+                        .copied()
+                        .fold(0_f64, f64::max)
+                } else {
+                    // the band is narrower than a bin, take the nearest bin of its center
+                    amplitudes[((low * high).sqrt().round() as usize).min(amplitudes.len() - 1)]
+                };
+                let decibels = 20_f64 * amplitude.max(f64::MIN_POSITIVE).log10();
+                ((decibels + self.range) / self.range).clamp(0_f64, 1_f64)
+            })
+            .collect()
+    }
+}
+
+/// Group the keys that have a light in columns ordered from left to right
+#[allow(clippy::cast_possible_truncation)]
+fn columns(layout: &dyn Layout) -> Vec<Column> {
+    let keys: Vec<_> = layout
+        .layout()
+        .iter()
+        .filter(|key| usize::from(key.key_code_light().code()) < NUMBER_KEY_LED_BUFFER)
+        .collect();
+    let (min_y, max_y) = keys
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), key| {
+            (min.min(key.key_pos().y()), max.max(key.key_pos().y()))
+        });
+    let height = if max_y > min_y { max_y - min_y } else { 1_f64 };
+    // the positions are small, they are rounded to get the columns
+    let mut sorted: Vec<(i64, f64, usize)> = keys
+        .iter()
+        .map(|key| {
+            (
+                key.key_pos().x().round() as i64,
+                (key.key_pos().y() - min_y) / height,
+                usize::from(key.key_code_light().code()),
+            )
+        })
+        .collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let mut columns: Vec<(i64, Column)> = Vec::new();
+    for (x, height, light) in sorted {
+        match columns.last_mut() {
+            Some((column_x, column)) if *column_x == x => column.keys.push((light, height)),
+            _ => columns.push((
+                x,
+                Column {
+                    keys: vec![(light, height)],
+                },
+            )),
+        }
+    }
+    columns.into_iter().map(|(_, column)| column).collect()
+}
+
+impl<S: SampleSource> Effect for Spectrum<S> {
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        self.samples.resize(self.window, 0_f32);
+        self.source.read(elapsed, &mut self.samples);
+        let columns = columns(layout);
+        let levels = self.band_levels(columns.len());
+
+        let fall = self.last_frame.map_or(1_f64, |last| {
+            elapsed.saturating_sub(last).as_secs_f64() * self.decay
+        });
+        self.last_frame = Some(elapsed);
+        self.levels.resize(columns.len(), 0_f64);
+        for (level, new) in self.levels.iter_mut().zip(levels) {
+            *level = new.max(*level - fall);
+        }
+
+        for (column, level) in columns.iter().zip(&self.levels) {
+            let lit = (level * column.keys.len() as f64).round() as usize;
+            for (light, height) in column.keys.iter().take(lit) {
+                buffer[*light] = self.gradient.sample(*height);
+            }
+        }
+    }
+}
+
+impl<S: SampleSource> Display for Spectrum<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "spectrum from {} Hz to {} Hz falling by {} per second",
+            self.min_frequency, self.max_frequency, self.decay
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::{KeyName, LayoutFrCh};
+
+    /// Gradient from green at the bottom to red at the top
+    fn gradient() -> Gradient {
+        Gradient::new_evenly_spaced(&[ColorRgb::new(0, 255, 0), ColorRgb::new(255, 0, 0)]).unwrap()
+    }
+
+    #[test]
+    fn columns_of_layout() {
+        let layout = LayoutFrCh::new();
+        let columns = columns(&layout);
+        assert_eq!(columns.len(), 23);
+        // the first column goes from left control up to Escape
+        let first = &columns[0].keys;
+        let light = |key| {
+            usize::from(
+                layout
+                    .find_from_key_name(key)
+                    .unwrap()
+                    .key_code_light()
+                    .code(),
+            )
+        };
+        assert_eq!(first[0], (light(KeyName::LeftControl), 0_f64));
+        assert_eq!(first[first.len() - 1], (light(KeyName::Escape), 1_f64));
+    }
+
+    #[test]
+    fn sine() {
+        let layout = LayoutFrCh::new();
+        let sample_rate = 48_000;
+        let sine = SineSweep::constant(sample_rate, 1_000_f64).unwrap();
+        let mut effect = Spectrum::new(sine, gradient(), 1_f64)
+            .unwrap()
+            .with_range(40_f64)
+            .unwrap();
+        let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+        effect.render(&mut buffer, &layout, Duration::from_secs(1));
+
+        let levels = effect.levels().to_vec();
+        assert_eq!(levels.len(), 23);
+        let loudest = levels
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap()
+            .0;
+        // 1 kHz is at 0.54 of the logarithmic range from 40 Hz to 16 kHz
+        assert_eq!(loudest, 12);
+        assert!(levels[loudest] > 0.95_f64);
+        assert_eq!(levels[0], 0_f64);
+        assert_eq!(levels[22], 0_f64);
+        // the first column is not lit, the loudest is fully lit
+        assert_eq!(
+            buffer.get_key(&layout, KeyName::Escape),
+            Some(&ColorRgb::new(0, 0, 0))
+        );
+        for (light, height) in &columns(&layout)[loudest].keys {
+            assert_eq!(buffer[*light], gradient().sample(*height));
+        }
+
+        // after the sound stops the levels fall at the decay
+        *effect.source_mut() = SineSweep::constant(sample_rate, 20_f64).unwrap();
+        effect.render(&mut buffer, &layout, Duration::from_millis(1_500));
+        assert!((effect.levels()[loudest] - (levels[loudest] - 0.5_f64)).abs() < 1E-9_f64);
+        effect.render(&mut buffer, &layout, Duration::from_secs(3));
+        assert_eq!(effect.levels()[loudest], 0_f64);
+    }
+
+    #[test]
+    fn sweep() {
+        let layout = LayoutFrCh::new();
+        let sweep = SineSweep::new(44_100, 40_f64, 16_000_f64, Duration::from_secs(10)).unwrap();
+        let mut effect = Spectrum::new(sweep, gradient(), 100_f64).unwrap();
+        let mut buffer = ColorBuffer::new();
+        let mut previous = 0;
+        // the loudest band moves to the right
+        for second in 1..10 {
+            effect.render(&mut buffer, &layout, Duration::from_secs(second));
+            let loudest = effect
+                .levels()
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .unwrap()
+                .0;
+            assert!(loudest > previous, "{} after {}", loudest, previous);
+            previous = loudest;
+        }
+
+        let sine = SineSweep::constant(44_100, 1_000_f64).unwrap();
+        assert!(Spectrum::new(sine, gradient(), 0_f64).is_none());
+        let effect = Spectrum::new(sine, gradient(), 1_f64).unwrap();
+        assert!(effect.clone().with_window(1_000).is_none());
+        assert_eq!(effect.clone().with_window(256).unwrap().window(), 256);
+        assert!(effect.clone().with_frequencies(100_f64, 50_f64).is_none());
+        assert!(effect.with_range(-1_f64).is_none());
+    }
+}
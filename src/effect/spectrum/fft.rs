@@ -0,0 +1,132 @@
+//! Fast Fourier transform of the samples of a [`super::SampleSource`]
+
+use std::f64::consts::PI;
+
+/// Complex number
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Complex {
+    /// Real part
+    re: f64,
+    /// Imaginary part
+    im: f64,
+}
+
+impl Complex {
+    /// Create a complex number
+    const fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// Add two complex numbers
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+
+    /// Subtract two complex numbers
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+
+    /// Multiply two complex numbers
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re.mul_add(other.re, -self.im * other.im),
+            self.re.mul_add(other.im, self.im * other.re),
+        )
+    }
+
+    /// Get the modulus
+    fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+/// In place iterative radix 2 FFT, the length of `data` must be a power of two
+fn fft(data: &mut [Complex]) {
+    let len = data.len();
+    debug_assert!(len.is_power_of_two());
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..len {
+        let mut bit = len >> 1_u32;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1_u32;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let mut size = 2;
+    while size <= len {
+        let angle = -2_f64 * PI / size as f64;
+        let step = Complex::new(angle.cos(), angle.sin());
+        for start in (0..len).step_by(size) {
+            let mut twiddle = Complex::new(1_f64, 0_f64);
+            for k in 0..size / 2 {
+                let even = data[start + k];
+                let odd = data[start + k + size / 2].mul(twiddle);
+                data[start + k] = even.add(odd);
+                data[start + k + size / 2] = even.sub(odd);
+                twiddle = twiddle.mul(step);
+            }
+        }
+        size *= 2;
+    }
+}
+
+/// Get the amplitude of the frequencies of `samples`, whose length must be a power of two.
+///
+/// The bin `k` is the frequency `k * sample_rate / samples.len()`, there are
+/// `samples.len() / 2 + 1` bins. A Hann window is applied and the amplitudes are scaled
+/// so that a sine of amplitude 1 at the frequency of a bin gives 1.
+pub(super) fn amplitudes(samples: &[f32]) -> Vec<f64> {
+    let len = samples.len();
+    let mut data: Vec<Complex> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            let window = (-0.5_f64).mul_add((2_f64 * PI * i as f64 / len as f64).cos(), 0.5_f64);
+            Complex::new(f64::from(*sample) * window, 0_f64)
+        })
+        .collect();
+    fft(&mut data);
+    // the coherent gain of the Hann window is 1/2, and half of the energy is
+    // in the negative frequencies
+    let scale = 4_f64 / len as f64;
+    data[..=len / 2]
+        .iter()
+        .map(|value| value.abs() * scale)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn transform() {
+        // the transform of an impulse is flat
+        let mut data = vec![Complex::default(); 8];
+        data[0] = Complex::new(1_f64, 0_f64);
+        fft(&mut data);
+        for value in &data {
+            assert!((value.re - 1_f64).abs() < 1E-12_f64);
+            assert!(value.im.abs() < 1E-12_f64);
+        }
+
+        // a sine on the bin 8 of 64
+        let samples: Vec<f32> = (0..64_u8)
+            .map(|i| (2_f64 * PI * 8_f64 * f64::from(i) / 64_f64).sin() as f32)
+            .collect();
+        let amplitudes = amplitudes(&samples);
+        assert_eq!(amplitudes.len(), 33);
+        assert!((amplitudes[8] - 1_f64).abs() < 1E-6_f64);
+        // the Hann window spreads on the neighbouring bins only
+        assert!((amplitudes[7] - 0.5_f64).abs() < 1E-6_f64);
+        assert!(amplitudes[0] < 1E-6_f64);
+        assert!(amplitudes[20] < 1E-6_f64);
+    }
+}
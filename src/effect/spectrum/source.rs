@@ -0,0 +1,423 @@
+//! Contains the trait [`SampleSource`], [`SineSweep`] and [`WavSource`]
+
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::time::Duration;
+
+/// Source of the audio samples shown by [`super::Spectrum`], for instance a sound card.
+///
+/// The samples are mono, between -1 and 1.
+pub trait SampleSource {
+    /// Get the number of samples per second
+    fn sample_rate(&self) -> u32;
+
+    /// Fill `samples` with the last samples played at `elapsed`, the last one being
+    /// the most recent. The samples not available are set to 0.
+    ///
+    /// `elapsed` is the time since the start of the rendering.
+    fn read(&mut self, elapsed: Duration, samples: &mut [f32]);
+}
+
+impl<S: SampleSource + ?Sized> SampleSource for Box<S> {
+    fn sample_rate(&self) -> u32 {
+        (**self).sample_rate()
+    }
+
+    fn read(&mut self, elapsed: Duration, samples: &mut [f32]) {
+        (**self).read(elapsed, samples);
+    }
+}
+
+/// Index of the sample played at `elapsed`, the samples before it were already played
+#[allow(clippy::cast_possible_truncation)]
+fn sample_index(elapsed: Duration, sample_rate: u32) -> i64 {
+    (elapsed.as_secs_f64() * f64::from(sample_rate)) as i64
+}
+
+/// Sine whose frequency goes from `from` to `to` exponentially during `duration`, then repeats.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{SampleSource, SineSweep};
+///
+/// let mut sweep = SineSweep::new(44_100, 50_f64, 10_000_f64, Duration::from_secs(5)).unwrap();
+/// let mut samples = [0_f32; 1024];
+/// sweep.read(Duration::from_secs(1), &mut samples);
+/// assert!(samples
+///     .iter()
+///     .all(|sample| (-1_f32..=1_f32).contains(sample)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SineSweep {
+    /// Number of samples per second
+    sample_rate: u32,
+    /// Frequency at the start in Hz
+    from: f64,
+    /// Frequency at the end in Hz
+    to: f64,
+    /// Duration of the sweep in seconds
+    duration: f64,
+}
+
+impl SineSweep {
+    /// Create a sweep, returns [`None`] if the sample rate or the duration is zero
+    /// or if a frequency is not finite and strictly positive.
+    pub fn new(sample_rate: u32, from: f64, to: f64, duration: Duration) -> Option<Self> {
+        let valid = |frequency: f64| frequency.is_finite() && frequency > 0_f64;
+        if sample_rate == 0 || duration.is_zero() || !valid(from) || !valid(to) {
+            return None;
+        }
+        Some(Self {
+            sample_rate,
+            from,
+            to,
+            duration: duration.as_secs_f64(),
+        })
+    }
+
+    /// Create a sine of constant frequency, returns [`None`] if the sample rate is zero
+    /// or if the frequency is not finite and strictly positive.
+    pub fn constant(sample_rate: u32, frequency: f64) -> Option<Self> {
+        Self::new(sample_rate, frequency, frequency, Duration::from_secs(1))
+    }
+
+    /// Get the frequency at `elapsed` in Hz
+    pub fn frequency(&self, elapsed: Duration) -> f64 {
+        let progress = (elapsed.as_secs_f64() / self.duration).fract();
+        self.from * (self.to / self.from).powf(progress)
+    }
+
+    /// Get the phase in radians at `time` seconds
+    fn phase(&self, time: f64) -> f64 {
+        let sweeps = (time / self.duration).floor();
+        let time = sweeps.mul_add(-self.duration, time);
+        let ratio = self.to / self.from;
+        if (ratio - 1_f64).abs() < f64::EPSILON {
+            2_f64 * PI * self.from * time
+        } else {
+            // integral of the frequency, the phase restarts with each sweep
+            let rate = ratio.ln() / self.duration;
+            2_f64 * PI * self.from * (rate * time).exp_m1() / rate
+        }
+    }
+}
+
+impl SampleSource for SineSweep {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    fn read(&mut self, elapsed: Duration, samples: &mut [f32]) {
+        let end = sample_index(elapsed, self.sample_rate);
+        let len = samples.len() as i64;
+        for (index, sample) in (end - len..end).zip(samples.iter_mut()) {
+            *sample = if index < 0 {
+                0_f32
+            } else {
+                self.phase(index as f64 / f64::from(self.sample_rate)).sin() as f32
+            };
+        }
+    }
+}
+
+/// Samples of a WAV file, played once from the start of the rendering.
+///
+/// The file is read entirely and the channels are mixed down to mono. The integer PCM
+/// formats of 8, 16, 24 and 32 bits and the 32 bits floating point format are supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavSource {
+    /// Number of samples per second
+    sample_rate: u32,
+    /// Mono samples
+    samples: Vec<f32>,
+}
+
+impl WavSource {
+    /// Create a source from mono samples
+    pub const fn new(sample_rate: u32, samples: Vec<f32>) -> Self {
+        Self {
+            sample_rate,
+            samples,
+        }
+    }
+
+    /// Read the WAV file at `path`
+    /// # Errors
+    /// Returns the errors of the file, or an error of kind [`io::ErrorKind::InvalidData`]
+    /// if it is not a supported WAV file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Read a WAV file from `reader`
+    /// # Errors
+    /// Returns the errors of `reader`, or an error of kind [`io::ErrorKind::InvalidData`]
+    /// if it is not a supported WAV file.
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut header = [0_u8; 12];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != b"RIFF" || &header[8..] != b"WAVE" {
+            return Err(invalid_data("not a RIFF WAVE file"));
+        }
+        let mut format = None;
+        loop {
+            let mut chunk = [0_u8; 8];
+            reader.read_exact(&mut chunk)?;
+            let len = u64::from(u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]));
+            // chunks are padded to an even length
+            let padded_len = len + len % 2;
+            if &chunk[..4] != b"fmt " && &chunk[..4] != b"data" {
+                // the unknown chunks are skipped without being stored
+                if io::copy(&mut reader.by_ref().take(padded_len), &mut io::sink())? != padded_len {
+                    return Err(truncated());
+                }
+                continue;
+            }
+            // the length is not trusted, the data grows with what is actually read
+            let mut data = Vec::new();
+            if reader.by_ref().take(len).read_to_end(&mut data)?
+                != usize::try_from(len).unwrap_or(usize::MAX)
+            {
+                return Err(truncated());
+            }
+            if len % 2 == 1 {
+                reader.read_exact(&mut [0_u8])?;
+            }
+            if &chunk[..4] == b"fmt " {
+                format = Some(WavFormat::parse(&data)?);
+            } else {
+                let format = format.ok_or_else(|| invalid_data("data before fmt chunk"))?;
+                return Ok(Self::new(format.sample_rate, format.decode(&data)));
+            }
+        }
+    }
+
+    /// Get the mono samples
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Get the duration of the samples
+    pub fn duration(&self) -> Duration {
+        if self.sample_rate == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.samples.len() as f64 / f64::from(self.sample_rate))
+    }
+}
+
+impl SampleSource for WavSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn read(&mut self, elapsed: Duration, samples: &mut [f32]) {
+        let end = sample_index(elapsed, self.sample_rate);
+        let len = samples.len() as i64;
+        for (index, sample) in (end - len..end).zip(samples.iter_mut()) {
+            *sample = usize::try_from(index)
+                .ok()
+                .and_then(|index| self.samples.get(index))
+                .copied()
+                .unwrap_or(0_f32);
+        }
+    }
+}
+
+/// Create an error for a chunk shorter than its length
+fn truncated() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "invalid wav : truncated chunk",
+    )
+}
+
+/// Create an error for a file that is not a supported WAV file
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid wav : {}", message),
+    )
+}
+
+/// Encoding of the samples of a WAV file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WavFormat {
+    /// Number of samples per second
+    sample_rate: u32,
+    /// Number of channels
+    channels: usize,
+    /// Number of bytes per sample of a channel
+    bytes: usize,
+    /// Whether the samples are floating point numbers
+    float: bool,
+}
+
+impl WavFormat {
+    /// Parse the content of the chunk `fmt `
+    fn parse(data: &[u8]) -> io::Result<Self> {
+        if data.len() < 16 {
+            return Err(invalid_data("fmt chunk too short"));
+        }
+        let u16_at = |index: usize| u16::from_le_bytes([data[index], data[index + 1]]);
+        let mut tag = u16_at(0);
+        if tag == 0xFFFE && data.len() >= 26 {
+            // WAVE_FORMAT_EXTENSIBLE, the format is at the start of the sub format
+            tag = u16_at(24);
+        }
+        let channels = usize::from(u16_at(2));
+        let sample_rate = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        let bits = u16_at(14);
+        let float = match (tag, bits) {
+            (1, 8 | 16 | 24 | 32) => false,
+            (3, 32) => true,
+            _ => return Err(invalid_data("unsupported sample format")),
+        };
+        if channels == 0 || sample_rate == 0 {
+            return Err(invalid_data("no channel or no sample rate"));
+        }
+        Ok(Self {
+            sample_rate,
+            channels,
+            bytes: usize::from(bits / 8),
+            float,
+        })
+    }
+
+    /// Decode the content of the chunk `data`, mixed down to mono
+    fn decode(self, data: &[u8]) -> Vec<f32> {
+        data.chunks_exact(self.bytes * self.channels)
+            .map(|frame| {
+                let sum: f32 = frame
+                    .chunks_exact(self.bytes)
+                    .map(|sample| self.decode_sample(sample))
+                    .sum();
+                sum / self.channels as f32
+            })
+            .collect()
+    }
+
+    /// Decode the sample of one channel, between -1 and 1
+    fn decode_sample(self, bytes: &[u8]) -> f32 {
+        match (self.float, bytes) {
+            (true, [a, b, c, d]) => f32::from_le_bytes([*a, *b, *c, *d]),
+            // 8 bits samples are unsigned
+            (false, [a]) => (f32::from(*a) - 128_f32) / 128_f32,
+            (false, [a, b]) => f32::from(i16::from_le_bytes([*a, *b])) / 32_768_f32,
+            (false, [a, b, c]) => i32::from_le_bytes([0, *a, *b, *c]) as f32 / 2_147_483_648_f32,
+            (false, [a, b, c, d]) => {
+                i32::from_le_bytes([*a, *b, *c, *d]) as f32 / 2_147_483_648_f32
+            }
+            _ => 0_f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Encode 16 bits stereo samples as a WAV file
+    #[allow(clippy::cast_possible_truncation)]
+    fn wav(sample_rate: u32, frames: &[[i16; 2]]) -> Vec<u8> {
+        let data: Vec<u8> = frames
+            .iter()
+            .flat_map(|frame| frame.iter().flat_map(|sample| sample.to_le_bytes()))
+            .collect();
+        let mut file = Vec::new();
+        file.extend_from_slice(b"RIFF");
+        file.extend_from_slice(&(48 + data.len() as u32).to_le_bytes());
+        file.extend_from_slice(b"WAVE");
+        // an unknown chunk is skipped
+        file.extend_from_slice(b"LIST\x03\x00\x00\x00abc\x00");
+        file.extend_from_slice(b"fmt \x10\x00\x00\x00\x01\x00\x02\x00");
+        file.extend_from_slice(&sample_rate.to_le_bytes());
+        file.extend_from_slice(&(sample_rate * 4).to_le_bytes());
+        file.extend_from_slice(b"\x04\x00\x10\x00data");
+        file.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        file.extend_from_slice(&data);
+        file
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn wav_file() {
+        let file = wav(8_000, &[[16_384, 0], [-32_768, -32_768], [0, 32_767]]);
+        let mut source = WavSource::from_reader(file.as_slice()).unwrap();
+        assert_eq!(source.sample_rate(), 8_000);
+        assert_eq!(
+            source.samples(),
+            &[0.25_f32, -1_f32, 32_767_f32 / 65_536_f32]
+        );
+        assert_eq!(
+            source.duration(),
+            Duration::from_secs_f64(3_f64 / 8_000_f64)
+        );
+
+        let mut samples = [1_f32; 4];
+        // after two samples
+        source.read(Duration::from_micros(250), &mut samples);
+        assert_eq!(samples, [0_f32, 0_f32, 0.25_f32, -1_f32]);
+        // long after the end
+        source.read(Duration::from_secs(1), &mut samples);
+        assert_eq!(samples, [0_f32; 4]);
+
+        assert_eq!(
+            WavSource::from_reader(&b"RIFF\x00\x00\x00\x00AVI "[..])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            WavSource::from_reader(&file[..40]).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        // a chunk claiming 4 GB is not allocated before being read
+        let mut huge = file[..12].to_vec();
+        huge.extend_from_slice(b"data\xff\xff\xff\xff\x00\x00");
+        assert_eq!(
+            WavSource::from_reader(huge.as_slice()).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        huge[12..16].copy_from_slice(b"LIST");
+        assert_eq!(
+            WavSource::from_reader(huge.as_slice()).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn sweep() {
+        let sample_rate = 48_000;
+        let mut sweep =
+            SineSweep::new(sample_rate, 100_f64, 1_600_f64, Duration::from_secs(4)).unwrap();
+        assert!((sweep.frequency(Duration::ZERO) - 100_f64).abs() < 1E-9_f64);
+        assert!((sweep.frequency(Duration::from_secs(2)) - 400_f64).abs() < 1E-9_f64);
+        assert!((sweep.frequency(Duration::from_secs(5)) - 200_f64).abs() < 1E-9_f64);
+
+        // the number of zero crossings over 0.1 s gives the frequency
+        let mut samples = vec![0_f32; 4_800];
+        sweep.read(Duration::from_millis(2_050), &mut samples);
+        let crossings = samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0_f32) != (pair[1] < 0_f32))
+            .count();
+        assert!((75..=85).contains(&crossings), "{}", crossings);
+        // before the start
+        sweep.read(Duration::from_millis(50), &mut samples);
+        assert!(samples[..2_400].iter().all(|sample| *sample == 0_f32));
+
+        assert_eq!(SineSweep::constant(sample_rate, 0_f64), None);
+        assert_eq!(
+            SineSweep::new(0, 1_f64, 1_f64, Duration::from_secs(1)),
+            None
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Contains the effect [`GradientSweep`]
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{check_finite, Effect};
+use crate::{
+    color::{ColorBuffer, ColorRgb, Gradient},
+    layout::{Layout, Position},
+};
+
+/// Default direction, from left to right
+#[cfg(feature = "serde-serialize")]
+const fn default_direction() -> Position {
+    Position::new(1_f64, 0_f64)
+}
+
+/// A [`Gradient`] across the keyboard, moving at `speed` gradients per second.
+///
+/// With a speed of zero the gradient is static. To get a smooth animation use a gradient with
+/// the mode [`crate::GradientMode::Repeat`] or [`crate::GradientMode::Mirror`].
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorBuffer, ColorRgb, Effect, Gradient, GradientMode, GradientSweep, KeyName, LayoutFrCh,
+///     Position,
+/// };
+///
+/// let gradient =
+///     Gradient::new_evenly_spaced(&[ColorRgb::new(255, 0, 0), ColorRgb::new(0, 0, 255)])
+///         .unwrap()
+///         .with_mode(GradientMode::Mirror);
+/// let mut effect = GradientSweep::new(gradient, Position::new(1_f64, 0_f64), 1_f64).unwrap();
+/// let layout = LayoutFrCh::new();
+/// let mut buffer = ColorBuffer::new();
+/// effect.render(&mut buffer, &layout, Duration::from_secs(1));
+/// assert_eq!(
+///     buffer.get_key(&layout, KeyName::Escape),
+///     Some(&ColorRgb::new(0, 0, 255))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(deny_unknown_fields))]
+pub struct GradientSweep {
+    /// Gradient drawn
+    gradient: Gradient,
+    /// Direction of the gradient
+    #[cfg_attr(feature = "serde-serialize", serde(default = "default_direction"))]
+    direction: Position,
+    /// Number of gradients per second
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    speed: f64,
+}
+
+impl GradientSweep {
+    /// Create a new effect, returns [`None`] if `speed` or `direction` are not finite.
+    pub fn new(gradient: Gradient, direction: Position, speed: f64) -> Option<Self> {
+        let effect = Self {
+            gradient,
+            direction,
+            speed,
+        };
+        effect.validate().ok().map(|_| effect)
+    }
+
+    /// Get the gradient
+    pub const fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+
+    /// Get the direction of the gradient
+    pub const fn direction(&self) -> Position {
+        self.direction
+    }
+
+    /// Get the number of gradients per second
+    pub const fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Check the parameters, used after deserialization.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        check_finite(self.speed, "the speed must be finite")?;
+        check_finite(self.direction.x(), "the direction must be finite")?;
+        check_finite(self.direction.y(), "the direction must be finite")
+    }
+}
+
+impl Effect for GradientSweep {
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        self.gradient.fill_with_offset(
+            buffer,
+            layout,
+            self.direction,
+            -elapsed.as_secs_f64() * self.speed,
+        );
+    }
+}
+
+impl Display for GradientSweep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} toward {} at {} per second",
+            self.gradient, self.direction, self.speed
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        color::GradientMode,
+        layout::{KeyName, LayoutFrCh},
+    };
+
+    #[test]
+    fn sweep() {
+        let layout = LayoutFrCh::new();
+        let gradient =
+            Gradient::new_evenly_spaced(&[ColorRgb::new(255, 0, 0), ColorRgb::new(0, 0, 255)])
+                .unwrap()
+                .with_mode(GradientMode::Repeat);
+        let mut effect = GradientSweep::new(gradient, Position::new(1_f64, 0_f64), 0_f64).unwrap();
+        let mut buffer = ColorBuffer::new();
+        effect.render(&mut buffer, &layout, Duration::from_secs(10));
+        // static
+        assert_eq!(
+            buffer.get_key(&layout, KeyName::Escape),
+            Some(&ColorRgb::new(255, 0, 0))
+        );
+        assert!(GradientSweep::new(
+            effect.gradient().clone(),
+            Position::new(f64::NAN, 0_f64),
+            1_f64
+        )
+        .is_none());
+        assert!(effect.validate().is_ok());
+    }
+}
@@ -0,0 +1,181 @@
+//! Contains the effect [`Wave`]
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{check_finite, check_positive, check_unit, Effect};
+use crate::{
+    color::{ColorBuffer, ColorRgb, Hue, Saturation, Value, NUMBER_KEY_LED_BUFFER},
+    layout::{Layout, Position},
+};
+
+/// Rainbow moving across the keyboard.
+///
+/// The hue changes along `direction`, a full rainbow spans `wavelength` keys
+/// and it moves at `speed` rainbows per second.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorBuffer, ColorRgb, Effect, KeyName, LayoutFrCh, Position, Wave,
+/// };
+///
+/// let layout = LayoutFrCh::new();
+/// let mut effect = Wave::new(0.5_f64, 22_f64, Position::new(1_f64, 0_f64)).unwrap();
+/// let mut buffer = ColorBuffer::new();
+/// effect.render(&mut buffer, &layout, Duration::ZERO);
+/// assert_eq!(
+///     buffer.get_key(&layout, KeyName::Escape),
+///     Some(&ColorRgb::new(255, 0, 0))
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(default, deny_unknown_fields))]
+pub struct Wave {
+    /// Number of rainbows per second, negative to go backward
+    speed: f64,
+    /// Number of keys for a full rainbow
+    wavelength: f64,
+    /// Direction of the rainbow
+    direction: Position,
+    /// Saturation of the colors, between 0 and 1
+    saturation: f64,
+    /// Value of the colors, between 0 and 1
+    value: f64,
+}
+
+impl Wave {
+    /// Create a new wave with fully saturated colors.
+    ///
+    /// Returns [`None`] if `speed` is not finite or if `wavelength` is not strictly positive.
+    pub fn new(speed: f64, wavelength: f64, direction: Position) -> Option<Self> {
+        let effect = Self {
+            speed,
+            wavelength,
+            direction,
+            ..Self::default()
+        };
+        effect.validate().ok().map(|_| effect)
+    }
+
+    /// Set the saturation and the value of the colors
+    pub const fn with_color(mut self, saturation: Saturation, value: Value) -> Self {
+        self.saturation = saturation.value();
+        self.value = value.value();
+        self
+    }
+
+    /// Get the number of rainbows per second
+    pub const fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Get the number of keys for a full rainbow
+    pub const fn wavelength(&self) -> f64 {
+        self.wavelength
+    }
+
+    /// Get the direction of the rainbow
+    pub const fn direction(&self) -> Position {
+        self.direction
+    }
+
+    /// Check the parameters, used after deserialization.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        check_finite(self.speed, "the speed must be finite")?;
+        check_positive(self.wavelength, "the wavelength must be strictly positive")?;
+        check_finite(self.direction.x(), "the direction must be finite")?;
+        check_finite(self.direction.y(), "the direction must be finite")?;
+        check_unit(self.saturation, "the saturation must be between 0 and 1")?;
+        check_unit(self.value, "the value must be between 0 and 1")
+    }
+}
+
+impl Default for Wave {
+    /// One rainbow on the width of the keyboard from left to right, moving every two seconds
+    fn default() -> Self {
+        Self {
+            speed: 0.5_f64,
+            wavelength: 22_f64,
+            direction: Position::new(1_f64, 0_f64),
+            saturation: 1_f64,
+            value: 1_f64,
+        }
+    }
+}
+
+impl Effect for Wave {
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        let length = self.direction.length();
+        let direction = if length > 0_f64 {
+            Position::new(self.direction.x() / length, self.direction.y() / length)
+        } else {
+            self.direction
+        };
+        let saturation = Saturation::new(self.saturation).unwrap_or_default();
+        let value = Value::new(self.value).unwrap_or_default();
+        let shift = elapsed.as_secs_f64() * self.speed;
+        for key in layout.layout() {
+            let index = usize::from(key.key_code_light().code());
+            if index >= NUMBER_KEY_LED_BUFFER {
+                continue;
+            }
+            let position = key.key_pos();
+            let projection = position
+                .x()
+                .mul_add(direction.x(), position.y() * direction.y());
+            let hue = (projection / self.wavelength - shift).rem_euclid(1_f64);
+            let hue = Hue::new(hue).unwrap_or_default();
+            buffer[index] = ColorRgb::new_hsv(hue, saturation, value);
+        }
+    }
+}
+
+impl Display for Wave {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "wave toward {} at {} per second",
+            self.direction, self.speed
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::{KeyName, LayoutFrCh};
+
+    #[test]
+    fn wave() {
+        let layout = LayoutFrCh::new();
+        let mut effect = Wave::new(1_f64, 4_f64, Position::new(2_f64, 0_f64)).unwrap();
+        let mut buffer = ColorBuffer::new();
+        let color = |buffer: &ColorBuffer<ColorRgb>, key| *buffer.get_key(&layout, key).unwrap();
+
+        effect.render(&mut buffer, &layout, Duration::ZERO);
+        // Escape at x = 0, F1 at x = 2, half of the wavelength
+        assert_eq!(color(&buffer, KeyName::Escape), ColorRgb::new(255, 0, 0));
+        assert_eq!(color(&buffer, KeyName::F1), ColorRgb::new(0, 255, 255));
+        // after half a second the colors are swapped
+        effect.render(&mut buffer, &layout, Duration::from_millis(500));
+        assert_eq!(color(&buffer, KeyName::Escape), ColorRgb::new(0, 255, 255));
+        assert_eq!(color(&buffer, KeyName::F1), ColorRgb::new(255, 0, 0));
+
+        assert_eq!(Wave::new(1_f64, 0_f64, Position::new(1_f64, 0_f64)), None);
+        assert_eq!(
+            Wave::new(f64::NAN, 1_f64, Position::new(1_f64, 0_f64)),
+            None
+        );
+    }
+}
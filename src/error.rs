@@ -1,52 +1,252 @@
-//! Contains [`ErrorRoccatVulcanApi`]
+//! Contains [`ErrorRoccatVulcanApi`] and [`ErrorKind`]
 
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::time::Duration;
 
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::HidInterfaceFilter;
+
+/// Category of an [`ErrorRoccatVulcanApi`], see [`ErrorRoccatVulcanApi::kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The keyboard or one of its interfaces is not connected
+    NotFound,
+    /// An interface of the keyboard returned an error
+    Device,
+    /// The keyboard took too long to answer
+    Timeout,
+    /// hidapi could not be initialised
+    HidApi,
+    /// An argument is not valid
+    InvalidInput,
+    /// Input/output error
+    Io,
+    /// The daemon rejected the request
+    Rejected,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "not found"),
+            Self::Device => write!(f, "device error"),
+            Self::Timeout => write!(f, "timeout"),
+            Self::HidApi => write!(f, "hid api error"),
+            Self::InvalidInput => write!(f, "invalid input"),
+            Self::Io => write!(f, "io error"),
+            Self::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
 /// Error returned by the API
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{ErrorKind, ErrorRoccatVulcanApi};
+///
+/// let error = ErrorRoccatVulcanApi::WaitedToMuchTime {
+///     report: Some(7),
+///     duration: Duration::from_millis(120),
+/// };
+/// assert_eq!(error.kind(), ErrorKind::Timeout);
+/// assert!(error.is_transient());
+/// assert_eq!(
+///     error.to_string(),
+///     "waited for too long for the report 7, waited for 120 ms"
+/// );
+/// ```
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum ErrorRoccatVulcanApi {
     /// No keyboard detected.
-    KeyboardNotFound,
-    /// LED device not found
-    NoLedDevice,
+    KeyboardNotFound {
+        /// Product ids searched
+        product_ids: Vec<u16>,
+    },
+    /// LED device not found, with the filter searched
+    NoLedDevice(HidInterfaceFilter),
     /// LED device error
-    LedDeviceError(hidapi::HidError),
-    /// Control device not found
-    NoControlDevice,
+    LedDeviceError {
+        /// Number of the packet of the frame which failed, counted from 1 like the reports of
+        /// [`Self::ControlDeviceError`], [`None`] if outside of a frame
+        packet: Option<usize>,
+        /// Error of hidapi
+        source: hidapi::HidError,
+    },
+    /// Control device not found, with the filter searched
+    NoControlDevice(HidInterfaceFilter),
     /// Control device error
-    ControlDeviceError(hidapi::HidError),
-    /// Read device not found
-    NoReadDevice,
+    ControlDeviceError {
+        /// Number of the report of the initialisation which failed, counted from 1 in the
+        /// order they are sent like the packets of [`Self::LedDeviceError`], [`None`] if
+        /// outside of the initialisation
+        report: Option<usize>,
+        /// Error of hidapi
+        source: hidapi::HidError,
+    },
+    /// Read device not found, with the filter searched
+    NoReadDevice(HidInterfaceFilter),
     /// Read device error
     ReadDeviceError(hidapi::HidError),
     /// Too much time elapsed while wating for the device to be ready
-    WaitedToMuchTime(Duration),
+    WaitedToMuchTime {
+        /// Number of the report of the initialisation after which the device stalled, counted
+        /// from 1 in the order they are sent
+        report: Option<usize>,
+        /// Time waited
+        duration: Duration,
+    },
     /// error while trying the get the hdiapi.
     HidApiError(hidapi::HidError),
     /// Invalide input
     InvalidInput,
+    /// Input/output error, for instance on the connection to the daemon
+    Io(std::io::Error),
+    /// Request rejected by the daemon, with its explanation
+    Rejected(String),
+}
+
+impl ErrorRoccatVulcanApi {
+    /// Get the category of the error
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::KeyboardNotFound { .. }
+            | Self::NoLedDevice(_)
+            | Self::NoControlDevice(_)
+            | Self::NoReadDevice(_) => ErrorKind::NotFound,
+            Self::LedDeviceError { .. }
+            | Self::ControlDeviceError { .. }
+            | Self::ReadDeviceError(_) => ErrorKind::Device,
+            Self::WaitedToMuchTime { .. } => ErrorKind::Timeout,
+            Self::HidApiError(_) => ErrorKind::HidApi,
+            Self::InvalidInput => ErrorKind::InvalidInput,
+            Self::Io(_) => ErrorKind::Io,
+            Self::Rejected(_) => ErrorKind::Rejected,
+        }
+    }
+
+    /// Whether retrying the operation may succeed.
+    ///
+    /// The errors of the interfaces and the timeouts are transient, as well as the
+    /// interrupted and timed out input/output. A missing keyboard, an invalid input or a
+    /// rejected request are permanent.
+    ///
+    /// hidapi does not tell an unplugged keyboard apart from a failed transfer, so the
+    /// errors of the interfaces stay transient when the keyboard is gone. The retries must be
+    /// bounded, then the keyboard opened again with [`crate::KeyboardApi::new`] which returns
+    /// [`Self::KeyboardNotFound`] if it is not plugged anymore.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::LedDeviceError { .. }
+            | Self::ControlDeviceError { .. }
+            | Self::ReadDeviceError(_)
+            | Self::WaitedToMuchTime { .. } => true,
+            Self::Io(error) => matches!(
+                error.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::TimedOut
+            ),
+            Self::KeyboardNotFound { .. }
+            | Self::NoLedDevice(_)
+            | Self::NoControlDevice(_)
+            | Self::NoReadDevice(_)
+            | Self::HidApiError(_)
+            | Self::InvalidInput
+            | Self::Rejected(_) => false,
+        }
+    }
+
+    /// Set the number of the packet, from 1, on a [`Self::LedDeviceError`]
+    pub(crate) const fn with_packet(mut self, number: usize) -> Self {
+        if let Self::LedDeviceError { packet, .. } = &mut self {
+            *packet = Some(number);
+        }
+        self
+    }
+
+    /// Set the number of the report, from 1, on a [`Self::ControlDeviceError`] or a
+    /// [`Self::WaitedToMuchTime`]
+    pub(crate) const fn with_report(mut self, number: usize) -> Self {
+        match &mut self {
+            Self::ControlDeviceError { report, .. } | Self::WaitedToMuchTime { report, .. } => {
+                *report = Some(number);
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Create a [`Self::LedDeviceError`] outside of a frame
+    pub(crate) const fn led_device(source: hidapi::HidError) -> Self {
+        Self::LedDeviceError {
+            packet: None,
+            source,
+        }
+    }
+
+    /// Create a [`Self::ControlDeviceError`] outside of the initialisation
+    pub(crate) const fn control_device(source: hidapi::HidError) -> Self {
+        Self::ControlDeviceError {
+            report: None,
+            source,
+        }
+    }
 }
 
 impl Display for ErrorRoccatVulcanApi {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::KeyboardNotFound => write!(f, "keyboard not found"),
-            Self::NoLedDevice => write!(f, "led device not found"),
-            Self::LedDeviceError(error) => write!(f, "led device error : {}", error),
-            Self::NoControlDevice => write!(f, "control device not found"),
-            Self::ControlDeviceError(error) => write!(f, "control device error : {}", error),
-            Self::NoReadDevice => write!(f, "read device not found"),
-            Self::ReadDeviceError(error) => write!(f, "read device error : {}", error),
-            Self::WaitedToMuchTime(duration) => write!(
+            Self::KeyboardNotFound { product_ids } => {
+                write!(f, "keyboard not found")?;
+                if !product_ids.is_empty() {
+                    let ids: Vec<String> = product_ids.iter().map(u16::to_string).collect();
+                    write!(f, ", searched the product ids {}", ids.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::NoLedDevice(filter) => write!(f, "led device not found ({})", filter),
+            Self::LedDeviceError {
+                packet: Some(packet),
+                source,
+            } => write!(f, "led device error on the packet {} : {}", packet, source),
+            Self::LedDeviceError {
+                packet: None,
+                source,
+            } => write!(f, "led device error : {}", source),
+            Self::NoControlDevice(filter) => write!(f, "control device not found ({})", filter),
+            Self::ControlDeviceError {
+                report: Some(report),
+                source,
+            } => write!(
                 f,
-                "waited for too long, waited for {} ms",
-                duration.as_secs_f64() * 1_000_f64
+                "control device error on the report {} : {}",
+                report, source
             ),
+            Self::ControlDeviceError {
+                report: None,
+                source,
+            } => write!(f, "control device error : {}", source),
+            Self::NoReadDevice(filter) => write!(f, "read device not found ({})", filter),
+            Self::ReadDeviceError(error) => write!(f, "read device error : {}", error),
+            Self::WaitedToMuchTime { report, duration } => {
+                write!(f, "waited for too long")?;
+                if let Some(report) = report {
+                    write!(f, " for the report {}", report)?;
+                }
+                write!(f, ", waited for {} ms", duration.as_secs_f64() * 1_000_f64)
+            }
             Self::HidApiError(error) => write!(f, "hid api error : {}", error),
             Self::InvalidInput => write!(f, "invalide input"),
+            Self::Io(error) => write!(f, "io error : {}", error),
+            Self::Rejected(message) => write!(f, "request rejected : {}", message),
         }
     }
 }
@@ -54,16 +254,85 @@ impl Display for ErrorRoccatVulcanApi {
 impl Error for ErrorRoccatVulcanApi {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            Self::KeyboardNotFound
-            | Self::NoLedDevice
-            | Self::NoControlDevice
-            | Self::NoReadDevice
+            Self::KeyboardNotFound { .. }
+            | Self::NoLedDevice(_)
+            | Self::NoControlDevice(_)
+            | Self::NoReadDevice(_)
             | Self::InvalidInput
-            | Self::WaitedToMuchTime(_) => None,
-            Self::LedDeviceError(error)
-            | Self::ControlDeviceError(error)
-            | Self::ReadDeviceError(error)
-            | Self::HidApiError(error) => Some(error),
+            | Self::WaitedToMuchTime { .. }
+            | Self::Rejected(_) => None,
+            Self::LedDeviceError { source, .. } | Self::ControlDeviceError { source, .. } => {
+                Some(source)
+            }
+            Self::ReadDeviceError(error) | Self::HidApiError(error) => Some(error),
+            Self::Io(error) => Some(error),
         }
     }
 }
+
+impl From<std::io::Error> for ErrorRoccatVulcanApi {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn context() {
+        let filter = HidInterfaceFilter::new(12_440, 3);
+        let error = ErrorRoccatVulcanApi::NoLedDevice(filter);
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert!(!error.is_transient());
+        assert_eq!(
+            error.to_string(),
+            format!("led device not found ({})", filter)
+        );
+
+        let error = ErrorRoccatVulcanApi::KeyboardNotFound {
+            product_ids: vec![12_410, 12_440],
+        };
+        assert_eq!(
+            error.to_string(),
+            "keyboard not found, searched the product ids 12410, 12440"
+        );
+
+        let error = ErrorRoccatVulcanApi::led_device(hidapi::HidError::HidApiErrorEmpty);
+        assert_eq!(error.kind(), ErrorKind::Device);
+        assert!(error.is_transient());
+        let error = error.with_packet(3).with_report(1);
+        assert!(matches!(
+            error,
+            ErrorRoccatVulcanApi::LedDeviceError {
+                packet: Some(3),
+                ..
+            }
+        ));
+        assert!(error
+            .to_string()
+            .starts_with("led device error on the packet 3 : "));
+        assert!(error.source().is_some());
+
+        let error =
+            ErrorRoccatVulcanApi::control_device(hidapi::HidError::HidApiErrorEmpty).with_report(5);
+        assert!(error
+            .to_string()
+            .starts_with("control device error on the report 5 : "));
+
+        let error = ErrorRoccatVulcanApi::WaitedToMuchTime {
+            report: None,
+            duration: Duration::from_millis(100),
+        };
+        assert_eq!(error.to_string(), "waited for too long, waited for 100 ms");
+
+        let error = ErrorRoccatVulcanApi::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert_eq!(error.kind(), ErrorKind::Io);
+        assert!(error.is_transient());
+        let error = ErrorRoccatVulcanApi::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(!error.is_transient());
+        assert!(!ErrorRoccatVulcanApi::Rejected(String::new()).is_transient());
+        assert_eq!(ErrorKind::Timeout.to_string(), "timeout");
+    }
+}
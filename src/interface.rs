@@ -7,9 +7,9 @@ use hidapi::DeviceInfo;
 use serde::{Deserialize, Serialize};
 
 /// Product is of the Vulcan 100.
-const VULCAN_100_PRODUCT_ID: u16 = 12_410;
+pub(crate) const VULCAN_100_PRODUCT_ID: u16 = 12_410;
 /// Product is of the Vulcan 120.
-const VULCAN_120_PRODUCT_ID: u16 = 12_440;
+pub(crate) const VULCAN_120_PRODUCT_ID: u16 = 12_440;
 /// Defaut usage page for the read device.
 /// The reason the usage page is 10 is unkown and may change for diffrent device.
 const USAGE_PAGE_READ_DEVICE: u16 = 10;
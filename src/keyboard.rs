@@ -4,12 +4,13 @@ use std::fmt::{Debug, Display, Formatter};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use hidapi::{HidApi, HidDevice};
+use hidapi::HidApi;
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    color, reports, ColorBuffer, ColorRgb, ErrorRoccatVulcanApi, KeyPress, KeyboardIntrefacesFilter,
+    color, reports, ColorBuffer, ColorCorrection, ColorRgb, DeviceInfo, ErrorRoccatVulcanApi,
+    HidTransport, KeyMap, KeyPress, KeyboardEvent, KeyboardIntrefacesFilter, Transport,
 };
 
 /// Result returned by the API
@@ -22,13 +23,19 @@ const MAX_WAIT_DURATION: Duration = Duration::from_millis(100);
 
 // TODO more doc
 /// Main API
-pub struct KeyboardApi {
-    /// Read device that look for key press
-    read: HidDevice,
-    /// Control device where feature are send to initialize the keyboard
-    control: HidDevice,
-    /// Led device which send color for the keyboard
-    led: HidDevice,
+///
+/// The devices are accessed through a [`Transport`], by default [`HidTransport`]
+/// which talks to a real keyboard. Use [`Self::with_transport`] with a [`crate::MockTransport`]
+/// to test without keyboard.
+pub struct KeyboardApi<T: Transport = HidTransport> {
+    /// Access to the devices of the keyboard
+    transport: T,
+    /// Correction applied on the colors when rendering
+    color_correction: ColorCorrection,
+    /// Key map sent when initialising the keyboard
+    key_map: KeyMap,
+    /// Information on the keyboard read when opening it
+    device_info: DeviceInfo,
 }
 
 impl KeyboardApi {
@@ -55,84 +62,136 @@ impl KeyboardApi {
         api: &HidApi,
         interfaces_info: &[KeyboardIntrefacesFilter],
     ) -> Res<Self> {
+        // the error of a keyboard found but not opened is more useful than not found
+        let mut last_error = None;
         for interface in interfaces_info {
-            if let Ok(keyboard) = Self::new_model(api, interface) {
-                return Ok(keyboard);
+            match Self::new_model(api, interface) {
+                Ok(keyboard) => return Ok(keyboard),
+                Err(ErrorRoccatVulcanApi::KeyboardNotFound { .. }) => {}
+                Err(error) => last_error = Some(error),
             }
         }
-        Err(ErrorRoccatVulcanApi::KeyboardNotFound)
+        Err(
+            last_error.unwrap_or_else(|| ErrorRoccatVulcanApi::KeyboardNotFound {
+                product_ids: interfaces_info
+                    .iter()
+                    .map(|interface| interface.control_interface().product_id())
+                    .collect(),
+            }),
+        )
     }
 
     /// Initialize the API uing from a interface info.
     /// # Errors
     /// see [`Self::new`]
     pub fn new_model(api: &HidApi, interface: &KeyboardIntrefacesFilter) -> Res<Self> {
-        if !api
-            .device_list()
-            .any(|device| device.product_id() == interface.control_interface().product_id())
-        {
-            return Err(ErrorRoccatVulcanApi::KeyboardNotFound);
-        }
-        let read_info = api
-            .device_list()
-            .find(|device| interface.read_interface().match_filter(device))
-            .ok_or(ErrorRoccatVulcanApi::NoReadDevice)?;
-        let led_info = api
-            .device_list()
-            .find(|device| interface.led_interface().match_filter(device))
-            .ok_or(ErrorRoccatVulcanApi::NoLedDevice)?;
-        let control_info_list = api
-            .device_list()
-            .filter(|device| interface.control_interface().match_filter(device));
-
-        let control = control_info_list
-            .map(|device| device.open_device(api))
-            .find(|value| match value {
-                Ok(device) => Self::is_correct_control_device(device),
-                Err(_) => false,
-            })
-            .ok_or(ErrorRoccatVulcanApi::NoControlDevice)?
-            .or(Err(ErrorRoccatVulcanApi::NoControlDevice))?;
-        let read = read_info
-            .open_device(api)
-            .map_err(ErrorRoccatVulcanApi::ReadDeviceError)?;
-        let led = led_info
-            .open_device(api)
-            .map_err(ErrorRoccatVulcanApi::LedDeviceError)?;
-        read.set_blocking_mode(true)
-            .map_err(ErrorRoccatVulcanApi::ReadDeviceError)?;
-        let keyboard = Self { read, control, led };
+        Self::with_transport(HidTransport::open(api, interface)?)
+    }
+}
+
+impl<T: Transport> KeyboardApi<T> {
+    /// Initialize the API on the given transport, putting the keyboard in custom mode.
+    /// # Errors
+    /// - [`ErrorRoccatVulcanApi::ControlDeviceError`] Control device error,
+    /// - [`ErrorRoccatVulcanApi::WaitedToMuchTime`] Error while initalizing key board: waited for too long,
+    pub fn with_transport(transport: T) -> Res<Self> {
+        let device_info = transport.device_info();
+        let keyboard = Self {
+            transport,
+            color_correction: ColorCorrection::new(),
+            key_map: KeyMap::new(),
+            device_info,
+        };
         keyboard.initialise_control_device(ControlerFeatureKind::Custom)?;
         thread::sleep(WAIT_FOR_CONTROL_DURATION); // we seelp after initisation just to maje sure the fist render is done properly.
         Ok(keyboard)
     }
 
-    /// Verify if the given device is the correct control device.
-    /// Note that this change the state of the HidDevice.
-    /// If you want to close it you will have to resend a similar feature report.
-    fn is_correct_control_device(device: &HidDevice) -> bool {
-        let mut buffer: [u8; 255] = [0x00; 255];
-        buffer[0] = 0x0f;
-        let a = device.get_feature_report(&mut buffer);
-        match a {
-            Ok(val) => val > 0,
-            Err(_) => false,
-        }
+    /// Get the transport used to access the devices
+    pub const fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Get the information on the keyboard, read when the API is created
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{ErrorRoccatVulcanApi, KeyboardApi};
+    ///
+    /// # fn main() -> Result<(), ErrorRoccatVulcanApi> {
+    /// # #[cfg(not(feature = "no-keyboard-test"))]
+    /// # {
+    /// let keyboard = KeyboardApi::new()?;
+    /// let info = keyboard.device_info();
+    /// println!("{}, control device at {:?}", info, info.control_path());
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub const fn device_info(&self) -> &DeviceInfo {
+        &self.device_info
+    }
+
+    /// Switch the keyboard between the default rainbow mode and the custom mode
+    /// used by [`Self::render`].
+    ///
+    /// The keyboard is already in custom mode after [`Self::new`]
+    /// and goes back to rainbow mode when the API is dropped.
+    /// # Errors
+    /// - [`ErrorRoccatVulcanApi::ControlDeviceError`] if the control device encountered an error
+    /// - [`ErrorRoccatVulcanApi::WaitedToMuchTime`] if the control device took too long to be ready
+    pub fn set_mode(&self, kind: ControlerFeatureKind) -> Res<()> {
+        self.initialise_control_device(kind)
+    }
+
+    /// Get the key map sent when initialising the keyboard
+    pub const fn key_map(&self) -> &KeyMap {
+        &self.key_map
+    }
+
+    /// Remap the keys in the firmware, the key map works on every computer without software.
+    ///
+    /// The key map is sent with the other reports initialising the keyboard, which goes back
+    /// to [`ControlerFeatureKind::Custom`] mode. As in the captured initialisation, every
+    /// [`KeyboardApi`] sends its key map when it initialises the keyboard, the default one
+    /// until this method is called: a new API resets the remapping.
+    /// # Errors
+    /// - [`ErrorRoccatVulcanApi::ControlDeviceError`] if the control device encountered an error
+    /// - [`ErrorRoccatVulcanApi::WaitedToMuchTime`] if the control device took too long to be ready
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{ErrorRoccatVulcanApi, KeyMap, KeyName, KeyboardApi, LayoutKind};
+    ///
+    /// # fn main() -> Result<(), ErrorRoccatVulcanApi> {
+    /// # #[cfg(not(feature = "no-keyboard-test"))]
+    /// # {
+    /// let mut keyboard = KeyboardApi::new()?;
+    /// let mut key_map = KeyMap::new();
+    /// key_map.swap(
+    ///     KeyName::CapsLock,
+    ///     KeyName::Escape,
+    ///     LayoutKind::FrCh.layout(),
+    /// );
+    /// keyboard.set_key_map(key_map)?;
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_key_map(&mut self, key_map: KeyMap) -> Res<()> {
+        self.key_map = key_map;
+        self.initialise_control_device(ControlerFeatureKind::Custom)
     }
 
     /// Initialize the control device with either rainbow mode or custom mode
+    /// with the key map, waiting for the device after each report
     fn initialise_control_device(&self, kind: ControlerFeatureKind) -> Res<()> {
-        let feature_reports = {
-            match kind {
-                ControlerFeatureKind::Rainbow => &reports::FEATURE_REPORT_RAINBOW,
-                ControlerFeatureKind::Custom => &reports::FEATURE_REPORT_CUSTOM,
-            }
-        };
-        for feature_report in feature_reports.iter() {
-            self.control
-                .send_feature_report(feature_report)
-                .map_err(ErrorRoccatVulcanApi::ControlDeviceError)?;
-            self.wait_for_control_device()?;
+        for (index, feature_report) in reports::handshake(&self.key_map, kind)
+            .into_iter()
+            .enumerate()
+        {
+            self.transport
+                .send_feature_report(&feature_report)
+                .and_then(|()| self.wait_for_control_device())
+                .map_err(|error| error.with_report(index + 1))?;
         }
         Ok(())
     }
@@ -148,33 +207,71 @@ impl KeyboardApi {
             thread::sleep(WAIT_FOR_CONTROL_DURATION);
             let mut buffer: [u8; 255] = [0x00; 255];
             buffer[0] = 0x04;
-            let size = self.control.get_feature_report(&mut buffer);
+            let size = self.transport.get_feature_report(&mut buffer);
             if let Ok(val) = size {
                 if val > 0 {
                     break;
                 }
             };
             if now.elapsed() > MAX_WAIT_DURATION {
-                return Err(ErrorRoccatVulcanApi::WaitedToMuchTime(now.elapsed()));
+                return Err(ErrorRoccatVulcanApi::WaitedToMuchTime {
+                    report: None,
+                    duration: now.elapsed(),
+                });
             }
         }
         Ok(())
     }
 
-    /// Renders a collor buffer
+    /// Get the color correction applied when rendering.
+    pub const fn color_correction(&self) -> &ColorCorrection {
+        &self.color_correction
+    }
+
+    /// Set the color correction applied when rendering.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{
+    ///     ColorBuffer, ColorCorrection, ColorRgb, ErrorRoccatVulcanApi, Gamma, KeyboardApi, Value,
+    /// };
+    ///
+    /// # fn main() -> Result<(), ErrorRoccatVulcanApi> {
+    /// # #[cfg(not(feature = "no-keyboard-test"))]
+    /// # {
+    /// let mut keyboard = KeyboardApi::new()?;
+    /// keyboard.set_color_correction(
+    ///     ColorCorrection::new()
+    ///         .with_brightness(Value::new(0.5_f64).unwrap())
+    ///         .with_gamma(Gamma::new(2.2_f64).unwrap()),
+    /// );
+    /// keyboard.render(&ColorBuffer::from_element(ColorRgb::new(255, 128, 0)))?;
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub const fn set_color_correction(&mut self, color_correction: ColorCorrection) {
+        self.color_correction = color_correction;
+    }
+
+    /// Get the color correction applied when rendering as a mutable reference.
+    pub const fn color_correction_mut(&mut self) -> &mut ColorCorrection {
+        &mut self.color_correction
+    }
+
+    /// Renders a collor buffer, applying the [`ColorCorrection`] of the API.
     /// # Errors
     /// [`ErrorRoccatVulcanApi::LedDeviceError`] if the lead device encountered an error
     pub fn render(
         &self,
         buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>,
     ) -> Result<(), ErrorRoccatVulcanApi> {
-        let buffer_bite = buffer.get_led_buffer();
+        let buffer_bite = buffer.get_led_buffer_corrected(&self.color_correction);
         let bite_to_write = color::BITE_PACKET_SIZE + 1;
         for i in 0..(buffer_bite.len() / bite_to_write) {
             let buffer_write = &buffer_bite[(i * (bite_to_write))..(i + 1) * bite_to_write];
-            self.led
-                .write(buffer_write)
-                .map_err(ErrorRoccatVulcanApi::LedDeviceError)?;
+            self.transport
+                .write_led(buffer_write)
+                .map_err(|error| error.with_packet(i + 1))?;
         }
         Ok(())
     }
@@ -199,12 +296,55 @@ impl KeyboardApi {
     /// # Ok(())
     /// # }
     /// ```
-    #[allow(clippy::cast_possible_truncation)]
     pub fn read_key_press(&self, duration: Duration) -> Res<Vec<KeyPress>> {
+        let mut vector_result = Vec::new();
+        self.read_raw_for(duration, |buffer| {
+            if buffer[2] > 0 {
+                vector_result.push(KeyPress::new_from_buffer(buffer));
+            }
+        })?;
+        Ok(vector_result)
+    }
+
+    /// Read events for a time of at least duration and return them in the order they occured.
+    ///
+    /// Contrary to [`Self::read_key_press`] the reports that are not understood are returned as
+    /// [`KeyboardEvent::Unknown`].
+    /// # Errors
+    /// - [`ErrorRoccatVulcanApi::InvalidInput`] the duration is not valide (too big)
+    /// - [`ErrorRoccatVulcanApi::ReadDeviceError`] if the read device had an error
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use roccat_vulcan_api_rs::{ErrorRoccatVulcanApi, KeyboardApi};
+    ///
+    /// # fn main() -> Result<(), ErrorRoccatVulcanApi> {
+    /// # #[cfg(not(feature = "no-keyboard-test"))]
+    /// # {
+    /// let keyboard = KeyboardApi::new()?;
+    /// for event in keyboard.read_events(Duration::from_millis(400))? {
+    ///     if event.is_unknown() {
+    ///         eprintln!("{}", event);
+    ///     }
+    /// }
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_events(&self, duration: Duration) -> Res<Vec<KeyboardEvent>> {
+        let mut vector_result = Vec::new();
+        self.read_raw_for(duration, |buffer| {
+            vector_result.push(KeyboardEvent::new_from_buffer(buffer));
+        })?;
+        Ok(vector_result)
+    }
+
+    /// Read the raw reports for a time of at least duration and call `f` on each of them.
+    fn read_raw_for(&self, duration: Duration, mut f: impl FnMut([u8; 5])) -> Res<()> {
         if duration.as_millis() > i32::MAX as u128 {
             return Err(ErrorRoccatVulcanApi::InvalidInput);
         }
-        let mut vector_result = Vec::new();
         let now = Instant::now();
         loop {
             let elapsed = now.elapsed();
@@ -212,14 +352,13 @@ impl KeyboardApi {
                 break;
             }
             let mut buffer: [u8; 5] = [0; 5];
-            self.read
-                .read_timeout(&mut buffer, (duration - elapsed).as_millis() as i32)
-                .map_err(ErrorRoccatVulcanApi::ReadDeviceError)?;
-            if buffer[2] > 0 {
-                vector_result.push(KeyPress::new_from_buffer(buffer));
+            let size = self.transport.read(&mut buffer, Some(duration - elapsed))?;
+            // a size of 0 means that the timeout was reached
+            if size > 0 {
+                f(buffer);
             }
         }
-        Ok(vector_result)
+        Ok(())
     }
 
     /// Block the thread until a key event or an error occur
@@ -227,48 +366,115 @@ impl KeyboardApi {
     /// [`ErrorRoccatVulcanApi::ReadDeviceError`] when the read device has an error
     pub fn wait_for_key_press(&self) -> Res<KeyPress> {
         self.listen_key_press()
-            .map_err(ErrorRoccatVulcanApi::ReadDeviceError)
+    }
+
+    /// Block the thread until an event or an error occur
+    /// # Errors
+    /// [`ErrorRoccatVulcanApi::ReadDeviceError`] when the read device has an error
+    pub fn wait_for_event(&self) -> Res<KeyboardEvent> {
+        self.listen_key_press_raw()
+            .map(KeyboardEvent::new_from_buffer)
     }
 
     /// wait for a key perss and return a [`Keypress`]
-    fn listen_key_press(&self) -> Result<KeyPress, hidapi::HidError> {
+    fn listen_key_press(&self) -> Res<KeyPress> {
         let buffer = self.listen_key_press_raw()?;
         Ok(KeyPress::new_from_buffer(buffer))
     }
 
     /// wait for key press and rturn the raw value
-    fn listen_key_press_raw(&self) -> Result<[u8; 5], hidapi::HidError> {
+    fn listen_key_press_raw(&self) -> Res<[u8; 5]> {
         let mut buffer: [u8; 5] = [0; 5];
-        self.read.read(&mut buffer)?;
+        self.transport.read(&mut buffer, None)?;
         Ok(buffer)
     }
 }
 
-impl Drop for KeyboardApi {
+/// Lighting and key presses of a keyboard, either accessed directly with [`KeyboardApi`]
+/// or shared through the daemon with [`crate::DaemonClient`].
+///
+/// Code written against this trait switches between the two by changing the constructor.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorBuffer, ColorRgb, ErrorRoccatVulcanApi, Keyboard, KeyboardApi,
+/// };
+///
+/// fn flash(keyboard: &dyn Keyboard) -> Result<(), ErrorRoccatVulcanApi> {
+///     keyboard.render(&ColorBuffer::from_element(ColorRgb::new(255, 255, 255)))?;
+///     keyboard.read_key_press(Duration::from_millis(100))?;
+///     keyboard.render(&ColorBuffer::from_element(ColorRgb::new(0, 0, 0)))
+/// }
+///
+/// # fn main() -> Result<(), ErrorRoccatVulcanApi> {
+/// # #[cfg(not(feature = "no-keyboard-test"))]
+/// # {
+/// flash(&KeyboardApi::new()?)?;
+/// # }
+/// # Ok(())
+/// # }
+/// ```
+pub trait Keyboard {
+    /// Renders a color buffer, see [`KeyboardApi::render`]
+    /// # Errors
+    /// Returns an error if the keyboard or the connection to the daemon failed
+    fn render(&self, buffer: &ColorBuffer<ColorRgb>) -> Res<()>;
+
+    /// Read the key presses for a time of at least duration, see [`KeyboardApi::read_key_press`]
+    /// # Errors
+    /// Returns an error if the keyboard or the connection to the daemon failed
+    fn read_key_press(&self, duration: Duration) -> Res<Vec<KeyPress>>;
+
+    /// Switch the mode of the keyboard, see [`KeyboardApi::set_mode`]
+    /// # Errors
+    /// Returns an error if the keyboard or the connection to the daemon failed
+    fn set_mode(&self, kind: ControlerFeatureKind) -> Res<()>;
+}
+
+impl<T: Transport> Keyboard for KeyboardApi<T> {
+    fn render(&self, buffer: &ColorBuffer<ColorRgb>) -> Res<()> {
+        Self::render(self, buffer)
+    }
+
+    fn read_key_press(&self, duration: Duration) -> Res<Vec<KeyPress>> {
+        Self::read_key_press(self, duration)
+    }
+
+    fn set_mode(&self, kind: ControlerFeatureKind) -> Res<()> {
+        Self::set_mode(self, kind)
+    }
+}
+
+impl<T: Transport> Drop for KeyboardApi<T> {
     fn drop(&mut self) {
         let _ = self.initialise_control_device(ControlerFeatureKind::Rainbow);
     }
 }
 
-/* impl Debug for KeyboardApi {
+impl<T: Transport> Debug for KeyboardApi<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        // TODO
-        todo!()
+        f.debug_struct("KeyboardApi")
+            .field("device_info", &self.device_info)
+            .field("color_correction", &self.color_correction)
+            .field("key_map", &format_args!("{}", self.key_map))
+            .finish_non_exhaustive()
     }
 }
 
-impl Display for KeyboardApi {
+impl<T: Transport> Display for KeyboardApi<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        // TODO
-        todo!()
+        write!(f, "{}", self.device_info)
     }
-} */
+}
 
-/// Kind of feature report
+/// Mode of the keyboard, see [`KeyboardApi::set_mode`]
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Copy, Hash)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(rename_all = "lowercase"))]
 #[non_exhaustive]
-enum ControlerFeatureKind {
+pub enum ControlerFeatureKind {
     /// Default rainbow behaviour.
     Rainbow,
     /// Mode where the API can send custom configuration.
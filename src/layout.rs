@@ -11,9 +11,18 @@ pub use layout_fr_ch::*;
 mod keypress;
 pub use keypress::*;
 
+mod event;
+pub use event::*;
+
 mod key_name;
 pub use key_name::*;
 
+mod key_group;
+pub use key_group::*;
+
+mod layout_kind;
+pub use layout_kind::*;
+
 mod position;
 pub use position::*;
 
@@ -86,6 +95,7 @@ impl UpperExp for KeyLight {
 
 ///  associative data for a key.
 #[derive(Clone, Debug, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct KeyInfo {
     /// Name of the key
     key_name: KeyName,
@@ -128,6 +138,11 @@ impl KeyInfo {
         &self.key_name
     }
 
+    /// Get the position of the key on the keyboard
+    pub const fn key_pos(&self) -> &Position {
+        &self.key_pos
+    }
+
     /// Get the key code ofr the led.
     pub fn key_code_light_mut(&mut self) -> &mut KeyLight {
         &mut self.key_code_light
@@ -142,6 +157,11 @@ impl KeyInfo {
     pub fn key_name_mut(&mut self) -> &mut KeyName {
         &mut self.key_name
     }
+
+    /// Get the position of the key on the keyboard
+    pub const fn key_pos_mut(&mut self) -> &mut Position {
+        &mut self.key_pos
+    }
 }
 
 /// Defines a Keyboard layout
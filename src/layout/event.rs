@@ -0,0 +1,295 @@
+//! Contains the typed input event [`KeyboardEvent`]
+
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{KeyCode, KeyPress};
+
+/// Event type byte of a regular key.
+const EVENT_TYPE_KEY: u8 = 0xfb;
+/// Event type byte of the caps lock key (which is also the easy-shift key).
+const EVENT_TYPE_CAPS_LOCK: u8 = 0x0a;
+/// Event type byte of the wheel.
+const EVENT_TYPE_WHEEL: u8 = 0xcc;
+/// Code byte of the Fn key, sent with [`EVENT_TYPE_KEY`].
+const CODE_FN: u8 = 0x77;
+/// Code byte of a wheel rotation toward the top.
+const CODE_WHEEL_UP: u8 = 0x01;
+/// Code byte of a wheel rotation toward the bottom.
+const CODE_WHEEL_DOWN: u8 = 0xff;
+
+/// Direction of a wheel rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum WheelDirection {
+    /// Rotation toward the top of the keyboard
+    Up,
+    /// Rotation toward the bottom of the keyboard
+    Down,
+}
+
+impl Display for WheelDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Up => write!(f, "up"),
+            Self::Down => write!(f, "down"),
+        }
+    }
+}
+
+/// Event decoded from a report of the read device.
+///
+/// Unlike [`KeyPress`] nothing is discarded: reports that are not understood are kept as
+/// [`KeyboardEvent::Unknown`] so they can be logged.
+/// The easy-shift and game mode switches have not been seen as distinct reports yet,
+/// if the firmware sends them they end up in [`KeyboardEvent::Unknown`].
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{KeyCode, KeyboardEvent, WheelDirection};
+///
+/// assert_eq!(
+///     KeyboardEvent::new_from_buffer([0x03, 0x00, 0xfb, 0x11, 0x01]),
+///     KeyboardEvent::KeyDown(KeyCode::new(0xfb, 0x11))
+/// );
+/// assert_eq!(
+///     KeyboardEvent::new_from_buffer([0x03, 0x00, 0xcc, 0xff, 0x00]),
+///     KeyboardEvent::Wheel(WheelDirection::Down)
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum KeyboardEvent {
+    /// A key is pressed
+    KeyDown(KeyCode),
+    /// A key is released
+    KeyUp(KeyCode),
+    /// The wheel is rotated by one step
+    Wheel(WheelDirection),
+    /// The Fn layer is activated (Fn pressed) or deactivated (Fn released)
+    FnLayer {
+        /// Whether the layer is active
+        active: bool,
+    },
+    /// Report that could not be decoded
+    Unknown {
+        /// Raw report as read
+        raw: [u8; 5],
+    },
+}
+
+impl KeyboardEvent {
+    /// Decode an event from a read device report.
+    pub const fn new_from_buffer(buffer: [u8; 5]) -> Self {
+        match (buffer[2], buffer[3]) {
+            (EVENT_TYPE_KEY, CODE_FN) => Self::FnLayer {
+                active: buffer[4] != 0,
+            },
+            (EVENT_TYPE_KEY, code) => {
+                Self::new_key(KeyCode::new(EVENT_TYPE_KEY, code), buffer[4] != 0)
+            }
+            // the caps lock signal is inverted
+            (EVENT_TYPE_CAPS_LOCK, code) => {
+                Self::new_key(KeyCode::new(EVENT_TYPE_CAPS_LOCK, code), buffer[4] == 0)
+            }
+            (EVENT_TYPE_WHEEL, CODE_WHEEL_UP) => Self::Wheel(WheelDirection::Up),
+            (EVENT_TYPE_WHEEL, CODE_WHEEL_DOWN) => Self::Wheel(WheelDirection::Down),
+            _ => Self::Unknown { raw: buffer },
+        }
+    }
+
+    /// Create a [`Self::KeyDown`] or [`Self::KeyUp`] event.
+    const fn new_key(key_code: KeyCode, is_pressed: bool) -> Self {
+        if is_pressed {
+            Self::KeyDown(key_code)
+        } else {
+            Self::KeyUp(key_code)
+        }
+    }
+
+    /// Get the [`KeyCode`] associated with the event if any.
+    ///
+    /// The wheel and the Fn layer return the code used for them in [`crate::Layout`].
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{KeyCode, KeyboardEvent};
+    ///
+    /// let event = KeyboardEvent::FnLayer { active: true };
+    /// assert_eq!(event.key_code(), Some(KeyCode::new(251, 119)));
+    /// let event = KeyboardEvent::Unknown { raw: [0; 5] };
+    /// assert_eq!(event.key_code(), None);
+    /// ```
+    pub const fn key_code(&self) -> Option<KeyCode> {
+        match self {
+            Self::KeyDown(key_code) | Self::KeyUp(key_code) => Some(*key_code),
+            Self::Wheel(WheelDirection::Up) => Some(KeyCode::new(EVENT_TYPE_WHEEL, CODE_WHEEL_UP)),
+            Self::Wheel(WheelDirection::Down) => {
+                Some(KeyCode::new(EVENT_TYPE_WHEEL, CODE_WHEEL_DOWN))
+            }
+            Self::FnLayer { .. } => Some(KeyCode::new(EVENT_TYPE_KEY, CODE_FN)),
+            Self::Unknown { .. } => None,
+        }
+    }
+
+    /// Convert the event into a [`KeyPress`] if it is linked to a key.
+    ///
+    /// A wheel rotation is considered as a press.
+    pub const fn key_press(&self) -> Option<KeyPress> {
+        match (self.key_code(), self) {
+            (Some(key_code), Self::KeyUp(_) | Self::FnLayer { active: false }) => {
+                Some(KeyPress::new(key_code, false))
+            }
+            (Some(key_code), _) => Some(KeyPress::new(key_code, true)),
+            (None, _) => None,
+        }
+    }
+
+    /// Returns whether the event could not be decoded.
+    pub const fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown { .. })
+    }
+}
+
+impl From<[u8; 5]> for KeyboardEvent {
+    fn from(buffer: [u8; 5]) -> Self {
+        Self::new_from_buffer(buffer)
+    }
+}
+
+impl From<KeyPress> for KeyboardEvent {
+    fn from(key_press: KeyPress) -> Self {
+        Self::new_key(*key_press.key_code(), key_press.is_pressed())
+    }
+}
+
+impl Display for KeyboardEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KeyDown(key_code) => write!(f, "key code {} pressed", key_code),
+            Self::KeyUp(key_code) => write!(f, "key code {} released", key_code),
+            Self::Wheel(direction) => write!(f, "wheel {}", direction),
+            Self::FnLayer { active: true } => write!(f, "fn layer activated"),
+            Self::FnLayer { active: false } => write!(f, "fn layer deactivated"),
+            Self::Unknown { raw } => write!(f, "unknown event {:02x?}", raw),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyName, Layout, LayoutFrCh};
+
+    /// Reports captured from the read device.
+    const ESCAPE_DOWN: [u8; 5] = [0x03, 0x00, 0xfb, 0x11, 0x01];
+    const ESCAPE_UP: [u8; 5] = [0x03, 0x00, 0xfb, 0x11, 0x00];
+    const CAPS_LOCK_DOWN: [u8; 5] = [0x03, 0x00, 0x0a, 0x39, 0x00];
+    const CAPS_LOCK_UP: [u8; 5] = [0x03, 0x00, 0x0a, 0x39, 0x01];
+    const FN_DOWN: [u8; 5] = [0x03, 0x00, 0xfb, 0x77, 0x01];
+    const FN_UP: [u8; 5] = [0x03, 0x00, 0xfb, 0x77, 0x00];
+    const WHEEL_UP: [u8; 5] = [0x03, 0x00, 0xcc, 0x01, 0x00];
+    const WHEEL_DOWN: [u8; 5] = [0x03, 0x00, 0xcc, 0xff, 0x00];
+    const EMPTY: [u8; 5] = [0x00; 5];
+    const UNKNOWN_TYPE: [u8; 5] = [0x03, 0x00, 0x0b, 0x01, 0x01];
+    const UNKNOWN_WHEEL: [u8; 5] = [0x03, 0x00, 0xcc, 0x05, 0x00];
+
+    #[test]
+    fn decode_keys() {
+        let layout = LayoutFrCh::new();
+
+        let event = KeyboardEvent::new_from_buffer(ESCAPE_DOWN);
+        assert_eq!(event, KeyboardEvent::KeyDown(KeyCode::new(0xfb, 0x11)));
+        let key = layout
+            .find_from_key_code(event.key_code().unwrap())
+            .unwrap();
+        assert_eq!(*key.key_name(), KeyName::Escape);
+        assert_eq!(
+            KeyboardEvent::new_from_buffer(ESCAPE_UP),
+            KeyboardEvent::KeyUp(KeyCode::new(0xfb, 0x11))
+        );
+
+        let event = KeyboardEvent::new_from_buffer(CAPS_LOCK_DOWN);
+        assert_eq!(event, KeyboardEvent::KeyDown(KeyCode::new(0x0a, 0x39)));
+        let key = layout
+            .find_from_key_code(event.key_code().unwrap())
+            .unwrap();
+        assert_eq!(*key.key_name(), KeyName::CapsLock);
+        assert_eq!(
+            KeyboardEvent::new_from_buffer(CAPS_LOCK_UP),
+            KeyboardEvent::KeyUp(KeyCode::new(0x0a, 0x39))
+        );
+    }
+
+    #[test]
+    fn decode_special() {
+        assert_eq!(
+            KeyboardEvent::new_from_buffer(FN_DOWN),
+            KeyboardEvent::FnLayer { active: true }
+        );
+        assert_eq!(
+            KeyboardEvent::new_from_buffer(FN_UP),
+            KeyboardEvent::FnLayer { active: false }
+        );
+        assert_eq!(
+            KeyboardEvent::new_from_buffer(WHEEL_UP),
+            KeyboardEvent::Wheel(WheelDirection::Up)
+        );
+        assert_eq!(
+            KeyboardEvent::new_from_buffer(WHEEL_DOWN),
+            KeyboardEvent::Wheel(WheelDirection::Down)
+        );
+
+        let layout = LayoutFrCh::new();
+        let fn_key = KeyboardEvent::new_from_buffer(FN_DOWN).key_code().unwrap();
+        assert_eq!(
+            *layout.find_from_key_code(fn_key).unwrap().key_name(),
+            KeyName::Function
+        );
+        let wheel = KeyboardEvent::new_from_buffer(WHEEL_DOWN)
+            .key_code()
+            .unwrap();
+        assert_eq!(
+            *layout.find_from_key_code(wheel).unwrap().key_name(),
+            KeyName::WheelDown
+        );
+    }
+
+    #[test]
+    fn decode_unknown() {
+        for raw in [EMPTY, UNKNOWN_TYPE, UNKNOWN_WHEEL] {
+            let event = KeyboardEvent::new_from_buffer(raw);
+            assert_eq!(event, KeyboardEvent::Unknown { raw });
+            assert!(event.is_unknown());
+            assert_eq!(event.key_code(), None);
+            assert_eq!(event.key_press(), None);
+        }
+        assert_eq!(
+            KeyboardEvent::new_from_buffer(UNKNOWN_TYPE).to_string(),
+            "unknown event [03, 00, 0b, 01, 01]"
+        );
+    }
+
+    /// The events agree with [`KeyPress::new_from_buffer`] on key reports.
+    #[test]
+    fn key_press_compatibility() {
+        for raw in [
+            ESCAPE_DOWN,
+            ESCAPE_UP,
+            CAPS_LOCK_DOWN,
+            CAPS_LOCK_UP,
+            FN_DOWN,
+            FN_UP,
+        ] {
+            let event = KeyboardEvent::new_from_buffer(raw);
+            assert_eq!(event.key_press(), Some(KeyPress::new_from_buffer(raw)));
+        }
+        let press = KeyPress::new_from_buffer(ESCAPE_DOWN);
+        assert_eq!(
+            KeyboardEvent::from(press),
+            KeyboardEvent::new_from_buffer(ESCAPE_DOWN)
+        );
+    }
+}
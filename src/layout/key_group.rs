@@ -0,0 +1,158 @@
+//! Contains the enum [`KeyGroup`]
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{KeyName, KeyParseError};
+
+/// Group of keys, used to select several keys at once.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{KeyGroup, KeyName};
+///
+/// assert!(KeyGroup::Letters.contains(KeyName::A));
+/// assert!(!KeyGroup::Letters.contains(KeyName::Key1));
+/// assert_eq!(KeyGroup::Arrows.keys().len(), 4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum KeyGroup {
+    /// Every keys except [`KeyName::Unbound`]
+    All,
+    /// F1 to F12
+    FunctionKeys,
+    /// Row of numbers above the letters
+    NumberRow,
+    /// Letters from A to Z
+    Letters,
+    /// Shift, control, alt, super, etc.
+    Modifiers,
+    /// Insert, delete, home, end, page up and page down
+    Navigation,
+    /// Arrows keys
+    Arrows,
+    /// Numeric keypad
+    Numpad,
+    /// Print screen, scroll lock and break
+    System,
+    /// Wheel up and down
+    Wheel,
+}
+
+impl KeyGroup {
+    /// Every groups in the order of declaration
+    pub const ALL: [Self; 10] = [
+        Self::All,
+        Self::FunctionKeys,
+        Self::NumberRow,
+        Self::Letters,
+        Self::Modifiers,
+        Self::Navigation,
+        Self::Arrows,
+        Self::Numpad,
+        Self::System,
+        Self::Wheel,
+    ];
+
+    /// Get the keys of the group
+    pub fn keys(self) -> &'static [KeyName] {
+        use KeyName::*;
+        match self {
+            Self::All => &KeyName::ALL[1..],
+            Self::FunctionKeys => &[F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12],
+            Self::NumberRow => &[
+                Section, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, Apostrophe,
+                Caret,
+            ],
+            Self::Letters => &[
+                A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+            ],
+            Self::Modifiers => &[
+                LeftShift,
+                RightShift,
+                LeftControl,
+                RightControl,
+                Super,
+                Alt,
+                AltGr,
+                Function,
+                Menu,
+                CapsLock,
+            ],
+            Self::Navigation => &[Insert, Home, PageUp, Delete, End, PageDown],
+            Self::Arrows => &[ArrowUp, ArrowDown, ArrowLeft, ArrowRight],
+            Self::Numpad => &[
+                NumLock,
+                NumDivide,
+                NumMultiply,
+                NumMinus,
+                NumPlus,
+                NumEnter,
+                NumDot,
+                Num1,
+                Num2,
+                Num3,
+                Num4,
+                Num5,
+                Num6,
+                Num7,
+                Num8,
+                Num9,
+                Num0,
+            ],
+            Self::System => &[PrintScreen, ScrollLock, Break],
+            Self::Wheel => &[WheelUp, WheelDown],
+        }
+    }
+
+    /// Returns whether the key is in the group
+    pub fn contains(self, key_name: KeyName) -> bool {
+        self.keys().contains(&key_name)
+    }
+}
+
+impl Display for KeyGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for KeyGroup {
+    type Err = KeyParseError;
+
+    /// Parse the name of the variant, ignoring the case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        Self::ALL
+            .iter()
+            .find(|group| format!("{:?}", group).eq_ignore_ascii_case(name))
+            .copied()
+            .ok_or_else(|| KeyParseError::UnknownKeyGroup(name.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn groups() {
+        for group in KeyGroup::ALL {
+            assert_eq!(group.to_string().parse(), Ok(group));
+            for key in group.keys() {
+                assert!(KeyGroup::All.contains(*key));
+            }
+        }
+        assert!(!KeyGroup::All.contains(KeyName::Unbound));
+        assert_eq!(KeyGroup::Letters.keys().len(), 26);
+        assert_eq!(KeyGroup::FunctionKeys.keys().len(), 12);
+        assert_eq!(
+            "Letter".parse::<KeyGroup>(),
+            Err(KeyParseError::UnknownKeyGroup("Letter".to_owned()))
+        );
+    }
+}
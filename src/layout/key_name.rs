@@ -1,6 +1,8 @@
 //! Contains the enum [`KeyName`]
 
+use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
@@ -137,6 +139,125 @@ impl Default for KeyName {
 }
 
 impl KeyName {
+    /// Every key name, including [`Self::Unbound`], in the order of declaration.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::KeyName;
+    ///
+    /// assert_eq!(KeyName::ALL[0], KeyName::Unbound);
+    /// assert!(KeyName::ALL.contains(&KeyName::Space));
+    /// ```
+    pub const ALL: [Self; 108] = [
+        Self::Unbound,
+        Self::Escape,
+        Self::F1,
+        Self::F2,
+        Self::F3,
+        Self::F4,
+        Self::F5,
+        Self::F6,
+        Self::F7,
+        Self::F8,
+        Self::F9,
+        Self::F10,
+        Self::F11,
+        Self::F12,
+        Self::PrintScreen,
+        Self::ScrollLock,
+        Self::Break,
+        Self::WheelUp,
+        Self::WheelDown,
+        Self::Section,
+        Self::Key1,
+        Self::Key2,
+        Self::Key3,
+        Self::Key4,
+        Self::Key5,
+        Self::Key6,
+        Self::Key7,
+        Self::Key8,
+        Self::Key9,
+        Self::Key0,
+        Self::Apostrophe,
+        Self::Caret,
+        Self::BackSpace,
+        Self::Tab,
+        Self::Q,
+        Self::W,
+        Self::E,
+        Self::R,
+        Self::T,
+        Self::Z,
+        Self::U,
+        Self::I,
+        Self::O,
+        Self::P,
+        Self::EGrave,
+        Self::Diaeresis,
+        Self::CapsLock,
+        Self::A,
+        Self::S,
+        Self::D,
+        Self::F,
+        Self::G,
+        Self::H,
+        Self::J,
+        Self::K,
+        Self::L,
+        Self::EAcute,
+        Self::AGrave,
+        Self::Dolar,
+        Self::Enter,
+        Self::LeftShift,
+        Self::LessThan,
+        Self::Y,
+        Self::X,
+        Self::C,
+        Self::V,
+        Self::B,
+        Self::N,
+        Self::M,
+        Self::Comma,
+        Self::Dot,
+        Self::Dash,
+        Self::RightShift,
+        Self::LeftControl,
+        Self::Super,
+        Self::Alt,
+        Self::Space,
+        Self::AltGr,
+        Self::Function,
+        Self::Menu,
+        Self::RightControl,
+        Self::Insert,
+        Self::Home,
+        Self::PageUp,
+        Self::PageDown,
+        Self::Delete,
+        Self::End,
+        Self::NumLock,
+        Self::NumDivide,
+        Self::NumMultiply,
+        Self::NumMinus,
+        Self::NumPlus,
+        Self::NumEnter,
+        Self::NumDot,
+        Self::Num1,
+        Self::Num2,
+        Self::Num3,
+        Self::Num4,
+        Self::Num5,
+        Self::Num6,
+        Self::Num7,
+        Self::Num8,
+        Self::Num9,
+        Self::Num0,
+        Self::ArrowUp,
+        Self::ArrowDown,
+        Self::ArrowLeft,
+        Self::ArrowRight,
+    ];
+
     /// Get a secprition for the key
     #[allow(clippy::non_ascii_literal)]
     pub const fn description(self) -> &'static str {
@@ -259,3 +380,69 @@ impl Display for KeyName {
         write!(f, "{}", self.description())
     }
 }
+
+impl FromStr for KeyName {
+    type Err = KeyParseError;
+
+    /// Parse the name of the variant, ignoring the case, as used by serde.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{KeyName, KeyParseError};
+    ///
+    /// assert_eq!("Escape".parse(), Ok(KeyName::Escape));
+    /// assert_eq!("pageup".parse(), Ok(KeyName::PageUp));
+    /// assert_eq!(
+    ///     "Escap".parse::<KeyName>(),
+    ///     Err(KeyParseError::UnknownKeyName("Escap".to_owned()))
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        // the debug representation is the name of the variant
+        Self::ALL
+            .iter()
+            .find(|key| format!("{:?}", key).eq_ignore_ascii_case(name))
+            .copied()
+            .ok_or_else(|| KeyParseError::UnknownKeyName(name.to_owned()))
+    }
+}
+
+/// Error returned when parsing a [`KeyName`] or a [`super::KeyGroup`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KeyParseError {
+    /// The key name does not exist
+    UnknownKeyName(String),
+    /// The key group does not exist
+    UnknownKeyGroup(String),
+}
+
+impl Display for KeyParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownKeyName(name) => write!(f, "unknown key name \"{}\"", name),
+            Self::UnknownKeyGroup(name) => write!(f, "unknown key group \"{}\"", name),
+        }
+    }
+}
+
+impl Error for KeyParseError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn all_and_parse() {
+        for (index, key) in KeyName::ALL.iter().enumerate() {
+            // no duplicate
+            assert!(!KeyName::ALL[..index].contains(key));
+            assert_eq!(format!("{:?}", key).parse(), Ok(*key));
+            assert_eq!(format!("{:?}", key).to_uppercase().parse(), Ok(*key));
+        }
+        assert_eq!(
+            "".parse::<KeyName>(),
+            Err(KeyParseError::UnknownKeyName(String::new()))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Contains the enum [`LayoutKind`]
+
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{Layout, LayoutFrCh};
+
+/// Layouts provided by the library, used to select a layout in a configuration file.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{KeyName, LayoutKind};
+///
+/// let layout = LayoutKind::FrCh.layout();
+/// assert!(layout.find_from_key_name(KeyName::Escape).is_some());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum LayoutKind {
+    /// Swiss French layout, see [`LayoutFrCh`]
+    FrCh,
+}
+
+impl LayoutKind {
+    /// Get the layout
+    pub const fn layout(self) -> &'static dyn Layout {
+        match self {
+            Self::FrCh => &LayoutFrCh,
+        }
+    }
+}
+
+impl Default for LayoutKind {
+    /// Returns [`Self::FrCh`]
+    fn default() -> Self {
+        Self::FrCh
+    }
+}
+
+impl Display for LayoutKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FrCh => write!(f, "Swiss French"),
+        }
+    }
+}
@@ -3,8 +3,12 @@
 use std::fmt::{Display, Formatter};
 use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
 
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
 /// Represent the position of a key
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct Position {
     /// X coord
     x: f64,
@@ -140,4 +144,24 @@ mod test {
         assert_eq!(format!("{}", p1), "(1, 2)");
         assert_eq!(format!("{}", p2), "(0, 4)");
     }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn serde() {
+        use crate::layout::{KeyCode, KeyInfo, KeyLight, KeyName};
+
+        let position = Position::new(1.5_f64, -2_f64);
+        let string = serde_json::to_string(&position).unwrap();
+        assert_eq!(string, "{\"x\":1.5,\"y\":-2.0}");
+        assert_eq!(serde_json::from_str::<Position>(&string).unwrap(), position);
+
+        let info = KeyInfo::new(
+            KeyLight::new(0),
+            KeyCode::new(251, 17),
+            KeyName::Escape,
+            Position::new(0_f64, 5_f64),
+        );
+        let string = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<KeyInfo>(&string).unwrap(), info);
+    }
 }
@@ -8,7 +8,6 @@
 // - multi threading
 // - API Check liste
 // - more color otions
-// - display / debug keyboard api by storing hdiapi::DeviceInfo
 // - builder
 
 //#![warn(clippy::as_conversions)]
@@ -47,18 +46,37 @@
 #![warn(clippy::missing_docs_in_private_items)]
 //#![doc(test(attr(deny(warnings))))]
 
+mod alert;
+mod analytics;
 mod color;
+#[cfg(all(unix, feature = "serde-serialize"))]
+mod daemon;
+mod effect;
 mod error;
 mod interface;
 mod keyboard;
 mod layout;
+mod metrics;
+mod openrgb;
+mod preview;
+mod profile;
 mod reports;
+mod transport;
 
 #[cfg(test)]
 mod test;
 
+#[doc(inline)]
+pub use alert::*;
+#[doc(inline)]
+pub use analytics::*;
 #[doc(inline)]
 pub use color::*;
+#[cfg(all(unix, feature = "serde-serialize"))]
+#[doc(inline)]
+pub use daemon::*;
+#[doc(inline)]
+pub use effect::*;
 #[doc(inline)]
 pub use error::*;
 #[doc(inline)]
@@ -67,3 +85,15 @@ pub use interface::*;
 pub use keyboard::*;
 #[doc(inline)]
 pub use layout::*;
+#[doc(inline)]
+pub use metrics::*;
+#[doc(inline)]
+pub use openrgb::*;
+#[doc(inline)]
+pub use preview::*;
+#[doc(inline)]
+pub use profile::*;
+#[doc(inline)]
+pub use reports::*;
+#[doc(inline)]
+pub use transport::*;
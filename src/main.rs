@@ -1,67 +1,18 @@
-use std::time::Duration;
+//! Command line tool to control the lighting of a Roccat Vulcan keyboard.
+//!
+//! Run with `help` to get the list of commands.
 
-use roccat_vulcan_api_rs::{
-    ColorBuffer, ColorRgb, ErrorRoccatVulcanApi, KeyName, KeyboardApi, Layout, LayoutFrCh,
-    NUMBER_KEY_LED_BUFFER,
-};
+use std::process::exit;
 
-fn main() -> Result<(), ErrorRoccatVulcanApi> {
-    //let api = hidapi::HidApi::new().unwrap();
+mod cli;
 
-    let mut key_press_mask: [bool; NUMBER_KEY_LED_BUFFER] = [false; NUMBER_KEY_LED_BUFFER];
-    let keyboard = KeyboardApi::new()?;
-    let base_color = ColorRgb::new(255, 255, 255);
-    let press_color = vec![
-        ColorRgb::new(255, 0, 255),
-        ColorRgb::new(0, 0, 255),
-        ColorRgb::new(255, 0, 0),
-        ColorRgb::new(0, 255, 255),
-    ];
-    let mut press_count: usize = 0;
-    let mut buffer = ColorBuffer::<ColorRgb>::from_element(base_color);
-    let layout = LayoutFrCh::new();
-    keyboard.render(&buffer)?;
-    'mainloop: loop {
-        let result = keyboard.read_key_press(Duration::from_millis(62));
-        if let Ok(val) = result {
-            for keypress in val {
-                let a = layout.find_from_key_code(*keypress.key_code());
-                if let Some(key) = a {
-                    if *key.key_name() == KeyName::Escape {
-                        break 'mainloop;
-                    }
-                    let index_key = key.key_code_light().code() as usize;
-                    if index_key < buffer.buffer().len() {
-                        if keypress.is_pressed() {
-                            buffer.buffer_mut()[index_key] =
-                                press_color[press_count % press_color.len()];
-                            press_count += 1;
-                        }
-                        key_press_mask[index_key] = keypress.is_pressed();
-                    }
-                }
-            }
-        }
-        keyboard.render(&buffer).unwrap();
-        for (index, el) in buffer.buffer_mut().iter_mut().enumerate() {
-            if *el != base_color && !key_press_mask[index] {
-                if el.r() > base_color.r() {
-                    *el.r_mut() -= 5.min(el.r() - base_color.r());
-                } else {
-                    *el.r_mut() += 5.min(base_color.r() - el.r());
-                }
-                if el.g() > base_color.g() {
-                    *el.g_mut() -= 5.min(el.g() - base_color.g());
-                } else {
-                    *el.g_mut() += 5.min(base_color.g() - el.g());
-                }
-                if el.b() > base_color.b() {
-                    *el.b_mut() -= 5.min(el.b() - base_color.b());
-                } else {
-                    *el.b_mut() += 5.min(base_color.b() - el.b());
-                }
-            }
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(error) = cli::parse(&args).and_then(cli::run) {
+        eprintln!("error: {}", error);
+        if let cli::CliError::Usage(_) = error {
+            eprintln!("run with --help to get the list of commands");
         }
+        exit(error.exit_code());
     }
-    Ok(())
 }
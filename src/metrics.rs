@@ -0,0 +1,11 @@
+//! System metrics read from `/proc` and shown on the keyboard by [`SystemMetrics`].
+//!
+//! The files are parsed by [`CpuTimes::parse_stat`], [`MemoryInfo::parse`],
+//! [`LoadAverage::parse`] and [`DiskStats::parse`], which take the content of the files
+//! so that they can be tested without the system.
+
+mod proc;
+pub use proc::*;
+
+mod effect;
+pub use effect::*;
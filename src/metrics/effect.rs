@@ -0,0 +1,380 @@
+//! Contains the effect [`SystemMetrics`] and [`Metric`]
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use super::{MetricsSource, ProcSource, SystemSnapshot};
+use crate::{
+    color::{ColorBuffer, ColorRgb, Gradient, NUMBER_KEY_LED_BUFFER},
+    effect::Effect,
+    layout::{KeyGroup, Layout},
+};
+
+/// Default interval between two readings of the metrics
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A value of the system shown by [`SystemMetrics`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Metric {
+    /// Usage of each core, one key per core in the order of the group
+    CoreUsage,
+    /// Usage of all the CPUs together, as a bar
+    CpuUsage,
+    /// Fraction of the memory used, as a bar
+    MemoryPressure,
+    /// Load average over one minute divided by the number of cores, as a bar
+    Load,
+    /// Bytes read and written per second by the disks, as a bar full at `full_scale`
+    DiskIo {
+        /// Bytes per second filling the bar
+        full_scale: f64,
+    },
+}
+
+impl Metric {
+    /// Get the levels between 0 and 1 shown, one per core for [`Self::CoreUsage`]
+    /// and one otherwise
+    fn levels(
+        self,
+        current: &SystemSnapshot,
+        previous: Option<&SystemSnapshot>,
+        interval: Duration,
+    ) -> Vec<f64> {
+        let level = match self {
+            Self::CoreUsage => {
+                let zero = Vec::new();
+                let previous = previous.map_or(&zero[..], SystemSnapshot::cores);
+                return current
+                    .cores()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, core)| {
+                        core.usage_since(&previous.get(index).copied().unwrap_or_default())
+                    })
+                    .collect();
+            }
+            Self::CpuUsage => current
+                .cpu()
+                .usage_since(&previous.map(SystemSnapshot::cpu).unwrap_or_default()),
+            Self::MemoryPressure => current.memory().pressure(),
+            Self::Load => current.load().one() / current.cores().len().max(1) as f64,
+            Self::DiskIo { full_scale } => match previous {
+                Some(previous) if interval > Duration::ZERO => {
+                    let bytes = current.disk().transferred_since(&previous.disk());
+                    bytes as f64 / interval.as_secs_f64() / full_scale
+                }
+                _ => 0_f64,
+            },
+        };
+        vec![level.clamp(0_f64, 1_f64)]
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CoreUsage => write!(f, "usage per core"),
+            Self::CpuUsage => write!(f, "cpu usage"),
+            Self::MemoryPressure => write!(f, "memory pressure"),
+            Self::Load => write!(f, "load average"),
+            Self::DiskIo { full_scale } => write!(f, "disk io up to {} B/s", full_scale),
+        }
+    }
+}
+
+/// A metric shown on a group of keys
+#[derive(Debug, Clone, PartialEq)]
+struct Binding {
+    /// Value shown
+    metric: Metric,
+    /// Keys showing the value
+    group: KeyGroup,
+    /// Colors from a level of 0 to 1
+    gradient: Gradient,
+    /// Levels of the last reading
+    levels: Vec<f64>,
+}
+
+/// Values of the system shown on groups of keys, read at a fixed interval from a
+/// [`MetricsSource`].
+///
+/// A metric per core colors one key per core with the gradient. The other metrics fill the
+/// keys of their group as a bar, from the first key of the group, each key in the color of
+/// the gradient at its place in the bar. The other keys are left as they are.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{ColorRgb, Gradient, KeyGroup, Metric, ProcSource, SystemMetrics};
+///
+/// let gradient =
+///     Gradient::new_evenly_spaced(&[ColorRgb::new(0, 255, 0), ColorRgb::new(255, 0, 0)]).unwrap();
+/// let effect = SystemMetrics::new(ProcSource::new(), Duration::from_secs(2))
+///     .unwrap()
+///     .with_metric(Metric::CoreUsage, KeyGroup::FunctionKeys, gradient.clone())
+///     .with_metric(Metric::Load, KeyGroup::NumberRow, gradient);
+/// assert_eq!(effect.interval(), Duration::from_secs(2));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemMetrics<S: MetricsSource> {
+    /// Source of the metrics
+    source: S,
+    /// Interval between two readings
+    interval: Duration,
+    /// Metrics shown
+    bindings: Vec<Binding>,
+    /// Last reading and its time
+    last: Option<(Duration, SystemSnapshot)>,
+}
+
+impl<S: MetricsSource> SystemMetrics<S> {
+    /// Create the effect without metric, reading `source` every `interval`.
+    ///
+    /// Returns [`None`] if `interval` is zero.
+    pub fn new(source: S, interval: Duration) -> Option<Self> {
+        if interval.is_zero() {
+            return None;
+        }
+        Some(Self {
+            source,
+            interval,
+            bindings: Vec::new(),
+            last: None,
+        })
+    }
+
+    /// Show `metric` on the keys of `group`, with the colors of `gradient` from a level
+    /// of 0 to 1
+    pub fn with_metric(mut self, metric: Metric, group: KeyGroup, gradient: Gradient) -> Self {
+        self.bindings.push(Binding {
+            metric,
+            group,
+            gradient,
+            levels: Vec::new(),
+        });
+        self
+    }
+
+    /// Get the source of the metrics
+    pub const fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Get the interval between two readings
+    pub const fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Get the levels between 0 and 1 of the metrics of the last reading,
+    /// in the order they were added
+    pub fn levels(&self) -> impl Iterator<Item = (Metric, &[f64])> + '_ {
+        self.bindings
+            .iter()
+            .map(|binding| (binding.metric, &binding.levels[..]))
+    }
+
+    /// Read the source if the interval passed since the last reading.
+    ///
+    /// When the source returns an error the previous levels stay shown.
+    fn update(&mut self, elapsed: Duration) {
+        let due = match &self.last {
+            Some((time, _)) => elapsed.saturating_sub(*time) >= self.interval || elapsed < *time,
+            None => true,
+        };
+        if !due {
+            return;
+        }
+        let snapshot = match self.source.snapshot() {
+            Ok(snapshot) => snapshot,
+            Err(_) => return,
+        };
+        let (previous, interval) = match &self.last {
+            Some((time, previous)) => (Some(previous), elapsed.saturating_sub(*time)),
+            None => (None, Duration::ZERO),
+        };
+        for binding in &mut self.bindings {
+            binding.levels = binding.metric.levels(&snapshot, previous, interval);
+        }
+        self.last = Some((elapsed, snapshot));
+    }
+}
+
+impl Default for SystemMetrics<ProcSource> {
+    /// Read `/proc` every second, the usage per core on the function keys and the memory
+    /// pressure on the number row, from green to red
+    fn default() -> Self {
+        let effect = Self {
+            source: ProcSource::new(),
+            interval: DEFAULT_INTERVAL,
+            bindings: Vec::new(),
+            last: None,
+        };
+        let gradient = Gradient::new_evenly_spaced(&[
+            ColorRgb::new(0, 255, 0),
+            ColorRgb::new(255, 255, 0),
+            ColorRgb::new(255, 0, 0),
+        ]);
+        match gradient {
+            Some(gradient) => effect
+                .with_metric(Metric::CoreUsage, KeyGroup::FunctionKeys, gradient.clone())
+                .with_metric(Metric::MemoryPressure, KeyGroup::NumberRow, gradient),
+            None => effect,
+        }
+    }
+}
+
+impl<S: MetricsSource> Effect for SystemMetrics<S> {
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn render(
+        &mut self,
+        buffer: &mut ColorBuffer<ColorRgb>,
+        layout: &dyn Layout,
+        elapsed: Duration,
+    ) {
+        self.update(elapsed);
+        for binding in &self.bindings {
+            let lights: Vec<usize> = binding
+                .group
+                .keys()
+                .iter()
+                .filter_map(|key| layout.find_from_key_name(*key))
+                .map(|info| usize::from(info.key_code_light().code()))
+                .filter(|light| *light < NUMBER_KEY_LED_BUFFER)
+                .collect();
+            if binding.metric == Metric::CoreUsage {
+                for (light, level) in lights.iter().zip(&binding.levels) {
+                    buffer[*light] = binding.gradient.sample(*level);
+                }
+            } else if let Some(level) = binding.levels.first() {
+                let count = lights.len() as f64;
+                let lit = (level * count).round() as usize;
+                for (index, light) in lights.iter().take(lit).enumerate() {
+                    buffer[*light] = binding.gradient.sample((index as f64 + 0.5_f64) / count);
+                }
+            }
+        }
+    }
+}
+
+impl<S: MetricsSource> Display for SystemMetrics<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "system metrics every {} s", self.interval.as_secs_f64())?;
+        for binding in &self.bindings {
+            write!(f, ", {} on {}", binding.metric, binding.group)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::layout::{KeyName, LayoutFrCh};
+
+    /// Source reading the fixture `before` then the fixture `after`
+    struct Fixtures {
+        /// Number of readings
+        count: usize,
+    }
+
+    impl MetricsSource for Fixtures {
+        fn snapshot(&mut self) -> io::Result<SystemSnapshot> {
+            let name = if self.count == 0 { "before" } else { "after" };
+            self.count += 1;
+            let root: PathBuf = [
+                env!("CARGO_MANIFEST_DIR"),
+                "src",
+                "metrics",
+                "fixtures",
+                name,
+            ]
+            .iter()
+            .collect();
+            ProcSource::with_root(root).snapshot()
+        }
+    }
+
+    /// Gradient from black to white
+    fn gradient() -> Gradient {
+        Gradient::new_evenly_spaced(&[ColorRgb::new(0, 0, 0), ColorRgb::new(255, 255, 255)])
+            .unwrap()
+    }
+
+    #[test]
+    fn fixtures() {
+        let layout = LayoutFrCh::new();
+        let mut effect = SystemMetrics::new(Fixtures { count: 0 }, Duration::from_secs(1))
+            .unwrap()
+            .with_metric(Metric::CoreUsage, KeyGroup::FunctionKeys, gradient())
+            .with_metric(Metric::MemoryPressure, KeyGroup::NumberRow, gradient())
+            .with_metric(Metric::Load, KeyGroup::Arrows, gradient())
+            .with_metric(
+                Metric::DiskIo {
+                    full_scale: 4_096_f64 * 512_f64,
+                },
+                KeyGroup::Navigation,
+                gradient(),
+            );
+        let red = ColorRgb::new(255, 0, 0);
+        let mut buffer = ColorBuffer::from_element(red);
+        let color = |buffer: &ColorBuffer<ColorRgb>, key| *buffer.get_key(&layout, key).unwrap();
+
+        effect.render(&mut buffer, &layout, Duration::ZERO);
+        assert_eq!(effect.source().count, 1);
+        // usage since the boot, 10 %
+        assert_eq!(color(&buffer, KeyName::F1), gradient().sample(0.1_f64));
+        // the bar of 13 keys is filled at 25 %
+        assert_ne!(color(&buffer, KeyName::Key2), red);
+        assert_eq!(color(&buffer, KeyName::Key3), red);
+
+        // not read again before the interval
+        effect.render(&mut buffer, &layout, Duration::from_millis(500));
+        assert_eq!(effect.source().count, 1);
+
+        let mut buffer = ColorBuffer::from_element(red);
+        effect.render(&mut buffer, &layout, Duration::from_secs(2));
+        assert_eq!(effect.source().count, 2);
+        let levels: Vec<(Metric, Vec<f64>)> = effect
+            .levels()
+            .map(|(metric, levels)| (metric, levels.to_vec()))
+            .collect();
+        assert_eq!(levels[0].1, [1_f64, 0.5_f64, 0.25_f64, 0_f64]);
+        assert_eq!(levels[1].1, [0.75_f64]);
+        // load of 6 on 4 cores
+        assert_eq!(levels[2].1, [1_f64]);
+        // 2048 sectors in 2 seconds
+        assert_eq!(levels[3].1, [0.25_f64]);
+
+        assert_eq!(color(&buffer, KeyName::F1), ColorRgb::new(255, 255, 255));
+        assert_eq!(color(&buffer, KeyName::F4), ColorRgb::new(0, 0, 0));
+        // four cores only
+        assert_eq!(color(&buffer, KeyName::F5), red);
+        assert_ne!(color(&buffer, KeyName::Key9), red);
+        assert_eq!(color(&buffer, KeyName::Key0), red);
+        assert_ne!(color(&buffer, KeyName::ArrowRight), red);
+    }
+
+    #[test]
+    fn errors() {
+        let layout = LayoutFrCh::new();
+        assert!(SystemMetrics::new(ProcSource::new(), Duration::ZERO).is_none());
+        let mut effect = SystemMetrics::new(ProcSource::with_root("/missing"), DEFAULT_INTERVAL)
+            .unwrap()
+            .with_metric(Metric::CpuUsage, KeyGroup::Letters, gradient());
+        let mut buffer = ColorBuffer::from_element(ColorRgb::new(1, 2, 3));
+        effect.render(&mut buffer, &layout, Duration::ZERO);
+        assert_eq!(buffer, ColorBuffer::from_element(ColorRgb::new(1, 2, 3)));
+
+        let effect = SystemMetrics::default();
+        assert_eq!(
+            effect.to_string(),
+            "system metrics every 1 s, usage per core on FunctionKeys, \
+            memory pressure on NumberRow"
+        );
+    }
+}
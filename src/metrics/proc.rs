@@ -0,0 +1,481 @@
+//! Contains the parsers of the files of `/proc` and [`ProcSource`]
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Size of a sector in `/proc/diskstats`, independent of the device
+const SECTOR_SIZE: u64 = 512;
+
+/// Time spent by a CPU since the boot, from a `cpu` line of `/proc/stat`, in clock ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CpuTimes {
+    /// Time spent working
+    busy: u64,
+    /// Time spent idle or waiting for inputs and outputs
+    idle: u64,
+}
+
+impl CpuTimes {
+    /// Create the times from the ticks spent working and idle
+    pub const fn new(busy: u64, idle: u64) -> Self {
+        Self { busy, idle }
+    }
+
+    /// Get the ticks spent working
+    pub const fn busy(&self) -> u64 {
+        self.busy
+    }
+
+    /// Get the ticks spent idle or waiting for inputs and outputs
+    pub const fn idle(&self) -> u64 {
+        self.idle
+    }
+
+    /// Get the fraction of the time spent working between `previous` and `self`,
+    /// between 0 and 1, or 0 if no time passed
+    pub fn usage_since(&self, previous: &Self) -> f64 {
+        let busy = self.busy.saturating_sub(previous.busy);
+        let total = busy + self.idle.saturating_sub(previous.idle);
+        if total == 0 {
+            0_f64
+        } else {
+            busy as f64 / total as f64
+        }
+    }
+
+    /// Parse the content of `/proc/stat`, returns the times of all the CPUs together
+    /// followed by the times of each core, or [`None`] if the content is not valid.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::CpuTimes;
+    ///
+    /// let stat = "cpu  10 0 10 80 0 0 0 0 0 0\ncpu0 10 0 10 80 0 0 0 0 0 0\nintr 1\n";
+    /// let (total, cores) = CpuTimes::parse_stat(stat).unwrap();
+    /// assert_eq!(total, CpuTimes::new(20, 80));
+    /// assert_eq!(cores, vec![CpuTimes::new(20, 80)]);
+    /// ```
+    pub fn parse_stat(content: &str) -> Option<(Self, Vec<Self>)> {
+        let mut total = None;
+        let mut cores = Vec::new();
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            let name = match fields.next() {
+                Some(name) if name.starts_with("cpu") => name,
+                _ => continue,
+            };
+            // user nice system idle iowait irq softirq steal, the guest time is in user
+            let values = fields
+                .take(8)
+                .map(|field| field.parse::<u64>().ok())
+                .collect::<Option<Vec<u64>>>()?;
+            if values.len() < 4 {
+                return None;
+            }
+            let value = |index: usize| values.get(index).copied().unwrap_or(0);
+            let times = Self::new(
+                value(0) + value(1) + value(2) + value(5) + value(6) + value(7),
+                value(3) + value(4),
+            );
+            if name == "cpu" {
+                total = Some(times);
+            } else {
+                cores.push(times);
+            }
+        }
+        total.map(|total| (total, cores))
+    }
+}
+
+/// Memory of the system, from `/proc/meminfo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MemoryInfo {
+    /// Total usable memory in KiB
+    total: u64,
+    /// Memory available for new programs without swapping in KiB
+    available: u64,
+}
+
+impl MemoryInfo {
+    /// Create the information from the total and the available memory in KiB
+    pub const fn new(total: u64, available: u64) -> Self {
+        Self { total, available }
+    }
+
+    /// Get the total usable memory in KiB
+    pub const fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Get the memory available for new programs without swapping in KiB
+    pub const fn available(&self) -> u64 {
+        self.available
+    }
+
+    /// Get the fraction of the memory used, between 0 and 1
+    pub fn pressure(&self) -> f64 {
+        if self.total == 0 {
+            0_f64
+        } else {
+            self.total.saturating_sub(self.available) as f64 / self.total as f64
+        }
+    }
+
+    /// Parse the content of `/proc/meminfo`, returns [`None`] if `MemTotal`
+    /// or `MemAvailable` is missing.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::MemoryInfo;
+    ///
+    /// let meminfo = "MemTotal:  1000 kB\nMemFree:  100 kB\nMemAvailable:  250 kB\n";
+    /// let memory = MemoryInfo::parse(meminfo).unwrap();
+    /// assert_eq!(memory.pressure(), 0.75_f64);
+    /// ```
+    pub fn parse(content: &str) -> Option<Self> {
+        let field = |key: &str| {
+            content.lines().find_map(|line| {
+                let mut fields = line.split_whitespace();
+                if fields.next()?.strip_suffix(':')? == key {
+                    fields.next()?.parse::<u64>().ok()
+                } else {
+                    None
+                }
+            })
+        };
+        Some(Self::new(field("MemTotal")?, field("MemAvailable")?))
+    }
+}
+
+/// Number of processes running or waiting, averaged over 1, 5 and 15 minutes,
+/// from `/proc/loadavg`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LoadAverage {
+    /// Average over one minute
+    one: f64,
+    /// Average over five minutes
+    five: f64,
+    /// Average over fifteen minutes
+    fifteen: f64,
+}
+
+impl LoadAverage {
+    /// Create the averages over 1, 5 and 15 minutes
+    pub const fn new(one: f64, five: f64, fifteen: f64) -> Self {
+        Self { one, five, fifteen }
+    }
+
+    /// Get the average over one minute
+    pub const fn one(&self) -> f64 {
+        self.one
+    }
+
+    /// Get the average over five minutes
+    pub const fn five(&self) -> f64 {
+        self.five
+    }
+
+    /// Get the average over fifteen minutes
+    pub const fn fifteen(&self) -> f64 {
+        self.fifteen
+    }
+
+    /// Parse the content of `/proc/loadavg`, returns [`None`] if it is not valid
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::LoadAverage;
+    ///
+    /// let load = LoadAverage::parse("0.68 0.52 0.41 2/72 14941\n").unwrap();
+    /// assert_eq!(load.one(), 0.68_f64);
+    /// ```
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut fields = content
+            .split_whitespace()
+            .map(|field| field.parse::<f64>().ok());
+        Some(Self::new(fields.next()??, fields.next()??, fields.next()??))
+    }
+}
+
+/// Bytes read and written by the disks since the boot, from `/proc/diskstats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DiskStats {
+    /// Bytes read
+    read: u64,
+    /// Bytes written
+    written: u64,
+}
+
+impl DiskStats {
+    /// Create the statistics from the bytes read and written
+    pub const fn new(read: u64, written: u64) -> Self {
+        Self { read, written }
+    }
+
+    /// Get the bytes read
+    pub const fn read(&self) -> u64 {
+        self.read
+    }
+
+    /// Get the bytes written
+    pub const fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Get the bytes read and written between `previous` and `self`
+    pub const fn transferred_since(&self, previous: &Self) -> u64 {
+        self.read.saturating_sub(previous.read) + self.written.saturating_sub(previous.written)
+    }
+
+    /// Parse the content of `/proc/diskstats`, returns [`None`] if it is not valid.
+    ///
+    /// The partitions are not counted twice with their disk, and the loop and ram
+    /// devices are ignored.
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::DiskStats;
+    ///
+    /// let diskstats = "   8  0 sda 10 0 4 0 20 0 8 0 0 0 0\n   8  1 sda1 10 0 4 0 20 0 8 0 0 0 0\n";
+    /// assert_eq!(
+    ///     DiskStats::parse(diskstats),
+    ///     Some(DiskStats::new(4 * 512, 8 * 512))
+    /// );
+    /// ```
+    pub fn parse(content: &str) -> Option<Self> {
+        let devices = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let sectors = |index: usize| fields.get(index)?.parse::<u64>().ok();
+                Some((*fields.get(2)?, sectors(5)?, sectors(9)?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let stats = devices
+            .iter()
+            .filter(|(name, _, _)| !name.starts_with("loop") && !name.starts_with("ram"))
+            .filter(|(name, _, _)| {
+                !devices
+                    .iter()
+                    .any(|(disk, _, _)| is_partition_of(name, disk))
+            })
+            .fold(Self::default(), |stats, (_, read, written)| {
+                Self::new(
+                    stats.read + read * SECTOR_SIZE,
+                    stats.written + written * SECTOR_SIZE,
+                )
+            });
+        Some(stats)
+    }
+}
+
+/// Whether `name` is a partition of `disk`: the name of the disk followed by a number,
+/// with a `p` in between when the name of the disk ends with a digit (`sda1` of `sda`,
+/// `nvme0n1p1` of `nvme0n1`, but not `sdaa` of `sda` nor `md12` of `md1`)
+fn is_partition_of(name: &str, disk: &str) -> bool {
+    let number = match name.strip_prefix(disk) {
+        Some(rest) if disk.ends_with(|c: char| c.is_ascii_digit()) => rest.strip_prefix('p'),
+        rest => rest,
+    };
+    number.is_some_and(|number| {
+        !number.is_empty() && number.bytes().all(|byte| byte.is_ascii_digit())
+    })
+}
+
+/// State of the system at one time
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SystemSnapshot {
+    /// Times of all the CPUs together
+    cpu: CpuTimes,
+    /// Times of each core
+    cores: Vec<CpuTimes>,
+    /// Memory
+    memory: MemoryInfo,
+    /// Load average
+    load: LoadAverage,
+    /// Disk inputs and outputs
+    disk: DiskStats,
+}
+
+impl SystemSnapshot {
+    /// Create a snapshot
+    pub const fn new(
+        cpu: CpuTimes,
+        cores: Vec<CpuTimes>,
+        memory: MemoryInfo,
+        load: LoadAverage,
+        disk: DiskStats,
+    ) -> Self {
+        Self {
+            cpu,
+            cores,
+            memory,
+            load,
+            disk,
+        }
+    }
+
+    /// Get the times of all the CPUs together
+    pub const fn cpu(&self) -> CpuTimes {
+        self.cpu
+    }
+
+    /// Get the times of each core
+    pub fn cores(&self) -> &[CpuTimes] {
+        &self.cores
+    }
+
+    /// Get the memory
+    pub const fn memory(&self) -> MemoryInfo {
+        self.memory
+    }
+
+    /// Get the load average
+    pub const fn load(&self) -> LoadAverage {
+        self.load
+    }
+
+    /// Get the disk inputs and outputs
+    pub const fn disk(&self) -> DiskStats {
+        self.disk
+    }
+}
+
+/// Source of the [`SystemSnapshot`] shown by [`super::SystemMetrics`]
+pub trait MetricsSource {
+    /// Read the current state of the system
+    /// # Errors
+    /// Returns an error if the state can't be read.
+    fn snapshot(&mut self) -> io::Result<SystemSnapshot>;
+}
+
+impl<S: MetricsSource + ?Sized> MetricsSource for Box<S> {
+    fn snapshot(&mut self) -> io::Result<SystemSnapshot> {
+        (**self).snapshot()
+    }
+}
+
+/// [`MetricsSource`] reading the files `stat`, `meminfo`, `loadavg` and `diskstats`
+/// of `/proc`, or of another folder with the same files.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{MetricsSource, ProcSource};
+///
+/// let mut source = ProcSource::new();
+/// # #[cfg(target_os = "linux")]
+/// assert!(source.snapshot().unwrap().memory().total() > 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProcSource {
+    /// Folder of the files
+    root: PathBuf,
+}
+
+impl ProcSource {
+    /// Create a source reading `/proc`
+    pub fn new() -> Self {
+        Self::with_root("/proc")
+    }
+
+    /// Create a source reading the files in `root` instead of `/proc`
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Get the folder of the files
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Read a file of the folder and parse it
+    fn read<T>(&self, name: &str, parse: impl FnOnce(&str) -> Option<T>) -> io::Result<T> {
+        let path = self.root.join(name);
+        parse(&fs::read_to_string(&path)?).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid file {}", path.display()),
+            )
+        })
+    }
+}
+
+impl Default for ProcSource {
+    /// Read `/proc`
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for ProcSource {
+    fn snapshot(&mut self) -> io::Result<SystemSnapshot> {
+        let (cpu, cores) = self.read("stat", CpuTimes::parse_stat)?;
+        Ok(SystemSnapshot::new(
+            cpu,
+            cores,
+            self.read("meminfo", MemoryInfo::parse)?,
+            self.read("loadavg", LoadAverage::parse)?,
+            self.read("diskstats", DiskStats::parse)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Folder of the files of a fixture
+    fn fixture(name: &str) -> PathBuf {
+        [
+            env!("CARGO_MANIFEST_DIR"),
+            "src",
+            "metrics",
+            "fixtures",
+            name,
+        ]
+        .iter()
+        .collect()
+    }
+
+    #[test]
+    fn fixtures() {
+        let before = ProcSource::with_root(fixture("before")).snapshot().unwrap();
+        assert_eq!(before.cores().len(), 4);
+        assert_eq!(before.cpu(), CpuTimes::new(1_000, 9_000));
+        assert_eq!(before.cores()[0], CpuTimes::new(250, 2_250));
+        assert_eq!(before.memory(), MemoryInfo::new(16_000_000, 12_000_000));
+        assert!((before.memory().pressure() - 0.25_f64).abs() < 1E-12_f64);
+        assert_eq!(before.load(), LoadAverage::new(1.5_f64, 1_f64, 0.5_f64));
+        // sda, sdaa and nvme0n1 without their partitions, and without loop0
+        assert_eq!(before.disk(), DiskStats::new(3_500 * 512, 5_700 * 512));
+
+        let after = ProcSource::with_root(fixture("after")).snapshot().unwrap();
+        let usages: Vec<f64> = after
+            .cores()
+            .iter()
+            .zip(before.cores())
+            .map(|(after, before)| after.usage_since(before))
+            .collect();
+        assert_eq!(usages, [1_f64, 0.5_f64, 0.25_f64, 0_f64]);
+        assert_eq!(after.disk().transferred_since(&before.disk()), 2_048 * 512);
+
+        let error = ProcSource::with_root(fixture("missing"))
+            .snapshot()
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(CpuTimes::parse_stat("intr 1 2\n"), None);
+        assert_eq!(CpuTimes::parse_stat("cpu 1 a 3 4\n"), None);
+        assert_eq!(CpuTimes::parse_stat("cpu 1 2\n"), None);
+        assert_eq!(MemoryInfo::parse("MemTotal: 10 kB\n"), None);
+        assert_eq!(LoadAverage::parse("0.5 0.1"), None);
+        assert_eq!(DiskStats::parse("8 0 sda 1 2\n"), None);
+        let raids = "9 1 md1 0 0 1 0 0 0 1\n9 12 md12 0 0 2 0 0 0 2\n259 2 md1p1 0 0 1 0 0 0 1\n";
+        assert_eq!(
+            DiskStats::parse(raids),
+            Some(DiskStats::new(3 * 512, 3 * 512))
+        );
+        assert_eq!(CpuTimes::new(5, 5).usage_since(&CpuTimes::new(5, 5)), 0_f64);
+        assert_eq!(MemoryInfo::default().pressure(), 0_f64);
+    }
+}
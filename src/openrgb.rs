@@ -0,0 +1,18 @@
+//! Server of the OpenRGB SDK network protocol.
+//!
+//! [OpenRGB](https://openrgb.org) clients control the lighting of devices through a TCP
+//! server, by default on the port [`OPENRGB_DEFAULT_PORT`]. The [`OpenRgbServer`] speaks the
+//! version 3 of this protocol and presents the keyboard as one keyboard device,
+//! so the clients already used for other peripherals can drive it.
+//!
+//! The packets start with a header of 16 bytes, the magic `ORGB` followed by the index of the
+//! device, the identifier of the packet and the size of the data, as little endian u32.
+//! The server answers the requests for the number of devices, their description, the version
+//! of the protocol and the profiles (there is none). It applies the updates of the colors
+//! of the LEDs and of the mode. The other packets are ignored.
+
+mod protocol;
+pub use protocol::OPENRGB_DEFAULT_PORT;
+
+mod server;
+pub use server::*;
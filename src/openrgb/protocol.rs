@@ -0,0 +1,572 @@
+//! Encoding of the packets of the OpenRGB SDK protocol
+
+use std::convert::TryFrom;
+use std::io::{self, ErrorKind, Read};
+
+use crate::{
+    color::{ColorBuffer, ColorRgb, NUMBER_KEY_LED_BUFFER},
+    keyboard::ControlerFeatureKind,
+    layout::{KeyName, Layout},
+};
+
+/// Default TCP port of the OpenRGB SDK server
+pub const OPENRGB_DEFAULT_PORT: u16 = 6742;
+
+/// Most recent version of the protocol understood by the server
+pub(crate) const PROTOCOL_VERSION: u32 = 3;
+
+/// Magic bytes starting each packet
+const MAGIC: [u8; 4] = *b"ORGB";
+/// Size of the header of a packet
+const HEADER_SIZE: usize = 16;
+/// Largest packet accepted, to avoid allocating whatever size a client sends
+const MAX_PACKET_SIZE: u32 = 1 << 20;
+
+/// Identifiers of the packets
+pub(crate) mod packet_id {
+    /// Ask the number of devices
+    pub const REQUEST_CONTROLLER_COUNT: u32 = 0;
+    /// Ask the description of a device
+    pub const REQUEST_CONTROLLER_DATA: u32 = 1;
+    /// Exchange the versions of the protocol
+    pub const REQUEST_PROTOCOL_VERSION: u32 = 40;
+    /// Name the client
+    pub const SET_CLIENT_NAME: u32 = 50;
+    /// Ask the saved profiles
+    pub const REQUEST_PROFILE_LIST: u32 = 150;
+    /// Change the size of a resizable zone
+    pub const RESIZE_ZONE: u32 = 1000;
+    /// Set the colors of all the LEDs
+    pub const UPDATE_LEDS: u32 = 1050;
+    /// Set the colors of the LEDs of a zone
+    pub const UPDATE_ZONE_LEDS: u32 = 1051;
+    /// Set the color of one LED
+    pub const UPDATE_SINGLE_LED: u32 = 1052;
+    /// Switch to the mode with colors per LED
+    pub const SET_CUSTOM_MODE: u32 = 1100;
+    /// Switch to a mode
+    pub const UPDATE_MODE: u32 = 1101;
+    /// Switch to a mode and save it in the device
+    pub const SAVE_MODE: u32 = 1102;
+}
+
+/// Type of device of a keyboard
+const DEVICE_TYPE_KEYBOARD: i32 = 5;
+/// Type of zone laid out on a grid
+const ZONE_TYPE_MATRIX: i32 = 2;
+/// Flag of a mode where each LED has its own color
+const MODE_FLAG_HAS_PER_LED_COLOR: u32 = 1 << 5;
+/// Color mode of a mode without color
+const MODE_COLORS_NONE: u32 = 0;
+/// Color mode of a mode where each LED has its own color
+const MODE_COLORS_PER_LED: u32 = 1;
+/// Cell of the matrix without LED
+const NO_LED: u32 = u32::MAX;
+
+/// Modes of the device, in the order of their index
+pub(crate) const MODES: [ControlerFeatureKind; 2] =
+    [ControlerFeatureKind::Custom, ControlerFeatureKind::Rainbow];
+
+/// A packet of the protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Packet {
+    /// Index of the device concerned
+    pub device: u32,
+    /// Identifier of the packet, see [`packet_id`]
+    pub id: u32,
+    /// Content of the packet
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Create a packet
+    pub const fn new(device: u32, id: u32, data: Vec<u8>) -> Self {
+        Self { device, id, data }
+    }
+
+    /// Read a packet
+    /// # Errors
+    /// Returns the errors of the reader, and [`ErrorKind::InvalidData`] if the packet
+    /// does not start with the magic bytes or is too big.
+    pub fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let mut header = [0_u8; HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        let mut fields = Reader::new(&header[MAGIC.len()..]);
+        let (device, id, size) = match (fields.u32(), fields.u32(), fields.u32()) {
+            (Some(device), Some(id), Some(size)) if header[..MAGIC.len()] == MAGIC => {
+                (device, id, size)
+            }
+            _ => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "not an OpenRGB packet",
+                ))
+            }
+        };
+        if size > MAX_PACKET_SIZE {
+            return Err(io::Error::new(ErrorKind::InvalidData, "packet too big"));
+        }
+        let mut data = vec![0_u8; size as usize];
+        reader.read_exact(&mut data)?;
+        Ok(Self::new(device, id, data))
+    }
+
+    /// Encode the packet with its header
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer.bytes(&MAGIC);
+        writer.u32(self.device);
+        writer.u32(self.id);
+        writer.len_u32(self.data.len());
+        writer.bytes(&self.data);
+        writer.into_bytes()
+    }
+}
+
+/// Reads the little endian fields of a packet
+#[derive(Debug)]
+pub(crate) struct Reader<'a> {
+    /// Data not read yet
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Read `data` from the start
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Read `N` bytes
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.data.get(..N)?;
+        self.data = &self.data[N..];
+        <[u8; N]>::try_from(bytes).ok()
+    }
+
+    /// Read a u16
+    pub fn u16(&mut self) -> Option<u16> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    /// Read a u32
+    pub fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    /// Read an i32
+    pub fn i32(&mut self) -> Option<i32> {
+        self.take().map(i32::from_le_bytes)
+    }
+
+    /// Read a color, encoded as red, green, blue and a padding byte
+    pub fn color(&mut self) -> Option<ColorRgb> {
+        self.take::<4>().map(|[r, g, b, _]| ColorRgb::new(r, g, b))
+    }
+
+    /// Read a string prefixed by its length, with a null terminator
+    #[cfg(test)]
+    pub fn string(&mut self) -> Option<String> {
+        let len = usize::from(self.u16()?);
+        let bytes = self.data.get(..len)?;
+        self.data = &self.data[len..];
+        let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// Read a list of colors prefixed by their number as a u16
+    pub fn colors(&mut self) -> Option<Vec<ColorRgb>> {
+        let count = self.u16()?;
+        (0..count).map(|_| self.color()).collect()
+    }
+
+    /// Get the number of bytes not read
+    #[cfg(test)]
+    pub const fn remaining(&self) -> usize {
+        self.data.len()
+    }
+}
+
+/// Writes the little endian fields of a packet
+#[derive(Debug, Default)]
+pub(crate) struct Writer {
+    /// Data written
+    data: Vec<u8>,
+}
+
+impl Writer {
+    /// Create an empty writer
+    pub const fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Write bytes as they are
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Write a u16
+    pub fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    /// Write a u32
+    pub fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    /// Write an i32
+    pub fn i32(&mut self, value: i32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    /// Write a length as a u16, the lists of the protocol are small
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn len_u16(&mut self, len: usize) {
+        self.u16(len as u16);
+    }
+
+    /// Write a length as a u32
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn len_u32(&mut self, len: usize) {
+        self.u32(len as u32);
+    }
+
+    /// Write a color as red, green, blue and a padding byte
+    pub fn color(&mut self, color: ColorRgb) {
+        self.bytes(&[color.r(), color.g(), color.b(), 0]);
+    }
+
+    /// Write a string prefixed by its length, with a null terminator
+    pub fn string(&mut self, string: &str) {
+        self.len_u16(string.len() + 1);
+        self.bytes(string.as_bytes());
+        self.bytes(&[0]);
+    }
+
+    /// Get the data written
+    #[allow(clippy::missing_const_for_fn)] // the destructor of a Vec is not const
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Get the data written prefixed by its size, including the size itself
+    pub fn into_sized_bytes(self) -> Vec<u8> {
+        let mut writer = Self::new();
+        writer.len_u32(self.data.len() + 4);
+        writer.bytes(&self.data);
+        writer.data
+    }
+}
+
+/// A key presented as a LED of the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Led {
+    /// Name of the key
+    pub key_name: KeyName,
+    /// Index of the light in the [`ColorBuffer`]
+    pub light: usize,
+}
+
+/// Description of the keyboard as an OpenRGB device, with one matrix zone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Device {
+    /// The keys that have a light, in the order of the LEDs
+    pub leds: Vec<Led>,
+    /// Number of rows of the matrix
+    pub height: usize,
+    /// Number of columns of the matrix
+    pub width: usize,
+    /// Index of the LED of each cell of the matrix, row by row
+    pub matrix: Vec<u32>,
+}
+
+impl Device {
+    /// Derive the LEDs and the matrix from the keys of the layout and their position.
+    ///
+    /// When two keys fall in the same cell the first one of the layout is kept in the matrix.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn new(layout: &dyn Layout) -> Self {
+        let keys: Vec<_> = layout
+            .layout()
+            .iter()
+            .filter(|info| usize::from(info.key_code_light().code()) < NUMBER_KEY_LED_BUFFER)
+            .collect();
+        // the positions are positive and small
+        let cell = |value: f64| value.max(0_f64).round() as usize;
+        let cells: Vec<(usize, usize)> = keys
+            .iter()
+            .map(|info| (cell(info.key_pos().y()), cell(info.key_pos().x())))
+            .collect();
+        let height = cells.iter().map(|(row, _)| row + 1).max().unwrap_or(0);
+        let width = cells
+            .iter()
+            .map(|(_, column)| column + 1)
+            .max()
+            .unwrap_or(0);
+        let mut matrix = vec![NO_LED; height * width];
+        for (index, (row, column)) in cells.into_iter().enumerate() {
+            // the y axis of the layout goes up while the rows of the matrix go down
+            let cell = &mut matrix[(height - 1 - row) * width + column];
+            if *cell == NO_LED {
+                *cell = index as u32;
+            }
+        }
+        let leds = keys
+            .iter()
+            .map(|info| Led {
+                key_name: *info.key_name(),
+                light: usize::from(info.key_code_light().code()),
+            })
+            .collect();
+        Self {
+            leds,
+            height,
+            width,
+            matrix,
+        }
+    }
+
+    /// Encode the description of the device for the given version of the protocol
+    pub fn encode(
+        &self,
+        colors: &ColorBuffer<ColorRgb>,
+        mode: ControlerFeatureKind,
+        version: u32,
+    ) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer.i32(DEVICE_TYPE_KEYBOARD);
+        writer.string("Roccat Vulcan");
+        if version >= 1 {
+            writer.string("Roccat");
+        }
+        writer.string("Roccat Vulcan keyboard");
+        writer.string(env!("CARGO_PKG_VERSION"));
+        writer.string("");
+        writer.string("HID");
+
+        writer.len_u16(MODES.len());
+        let active = MODES.iter().position(|kind| *kind == mode).unwrap_or(0);
+        writer.len_u32(active);
+        for (value, kind) in MODES.iter().enumerate() {
+            let (name, flags, color_mode) = match kind {
+                ControlerFeatureKind::Custom => {
+                    ("Direct", MODE_FLAG_HAS_PER_LED_COLOR, MODE_COLORS_PER_LED)
+                }
+                ControlerFeatureKind::Rainbow => ("Rainbow", 0, MODE_COLORS_NONE),
+            };
+            writer.string(name);
+            writer.len_u32(value);
+            writer.u32(flags);
+            // speed min and max
+            writer.u32(0);
+            writer.u32(0);
+            if version >= 3 {
+                // brightness min and max
+                writer.u32(0);
+                writer.u32(0);
+            }
+            // colors min and max
+            writer.u32(0);
+            writer.u32(0);
+            // speed
+            writer.u32(0);
+            if version >= 3 {
+                // brightness
+                writer.u32(0);
+            }
+            // direction
+            writer.u32(0);
+            writer.u32(color_mode);
+            // no mode specific color
+            writer.u16(0);
+        }
+
+        writer.u16(1);
+        writer.string("Keyboard");
+        writer.i32(ZONE_TYPE_MATRIX);
+        for _ in 0..3_u8 {
+            // leds min, max and count
+            writer.len_u32(self.leds.len());
+        }
+        // size of the matrix with its height and width
+        writer.len_u16((self.matrix.len() + 2) * 4);
+        writer.len_u32(self.height);
+        writer.len_u32(self.width);
+        for cell in &self.matrix {
+            writer.u32(*cell);
+        }
+
+        writer.len_u16(self.leds.len());
+        for led in &self.leds {
+            writer.string(&format!("Key: {}", led.key_name));
+            writer.len_u32(led.light);
+        }
+        writer.len_u16(self.leds.len());
+        for led in &self.leds {
+            writer.color(colors[led.light]);
+        }
+        writer.into_sized_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::LayoutFrCh;
+
+    /// Fields of a description read back
+    struct Decoded {
+        device_type: i32,
+        name: String,
+        vendor: Option<String>,
+        modes: Vec<String>,
+        active_mode: i32,
+        height: u32,
+        width: u32,
+        matrix: Vec<u32>,
+        leds: Vec<(String, u32)>,
+        colors: Vec<ColorRgb>,
+    }
+
+    /// Read a description the way an OpenRGB client does
+    fn decode(data: &[u8], version: u32) -> Decoded {
+        let mut reader = Reader::new(data);
+        assert_eq!(reader.u32().unwrap() as usize, data.len());
+        let device_type = reader.i32().unwrap();
+        let name = reader.string().unwrap();
+        let vendor = (version >= 1).then(|| reader.string().unwrap());
+        for _ in 0..4_u8 {
+            reader.string().unwrap();
+        }
+        let mode_count = reader.u16().unwrap();
+        let active_mode = reader.i32().unwrap();
+        let mut modes = Vec::new();
+        for _ in 0..mode_count {
+            modes.push(reader.string().unwrap());
+            let fields = if version >= 3 { 12_u8 } else { 9_u8 };
+            for _ in 0..fields {
+                reader.u32().unwrap();
+            }
+            assert!(reader.colors().unwrap().is_empty());
+        }
+        assert_eq!(reader.u16(), Some(1));
+        assert_eq!(reader.string().unwrap(), "Keyboard");
+        assert_eq!(reader.i32(), Some(ZONE_TYPE_MATRIX));
+        let led_count = reader.u32().unwrap();
+        assert_eq!(reader.u32(), Some(led_count));
+        assert_eq!(reader.u32(), Some(led_count));
+        let matrix_size = reader.u16().unwrap();
+        let height = reader.u32().unwrap();
+        let width = reader.u32().unwrap();
+        assert_eq!(u32::from(matrix_size), (height * width + 2) * 4);
+        let matrix = (0..height * width).map(|_| reader.u32().unwrap()).collect();
+        let leds = (0..reader.u16().unwrap())
+            .map(|_| (reader.string().unwrap(), reader.u32().unwrap()))
+            .collect();
+        let colors = reader.colors().unwrap();
+        assert_eq!(reader.remaining(), 0);
+        Decoded {
+            device_type,
+            name,
+            vendor,
+            modes,
+            active_mode,
+            height,
+            width,
+            matrix,
+            leds,
+            colors,
+        }
+    }
+
+    #[test]
+    fn packet() {
+        let packet = Packet::new(0, packet_id::UPDATE_SINGLE_LED, vec![1, 2, 3]);
+        let bytes = packet.to_bytes();
+        assert_eq!(&bytes[..4], b"ORGB");
+        assert_eq!(bytes.len(), HEADER_SIZE + 3);
+        assert_eq!(Packet::read(&mut bytes.as_slice()).unwrap(), packet);
+
+        let mut wrong = bytes.clone();
+        wrong[0] = b'X';
+        assert_eq!(
+            Packet::read(&mut wrong.as_slice()).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(
+            Packet::read(&mut &bytes[..HEADER_SIZE + 1])
+                .unwrap_err()
+                .kind(),
+            ErrorKind::UnexpectedEof
+        );
+        let mut huge = Packet::new(0, 0, Vec::new()).to_bytes();
+        huge[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            Packet::read(&mut huge.as_slice()).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let mut writer = Writer::new();
+        writer.string("abc");
+        writer.color(ColorRgb::new(1, 2, 3));
+        writer.i32(-2);
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes[..10], [4, 0, b'a', b'b', b'c', 0, 1, 2, 3, 0]);
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.string().unwrap(), "abc");
+        assert_eq!(reader.color(), Some(ColorRgb::new(1, 2, 3)));
+        assert_eq!(reader.i32(), Some(-2_i32));
+        assert_eq!(reader.u16(), None);
+    }
+
+    #[test]
+    fn description() {
+        let layout = LayoutFrCh::new();
+        let device = Device::new(&layout);
+        let mut colors = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+        *colors.get_key_mut(&layout, KeyName::Escape).unwrap() = ColorRgb::new(255, 0, 0);
+
+        for version in 0..=PROTOCOL_VERSION {
+            let decoded = decode(
+                &device.encode(&colors, ControlerFeatureKind::Rainbow, version),
+                version,
+            );
+            assert_eq!(decoded.device_type, DEVICE_TYPE_KEYBOARD);
+            assert_eq!(decoded.name, "Roccat Vulcan");
+            assert_eq!(decoded.vendor.is_some(), version >= 1);
+            assert_eq!(decoded.modes, vec!["Direct", "Rainbow"]);
+            assert_eq!(decoded.active_mode, 1_i32);
+            assert_eq!(decoded.leds.len(), device.leds.len());
+            assert_eq!(decoded.colors.len(), device.leds.len());
+        }
+
+        let decoded = decode(
+            &device.encode(&colors, ControlerFeatureKind::Custom, PROTOCOL_VERSION),
+            PROTOCOL_VERSION,
+        );
+        assert_eq!(decoded.active_mode, 0_i32);
+        let escape = decoded
+            .leds
+            .iter()
+            .position(|(name, _)| name == "Key: Escape")
+            .unwrap();
+        assert_eq!(decoded.leds[escape].1, 0);
+        assert_eq!(decoded.colors[escape], ColorRgb::new(255, 0, 0));
+        // Escape is in the top left corner
+        assert_eq!(decoded.matrix[0], u32::try_from(escape).unwrap());
+        assert_eq!(decoded.height, 6);
+        assert!(decoded.width > 20);
+        // every LED is in the matrix except when two keys share a cell
+        let in_matrix = decoded
+            .matrix
+            .iter()
+            .filter(|cell| **cell != NO_LED)
+            .count();
+        assert!(in_matrix > decoded.leds.len() - 5);
+        assert!(!decoded.leds.iter().any(|(name, _)| name.contains("wheel")));
+    }
+}
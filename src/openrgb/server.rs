@@ -0,0 +1,523 @@
+//! Contains [`OpenRgbServer`]
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::io::{BufReader, ErrorKind, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use super::protocol::{packet_id, Device, Packet, Reader, Writer, MODES, PROTOCOL_VERSION};
+use crate::{
+    color::{ColorBuffer, ColorRgb},
+    error::ErrorRoccatVulcanApi,
+    keyboard::{ControlerFeatureKind, Keyboard},
+    layout::{KeyPress, Layout},
+};
+
+/// Identifier of a client of the [`OpenRgbServer`]
+type ClientId = u64;
+
+/// Maximum time spent writing to a client before considering it dead
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Event received by the main loop of [`OpenRgbServer::serve`]
+enum SocketEvent {
+    /// A packet sent by a client
+    Packet(ClientId, Packet),
+    /// The client closed the connection or sent an invalid packet
+    Closed(ClientId),
+}
+
+/// A client connected to the [`OpenRgbServer`]
+struct Client {
+    /// Name given by the client
+    name: String,
+    /// Version of the protocol used by the client
+    version: u32,
+    /// Connection to the client
+    stream: TcpStream,
+}
+
+/// Server of the OpenRGB SDK protocol presenting the keyboard as one device.
+///
+/// The device has a matrix zone whose LEDs are the keys of the [`Layout`] that have a light,
+/// named after their [`crate::KeyName`]. It has two modes, `Direct` where the colors sent by
+/// the clients are rendered, and `Rainbow` the default mode of the keyboard.
+/// # Example
+/// ```
+/// use std::net::TcpListener;
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ErrorRoccatVulcanApi, KeyName, KeyboardApi, Layout, LayoutKind, OpenRgbServer,
+///     OPENRGB_DEFAULT_PORT,
+/// };
+///
+/// # fn main() -> Result<(), ErrorRoccatVulcanApi> {
+/// # #[cfg(not(feature = "no-keyboard-test"))]
+/// # {
+/// let layout = LayoutKind::default().layout();
+/// let mut server = OpenRgbServer::new(KeyboardApi::new()?, layout);
+/// let listener = TcpListener::bind(("127.0.0.1", OPENRGB_DEFAULT_PORT))?;
+/// server.serve(&listener, Duration::from_millis(20), |key_press| {
+///     layout
+///         .find_from_key_code(*key_press.key_code())
+///         .map(|info| *info.key_name())
+///         == Some(KeyName::Escape)
+/// })?;
+/// # }
+/// # Ok(())
+/// # }
+/// ```
+pub struct OpenRgbServer<K: Keyboard> {
+    /// The keyboard
+    keyboard: K,
+    /// The keyboard as seen by the clients
+    device: Device,
+    /// Colors set by the clients
+    colors: ColorBuffer<ColorRgb>,
+    /// Mode set by the clients
+    mode: ControlerFeatureKind,
+    /// Whether the colors need to be rendered again
+    changed: bool,
+    /// Connected clients
+    clients: BTreeMap<ClientId, Client>,
+    /// Identifier given to the next client
+    next_id: ClientId,
+}
+
+impl<K: Keyboard> OpenRgbServer<K> {
+    /// Create a server presenting the keys of `layout`, all black
+    pub fn new(keyboard: K, layout: &dyn Layout) -> Self {
+        Self {
+            keyboard,
+            device: Device::new(layout),
+            colors: ColorBuffer::from_element(ColorRgb::new(0, 0, 0)),
+            mode: ControlerFeatureKind::Custom,
+            changed: true,
+            clients: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Get the keyboard
+    pub const fn keyboard(&self) -> &K {
+        &self.keyboard
+    }
+
+    /// Get the colors set by the clients
+    pub const fn colors(&self) -> &ColorBuffer<ColorRgb> {
+        &self.colors
+    }
+
+    /// Get the number of LEDs of the device, the keys that have a light
+    pub const fn led_count(&self) -> usize {
+        self.device.leds.len()
+    }
+
+    /// Get the number of clients connected
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Serve the clients connecting to `listener` until `stop` returns true for a key press.
+    ///
+    /// The colors are rendered when they change and the key presses are read during
+    /// `frame_duration` between each frame.
+    /// # Errors
+    /// - [`ErrorRoccatVulcanApi::Io`] if the listener failed
+    /// - the errors of [`Keyboard::render`], [`Keyboard::read_key_press`]
+    ///   and [`Keyboard::set_mode`]
+    pub fn serve(
+        &mut self,
+        listener: &TcpListener,
+        frame_duration: Duration,
+        stop: impl FnMut(KeyPress) -> bool,
+    ) -> Result<(), ErrorRoccatVulcanApi> {
+        listener.set_nonblocking(true)?;
+        let result = self.serve_loop(listener, frame_duration, stop);
+        for (_, client) in std::mem::take(&mut self.clients) {
+            // wake up the reading threads
+            let _ = client.stream.shutdown(Shutdown::Both);
+        }
+        result
+    }
+
+    /// Main loop of [`Self::serve`]
+    fn serve_loop(
+        &mut self,
+        listener: &TcpListener,
+        frame_duration: Duration,
+        mut stop: impl FnMut(KeyPress) -> bool,
+    ) -> Result<(), ErrorRoccatVulcanApi> {
+        let (sender, receiver) = mpsc::channel();
+        loop {
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => self.accept(stream, &sender)?,
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            loop {
+                match receiver.try_recv() {
+                    Ok(SocketEvent::Packet(id, packet)) => self.handle(id, &packet)?,
+                    Ok(SocketEvent::Closed(id)) => self.disconnect(id),
+                    Err(TryRecvError::Empty | TryRecvError::Disconnected) => break,
+                }
+            }
+            if self.changed && self.mode == ControlerFeatureKind::Custom {
+                self.keyboard.render(&self.colors)?;
+                self.changed = false;
+            }
+            if self
+                .keyboard
+                .read_key_press(frame_duration)?
+                .into_iter()
+                .any(&mut stop)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Add a client and start reading its packets
+    fn accept(
+        &mut self,
+        stream: TcpStream,
+        sender: &Sender<SocketEvent>,
+    ) -> Result<(), ErrorRoccatVulcanApi> {
+        let setup = stream
+            .set_nonblocking(false)
+            .and_then(|()| stream.set_nodelay(true))
+            .and_then(|()| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+            .and_then(|()| stream.try_clone());
+        let reader = match setup {
+            Ok(reader) => reader,
+            // the client is already gone
+            Err(_) => return Ok(()),
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        let sender = sender.clone();
+        thread::Builder::new()
+            .name(format!("openrgb client {}", id))
+            .spawn(move || {
+                let mut reader = BufReader::new(reader);
+                while let Ok(packet) = Packet::read(&mut reader) {
+                    if sender.send(SocketEvent::Packet(id, packet)).is_err() {
+                        return;
+                    }
+                }
+                let _ = sender.send(SocketEvent::Closed(id));
+            })?;
+        self.clients.insert(
+            id,
+            Client {
+                name: format!("client {}", id),
+                version: 0,
+                stream,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove a client
+    fn disconnect(&mut self, id: ClientId) {
+        if let Some(client) = self.clients.remove(&id) {
+            let _ = client.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Apply a packet of a client and send the reply.
+    ///
+    /// The packets that are not understood are ignored, like the OpenRGB server does.
+    fn handle(&mut self, id: ClientId, packet: &Packet) -> Result<(), ErrorRoccatVulcanApi> {
+        let mut reader = Reader::new(&packet.data);
+        let reply = match packet.id {
+            packet_id::REQUEST_CONTROLLER_COUNT => {
+                let mut writer = Writer::new();
+                writer.u32(1);
+                Some(writer.into_bytes())
+            }
+            packet_id::REQUEST_CONTROLLER_DATA if packet.device == 0 => {
+                // the clients older than the version 1 do not send their version
+                let version = reader.u32().unwrap_or(0).min(PROTOCOL_VERSION);
+                Some(self.device.encode(&self.colors, self.mode, version))
+            }
+            packet_id::REQUEST_PROTOCOL_VERSION => {
+                let version = reader.u32().unwrap_or(0).min(PROTOCOL_VERSION);
+                if let Some(client) = self.clients.get_mut(&id) {
+                    client.version = version;
+                }
+                let mut writer = Writer::new();
+                writer.u32(PROTOCOL_VERSION);
+                Some(writer.into_bytes())
+            }
+            packet_id::SET_CLIENT_NAME => {
+                if let Some(client) = self.clients.get_mut(&id) {
+                    let name = packet.data.split(|byte| *byte == 0).next().unwrap_or(&[]);
+                    client.name = String::from_utf8_lossy(name).into_owned();
+                }
+                None
+            }
+            packet_id::REQUEST_PROFILE_LIST => {
+                // no profile
+                let mut writer = Writer::new();
+                writer.u16(0);
+                Some(writer.into_sized_bytes())
+            }
+            _ if packet.device == 0 => {
+                self.apply(packet.id, &mut reader)?;
+                None
+            }
+            _ => None,
+        };
+        if let Some(data) = reply {
+            let bytes = Packet::new(packet.device, packet.id, data).to_bytes();
+            let sent = self.clients.get_mut(&id).map(|client| {
+                client
+                    .stream
+                    .write_all(&bytes)
+                    .and_then(|()| client.stream.flush())
+            });
+            if let Some(Err(_)) = sent {
+                self.disconnect(id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply a packet changing the device
+    /// # Errors
+    /// Returns the errors of [`Keyboard::set_mode`].
+    fn apply(&mut self, id: u32, reader: &mut Reader<'_>) -> Result<(), ErrorRoccatVulcanApi> {
+        match id {
+            packet_id::UPDATE_LEDS => {
+                if let Some(colors) = reader.u32().and_then(|_| reader.colors()) {
+                    self.set_colors(0, &colors);
+                }
+            }
+            packet_id::UPDATE_ZONE_LEDS => {
+                let zone = reader.u32().and_then(|_| reader.u32());
+                if let (Some(0), Some(colors)) = (zone, reader.colors()) {
+                    self.set_colors(0, &colors);
+                }
+            }
+            packet_id::UPDATE_SINGLE_LED => {
+                if let (Some(led), Some(color)) = (reader.i32(), reader.color()) {
+                    if let Ok(led) = usize::try_from(led) {
+                        self.set_colors(led, &[color]);
+                    }
+                }
+            }
+            packet_id::SET_CUSTOM_MODE => self.set_mode(ControlerFeatureKind::Custom)?,
+            packet_id::UPDATE_MODE | packet_id::SAVE_MODE => {
+                let mode = reader
+                    .u32()
+                    .and_then(|_| reader.i32())
+                    .and_then(|index| usize::try_from(index).ok())
+                    .and_then(|index| MODES.get(index));
+                if let Some(mode) = mode {
+                    self.set_mode(*mode)?;
+                }
+            }
+            // the zone has a fixed size
+            packet_id::RESIZE_ZONE => {}
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Set the colors of the LEDs starting at `first`, ignoring the LEDs that do not exist
+    fn set_colors(&mut self, first: usize, colors: &[ColorRgb]) {
+        for (led, color) in self.device.leds.iter().skip(first).zip(colors) {
+            self.colors[led.light] = *color;
+        }
+        self.changed = true;
+    }
+
+    /// Switch the mode of the keyboard if it changed
+    fn set_mode(&mut self, mode: ControlerFeatureKind) -> Result<(), ErrorRoccatVulcanApi> {
+        if mode != self.mode {
+            self.keyboard.set_mode(mode)?;
+            self.mode = mode;
+            self.changed = true;
+        }
+        Ok(())
+    }
+}
+
+impl<K: Keyboard> Debug for OpenRgbServer<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let clients: Vec<(&ClientId, &str, u32)> = self
+            .clients
+            .iter()
+            .map(|(id, client)| (id, client.name.as_str(), client.version))
+            .collect();
+        f.debug_struct("OpenRgbServer")
+            .field("led_count", &self.led_count())
+            .field("clients", &clients)
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use super::*;
+    use crate::{
+        keyboard::KeyboardApi,
+        layout::{KeyCode, KeyName, LayoutFrCh},
+        transport::MockTransport,
+    };
+
+    /// Client of the protocol
+    struct TestClient(TcpStream);
+
+    impl TestClient {
+        fn send(&mut self, id: u32, data: &[u8]) {
+            let packet = Packet::new(0, id, data.to_vec());
+            self.0.write_all(&packet.to_bytes()).unwrap();
+        }
+
+        fn request(&mut self, id: u32, data: &[u8]) -> Vec<u8> {
+            self.send(id, data);
+            let reply = Packet::read(&mut self.0).unwrap();
+            assert_eq!(reply.id, id);
+            reply.data
+        }
+    }
+
+    fn colors_data(colors: &[ColorRgb]) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer.len_u16(colors.len());
+        for color in colors {
+            writer.color(*color);
+        }
+        writer.into_sized_bytes()
+    }
+
+    #[test]
+    fn protocol_client() {
+        let layout = LayoutFrCh::new();
+        let transport = MockTransport::new();
+        let keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let layout = LayoutFrCh::new();
+            let mut server = OpenRgbServer::new(keyboard, &layout);
+            let result = server.serve(&listener, Duration::from_millis(5), |key_press| {
+                layout
+                    .find_from_key_code(*key_press.key_code())
+                    .map(|info| *info.key_name())
+                    == Some(KeyName::Escape)
+            });
+            (result, server.led_count())
+        });
+
+        let mut client = TestClient(TcpStream::connect(address).unwrap());
+        client.send(packet_id::SET_CLIENT_NAME, b"test\0");
+        assert_eq!(
+            client.request(packet_id::REQUEST_PROTOCOL_VERSION, &5_u32.to_le_bytes()),
+            PROTOCOL_VERSION.to_le_bytes()
+        );
+        assert_eq!(
+            client.request(packet_id::REQUEST_CONTROLLER_COUNT, &[]),
+            1_u32.to_le_bytes()
+        );
+        let data = client.request(
+            packet_id::REQUEST_CONTROLLER_DATA,
+            &PROTOCOL_VERSION.to_le_bytes(),
+        );
+        let device = Device::new(&layout);
+        assert_eq!(
+            data,
+            device.encode(
+                &ColorBuffer::from_element(ColorRgb::new(0, 0, 0)),
+                ControlerFeatureKind::Custom,
+                PROTOCOL_VERSION
+            )
+        );
+        assert_eq!(
+            client.request(packet_id::REQUEST_PROFILE_LIST, &[]),
+            [6, 0, 0, 0, 0, 0]
+        );
+
+        let escape_color = || {
+            transport
+                .last_frame()
+                .and_then(|frame| frame.get_key(&layout, KeyName::Escape).copied())
+        };
+        let wait_for = |color| {
+            for _ in 0_u32..400 {
+                if escape_color() == Some(color) {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+            panic!("expected {:?}, found {:?}", color, escape_color());
+        };
+        let red = ColorRgb::new(255, 0, 0);
+        client.send(packet_id::SET_CUSTOM_MODE, &[]);
+        client.send(
+            packet_id::UPDATE_LEDS,
+            &colors_data(&vec![red; device.leds.len()]),
+        );
+        wait_for(red);
+        let escape = device
+            .leds
+            .iter()
+            .position(|led| led.key_name == KeyName::Escape)
+            .unwrap();
+        let mut data = Vec::new();
+        data.extend_from_slice(&i32::try_from(escape).unwrap().to_le_bytes());
+        data.extend_from_slice(&[0, 0, 255, 0]);
+        client.send(packet_id::UPDATE_SINGLE_LED, &data);
+        wait_for(ColorRgb::new(0, 0, 255));
+        let mut data = vec![0, 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(&colors_data(&[ColorRgb::new(0, 255, 0)])[4..]);
+        client.send(packet_id::UPDATE_ZONE_LEDS, &data);
+        // a packet that is not understood is ignored
+        client.send(packet_id::UPDATE_LEDS, &[1, 2]);
+        assert_eq!(
+            client.request(packet_id::REQUEST_CONTROLLER_COUNT, &[]),
+            1_u32.to_le_bytes()
+        );
+        // the zone starts with the first LED, which is not Escape
+        thread::sleep(Duration::from_millis(50));
+        let frame = transport.last_frame().unwrap();
+        assert_eq!(
+            frame.get_key(&layout, device.leds[0].key_name),
+            Some(&ColorRgb::new(0, 255, 0))
+        );
+        assert_eq!(escape_color(), Some(ColorRgb::new(0, 0, 255)));
+
+        let mut data = vec![0, 0, 0, 0];
+        data.extend_from_slice(&1_i32.to_le_bytes());
+        client.send(packet_id::UPDATE_MODE, &data);
+        // the packets of a client are handled in order
+        client.request(packet_id::REQUEST_CONTROLLER_COUNT, &[]);
+        assert_eq!(transport.mode(), Some(ControlerFeatureKind::Rainbow));
+
+        // a client sending garbage is disconnected
+        let mut garbage = TcpStream::connect(address).unwrap();
+        garbage.write_all(&[0; 16]).unwrap();
+        let mut buffer = [0; 1];
+        garbage
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        assert_eq!(garbage.read(&mut buffer).unwrap(), 0);
+
+        transport.push_key_press(KeyPress::new(KeyCode::new(251, 17), true));
+        let (result, led_count) = server.join().unwrap();
+        result.unwrap();
+        assert_eq!(led_count, device.leds.len());
+    }
+}
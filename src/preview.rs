@@ -0,0 +1,12 @@
+//! Previews of a [`crate::ColorBuffer`] without keyboard.
+
+#[cfg(feature = "image-export")]
+mod error;
+#[cfg(feature = "image-export")]
+pub use error::*;
+#[cfg(feature = "image-export")]
+mod image;
+#[cfg(feature = "image-export")]
+pub use image::*;
+mod terminal;
+pub use terminal::*;
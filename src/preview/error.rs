@@ -0,0 +1,61 @@
+//! Contains [`ImageExportError`]
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Error returned when exporting frames to an image with [`super::ImagePreview`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ImageExportError {
+    /// Error while writing the image
+    Io(std::io::Error),
+    /// Error of the PNG encoder
+    Png(png::EncodingError),
+    /// Error of the GIF encoder
+    Gif(gif::EncodingError),
+    /// An animation needs at least one frame
+    NoFrame,
+    /// The image or the animation is too large for the format
+    TooLarge,
+}
+
+impl Display for ImageExportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "io error : {}", error),
+            Self::Png(error) => write!(f, "png error : {}", error),
+            Self::Gif(error) => write!(f, "gif error : {}", error),
+            Self::NoFrame => write!(f, "the animation has no frame"),
+            Self::TooLarge => write!(f, "the image is too large for the format"),
+        }
+    }
+}
+
+impl Error for ImageExportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Png(error) => Some(error),
+            Self::Gif(error) => Some(error),
+            Self::NoFrame | Self::TooLarge => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ImageExportError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<png::EncodingError> for ImageExportError {
+    fn from(error: png::EncodingError) -> Self {
+        Self::Png(error)
+    }
+}
+
+impl From<gif::EncodingError> for ImageExportError {
+    fn from(error: gif::EncodingError) -> Self {
+        Self::Gif(error)
+    }
+}
@@ -0,0 +1,603 @@
+//! Contains [`ImagePreview`] and [`RgbImage`]
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::io::Write;
+use std::time::Duration;
+
+use super::ImageExportError;
+use crate::{
+    color::{ColorBuffer, ColorRgb, NUMBER_KEY_LED_BUFFER},
+    layout::Layout,
+};
+
+/// Default number of pixels per key, including the gap between keys
+const DEFAULT_KEY_SIZE: u32 = 24;
+/// Default number of pixels between keys
+const DEFAULT_GAP: u32 = 2;
+/// Default color drawn around the keys
+const DEFAULT_BACKGROUND: ColorRgb = ColorRgb::new(32, 32, 32);
+
+/// Image with 8 bits RGB pixels, stored row by row from the top left corner
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RgbImage {
+    /// Width in pixels
+    width: u32,
+    /// Height in pixels
+    height: u32,
+    /// Three bytes per pixel
+    pixels: Vec<u8>,
+}
+
+impl RgbImage {
+    /// Get the width in pixels
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height in pixels
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the color of a pixel, [`None`] outside of the image
+    pub fn pixel(&self, x: u32, y: u32) -> Option<ColorRgb> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = usize::try_from(y * self.width + x).ok()? * 3;
+        Some(ColorRgb::new(
+            self.pixels[index],
+            self.pixels[index + 1],
+            self.pixels[index + 2],
+        ))
+    }
+
+    /// Get the pixels, three bytes per pixel row by row
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+/// A key placed in the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KeyRect {
+    /// Left side in pixels
+    x: u32,
+    /// Top side in pixels
+    y: u32,
+    /// Index of the light in the [`ColorBuffer`]
+    light: usize,
+}
+
+/// Draws a [`ColorBuffer`] as an image of the keyboard and records animations
+/// (requires the feature `image-export`).
+///
+/// Each key of the [`Layout`] that has a light is a square in its color placed according
+/// to its position. The output only depends on the colors, so the files can be compared
+/// byte by byte in tests.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorBuffer, ColorRgb, ImagePreview, LayoutKind, ProfileRenderer, Wave,
+/// };
+///
+/// let layout = LayoutKind::FrCh.layout();
+/// let preview = ImagePreview::new(layout);
+/// let mut png = Vec::new();
+/// preview.write_png(
+///     &mut png,
+///     &ColorBuffer::from_element(ColorRgb::new(255, 128, 0)),
+/// )?;
+///
+/// let mut renderer =
+///     ProfileRenderer::new(layout, ColorBuffer::from_element(ColorRgb::new(0, 0, 0)));
+/// renderer.push_effect(Wave::default(), None);
+/// let frame_duration = Duration::from_millis(100);
+/// let frames: Vec<_> = (0..20_u32)
+///     .map(|i| renderer.frame(frame_duration * i))
+///     .collect();
+/// let mut gif = Vec::new();
+/// preview.write_gif(&mut gif, &frames, frame_duration)?;
+/// # Ok::<(), roccat_vulcan_api_rs::ImageExportError>(())
+/// ```
+#[derive(Clone)]
+pub struct ImagePreview {
+    /// Layout drawn
+    layout: &'static dyn Layout,
+    /// Number of pixels per key, including the gap between keys
+    key_size: u32,
+    /// Number of pixels between keys
+    gap: u32,
+    /// Color drawn around the keys
+    background: ColorRgb,
+    /// Keys sorted from the top left corner
+    keys: Vec<KeyRect>,
+    /// Width of the image in pixels
+    width: u32,
+    /// Height of the image in pixels
+    height: u32,
+}
+
+impl ImagePreview {
+    /// Create a preview of the keys of `layout`
+    pub fn new(layout: &'static dyn Layout) -> Self {
+        Self::with_size_layout(layout, DEFAULT_KEY_SIZE, DEFAULT_GAP, DEFAULT_BACKGROUND)
+    }
+
+    /// Set the number of pixels per key including the gap, and the gap between keys.
+    ///
+    /// The key size is at least 1 and the gap is smaller than the key size.
+    pub fn with_key_size(self, key_size: u32, gap: u32) -> Self {
+        Self::with_size_layout(self.layout, key_size, gap, self.background)
+    }
+
+    /// Set the color drawn around the keys
+    pub const fn with_background(mut self, background: ColorRgb) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Place the keys of `layout`
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::cast_precision_loss)]
+    fn with_size_layout(
+        layout: &'static dyn Layout,
+        key_size: u32,
+        gap: u32,
+        background: ColorRgb,
+    ) -> Self {
+        let key_size = key_size.max(1);
+        let gap = gap.min(key_size - 1);
+        // the positions are positive and small
+        let to_pixels = |value: f64| (value.max(0_f64) * f64::from(key_size)).round() as u32;
+        let keys: Vec<_> = layout
+            .layout()
+            .iter()
+            .filter(|info| usize::from(info.key_code_light().code()) < NUMBER_KEY_LED_BUFFER)
+            .collect();
+        let rows = keys
+            .iter()
+            .map(|info| info.key_pos().y().max(0_f64).round() as u32 + 1)
+            .max()
+            .unwrap_or(0);
+        let mut placed: Vec<KeyRect> = keys
+            .iter()
+            .map(|info| KeyRect {
+                x: gap + to_pixels(info.key_pos().x()),
+                // the y axis of the layout goes up
+                y: gap + (rows - 1 - info.key_pos().y().max(0_f64).round() as u32) * key_size,
+                light: usize::from(info.key_code_light().code()),
+            })
+            .collect();
+        placed.sort_by_key(|key| (key.y, key.x));
+        let width = placed
+            .iter()
+            .map(|key| key.x - gap + key_size)
+            .max()
+            .unwrap_or(0)
+            + gap;
+        Self {
+            layout,
+            key_size,
+            gap,
+            background,
+            keys: placed,
+            width,
+            height: rows * key_size + gap,
+        }
+    }
+
+    /// Get the layout drawn
+    pub fn layout(&self) -> &'static dyn Layout {
+        self.layout
+    }
+
+    /// Get the number of pixels per key, including the gap between keys
+    pub const fn key_size(&self) -> u32 {
+        self.key_size
+    }
+
+    /// Get the number of pixels between keys
+    pub const fn gap(&self) -> u32 {
+        self.gap
+    }
+
+    /// Get the color drawn around the keys
+    pub const fn background(&self) -> ColorRgb {
+        self.background
+    }
+
+    /// Get the width of the images in pixels
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the images in pixels
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Draw one value per pixel, `key` gives the value of a key from the index of its light
+    #[allow(clippy::cast_possible_truncation)]
+    fn paint<T: Copy>(&self, background: T, key: impl Fn(usize) -> T) -> Vec<T> {
+        // the images are small, u32 fits in usize on the supported platforms
+        let width = self.width as usize;
+        let mut pixels = vec![background; width * self.height as usize];
+        let side = (self.key_size - self.gap) as usize;
+        for rect in &self.keys {
+            let value = key(rect.light);
+            for y in rect.y as usize..rect.y as usize + side {
+                let start = y * width + rect.x as usize;
+                pixels[start..start + side].fill(value);
+            }
+        }
+        pixels
+    }
+
+    /// Draw the buffer
+    pub fn render(&self, buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>) -> RgbImage {
+        let pixels = self
+            .paint(self.background, |light| buffer[light].into())
+            .iter()
+            .flat_map(|color| [color.r(), color.g(), color.b()])
+            .collect();
+        RgbImage {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+
+    /// Create a PNG encoder with the size and the settings of the preview
+    fn png_encoder<W: Write>(&self, out: W) -> png::Encoder<'static, W> {
+        let mut encoder = png::Encoder::new(out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(png::Compression::Default);
+        encoder.set_filter(png::FilterType::NoFilter);
+        encoder.set_adaptive_filter(png::AdaptiveFilterType::NonAdaptive);
+        encoder
+    }
+
+    /// Write the buffer to `out` as a PNG image
+    /// # Errors
+    /// Returns the errors of the encoder and of `out`.
+    pub fn write_png(
+        &self,
+        out: impl Write,
+        buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>,
+    ) -> Result<(), ImageExportError> {
+        let mut writer = self.png_encoder(out).write_header()?;
+        writer.write_image_data(self.render(buffer).as_bytes())?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Write the frames to `out` as an animated PNG looping forever,
+    /// showing each frame during `frame_duration`
+    /// # Errors
+    /// - [`ImageExportError::NoFrame`] if `frames` is empty
+    /// - [`ImageExportError::TooLarge`] if `frame_duration` is longer than about a minute
+    /// - otherwise the errors of the encoder and of `out`
+    pub fn write_apng(
+        &self,
+        out: impl Write,
+        frames: &[ColorBuffer<impl Into<ColorRgb> + Copy>],
+        frame_duration: Duration,
+    ) -> Result<(), ImageExportError> {
+        if frames.is_empty() {
+            return Err(ImageExportError::NoFrame);
+        }
+        let frame_count = u32::try_from(frames.len()).map_err(|_| ImageExportError::TooLarge)?;
+        let delay =
+            u16::try_from(frame_duration.as_millis()).map_err(|_| ImageExportError::TooLarge)?;
+        let mut encoder = self.png_encoder(out);
+        encoder.set_animated(frame_count, 0)?;
+        encoder.set_frame_delay(delay, 1000)?;
+        let mut writer = encoder.write_header()?;
+        for frame in frames {
+            writer.write_image_data(self.render(frame).as_bytes())?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Write the frames to `out` as an animated GIF looping forever,
+    /// showing each frame during `frame_duration` rounded to the hundredth of second.
+    ///
+    /// The keyboard has fewer lights than the colors of a GIF palette, so the colors are exact.
+    /// # Errors
+    /// - [`ImageExportError::NoFrame`] if `frames` is empty
+    /// - [`ImageExportError::TooLarge`] if the image is wider or higher than 65535 pixels
+    ///   or if `frame_duration` is longer than about ten minutes
+    /// - otherwise the errors of the encoder and of `out`
+    pub fn write_gif(
+        &self,
+        out: impl Write,
+        frames: &[ColorBuffer<impl Into<ColorRgb> + Copy>],
+        frame_duration: Duration,
+    ) -> Result<(), ImageExportError> {
+        if frames.is_empty() {
+            return Err(ImageExportError::NoFrame);
+        }
+        let width = u16::try_from(self.width).map_err(|_| ImageExportError::TooLarge)?;
+        let height = u16::try_from(self.height).map_err(|_| ImageExportError::TooLarge)?;
+        let delay = u16::try_from((frame_duration.as_millis() + 5) / 10)
+            .map_err(|_| ImageExportError::TooLarge)?;
+        let mut encoder = gif::Encoder::new(out, width, height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for frame in frames {
+            let (palette, indices) = self.palette(frame);
+            encoder.write_frame(&gif::Frame {
+                width,
+                height,
+                delay,
+                palette: Some(palette),
+                buffer: Cow::Owned(self.paint(0, |light| indices[light])),
+                ..gif::Frame::default()
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Get the palette of a frame, starting with the background, and the index
+    /// in the palette of each light
+    #[allow(clippy::cast_possible_truncation)]
+    fn palette(&self, buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>) -> (Vec<u8>, Vec<u8>) {
+        let mut colors = vec![self.background];
+        let mut indices = vec![0_u8; NUMBER_KEY_LED_BUFFER];
+        for key in &self.keys {
+            let color = buffer[key.light].into();
+            let index = colors
+                .iter()
+                .position(|known| *known == color)
+                .unwrap_or_else(|| {
+                    colors.push(color);
+                    colors.len() - 1
+                });
+            // there are at most NUMBER_KEY_LED_BUFFER + 1 colors
+            indices[key.light] = index as u8;
+        }
+        let palette = colors
+            .iter()
+            .flat_map(|color| [color.r(), color.g(), color.b()])
+            .collect();
+        (palette, indices)
+    }
+}
+
+impl Debug for ImagePreview {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImagePreview")
+            .field("key_size", &self.key_size)
+            .field("gap", &self.gap)
+            .field("background", &self.background)
+            .field("key_count", &self.keys.len())
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::layout::{KeyName, LayoutKind};
+
+    /// Content of an image, compared instead of the bytes written by the encoder
+    #[derive(Debug, PartialEq)]
+    struct Decoded {
+        /// Width and height in pixels
+        size: (u32, u32),
+        /// Number of plays of an animation, 0 for ever
+        plays: Option<u32>,
+        /// Frames in order
+        frames: Vec<DecodedFrame>,
+    }
+
+    /// Frame of a [`Decoded`] image
+    #[derive(Debug, PartialEq)]
+    struct DecodedFrame {
+        /// Delay as a fraction of second, numerator then denominator
+        delay: Option<(u16, u16)>,
+        /// Palette of a GIF frame
+        palette: Option<Vec<u8>>,
+        /// RGB pixels
+        pixels: Vec<u8>,
+    }
+
+    /// Decode a PNG or an animated PNG
+    fn decode_png(bytes: &[u8]) -> Decoded {
+        let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+        let info = reader.info();
+        let size = (info.width, info.height);
+        let (count, plays) = info.animation_control().map_or((1, None), |control| {
+            (control.num_frames, Some(control.num_plays))
+        });
+        let mut frames = Vec::new();
+        for _ in 0..count {
+            let mut pixels = vec![0; reader.output_buffer_size()];
+            reader.next_frame(&mut pixels).unwrap();
+            let delay = reader
+                .info()
+                .frame_control()
+                .map(|control| (control.delay_num, control.delay_den));
+            frames.push(DecodedFrame {
+                delay,
+                palette: None,
+                pixels,
+            });
+        }
+        Decoded {
+            size,
+            plays,
+            frames,
+        }
+    }
+
+    /// Decode an animated GIF, the indices are replaced by the colors of the palette
+    fn decode_gif(bytes: &[u8]) -> Decoded {
+        let mut decoder = gif::DecodeOptions::new().read_info(bytes).unwrap();
+        let size = (u32::from(decoder.width()), u32::from(decoder.height()));
+        let global_palette = decoder.global_palette().map(<[u8]>::to_vec);
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            let palette = frame.palette.clone().or_else(|| global_palette.clone());
+            let colors = palette.as_deref().unwrap_or_default();
+            let pixels = frame
+                .buffer
+                .iter()
+                .flat_map(|index| colors[usize::from(*index) * 3..][..3].to_vec())
+                .collect();
+            frames.push(DecodedFrame {
+                delay: Some((frame.delay, 100)),
+                palette,
+                pixels,
+            });
+        }
+        let plays = match decoder.repeat() {
+            gif::Repeat::Infinite => 0,
+            gif::Repeat::Finite(count) => u32::from(count),
+        };
+        Decoded {
+            size,
+            plays: Some(plays),
+            frames,
+        }
+    }
+
+    /// Compare the image `bytes` to the file `name` of the folder `src/preview/golden` once
+    /// decoded, so that a new version of the encoder does not break the test. The file is
+    /// written instead when the environment variable `UPDATE_GOLDEN` is set.
+    fn assert_golden(name: &str, bytes: &[u8], decode: fn(&[u8]) -> Decoded) -> Decoded {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "preview", "golden", name]
+            .iter()
+            .collect();
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, bytes).unwrap();
+        }
+        let decoded = decode(bytes);
+        assert_eq!(
+            decode(&fs::read(&path).unwrap()),
+            decoded,
+            "{} differs from the golden file",
+            name
+        );
+        decoded
+    }
+
+    /// Frames lighting Escape and A in different colors
+    fn frames() -> Vec<ColorBuffer<ColorRgb>> {
+        let layout = LayoutKind::FrCh.layout();
+        [
+            ColorRgb::new(255, 0, 0),
+            ColorRgb::new(0, 255, 0),
+            ColorRgb::new(0, 0, 255),
+        ]
+        .iter()
+        .map(|color| {
+            let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+            *buffer.get_key_mut(layout, KeyName::Escape).unwrap() = *color;
+            *buffer.get_key_mut(layout, KeyName::A).unwrap() = ColorRgb::new(255, 255, 255);
+            buffer
+        })
+        .collect()
+    }
+
+    #[test]
+    fn render() {
+        let layout = LayoutKind::FrCh.layout();
+        let preview = ImagePreview::new(layout);
+        let buffer = frames().remove(0);
+        let image = preview.render(&buffer);
+        assert_eq!(image.width(), preview.width());
+        assert_eq!(image.height(), preview.height());
+        assert_eq!(image.height(), 6 * 24 + 2);
+        assert_eq!(
+            image.as_bytes().len(),
+            (image.width() * image.height() * 3) as usize
+        );
+        // Escape is the top left key
+        assert_eq!(image.pixel(0, 0), Some(DEFAULT_BACKGROUND));
+        assert_eq!(image.pixel(2, 2), Some(ColorRgb::new(255, 0, 0)));
+        assert_eq!(image.pixel(23, 23), Some(ColorRgb::new(255, 0, 0)));
+        assert_eq!(image.pixel(24, 24), Some(DEFAULT_BACKGROUND));
+        assert_eq!(image.pixel(27, 2), Some(DEFAULT_BACKGROUND));
+        assert_eq!(image.pixel(image.width(), 0), None);
+
+        let small = preview
+            .with_key_size(0, 3)
+            .with_background(ColorRgb::new(1, 2, 3));
+        assert_eq!((small.key_size(), small.gap()), (1, 0));
+        assert_eq!(
+            small.render(&buffer).pixel(0, 0),
+            Some(ColorRgb::new(255, 0, 0))
+        );
+    }
+
+    #[test]
+    fn golden_png() {
+        let preview = ImagePreview::new(LayoutKind::FrCh.layout()).with_key_size(8, 1);
+        let mut png = Vec::new();
+        preview.write_png(&mut png, &frames()[0]).unwrap();
+        let decoded = assert_golden("keyboard.png", &png, decode_png);
+        assert_eq!(decoded.size, (preview.width(), preview.height()));
+        assert_eq!(decoded.plays, None);
+        assert_eq!(decoded.frames.len(), 1);
+        assert_eq!(
+            decoded.frames[0].pixels,
+            preview.render(&frames()[0]).as_bytes()
+        );
+    }
+
+    #[test]
+    fn golden_apng() {
+        let preview = ImagePreview::new(LayoutKind::FrCh.layout()).with_key_size(8, 1);
+        let mut apng = Vec::new();
+        preview
+            .write_apng(&mut apng, &frames(), Duration::from_millis(250))
+            .unwrap();
+        let decoded = assert_golden("animation.png", &apng, decode_png);
+        assert_eq!(decoded.plays, Some(0));
+        assert_eq!(decoded.frames.len(), 3);
+        for (decoded, frame) in decoded.frames.iter().zip(frames()) {
+            assert_eq!(decoded.delay, Some((250, 1000)));
+            assert_eq!(decoded.pixels, preview.render(&frame).as_bytes());
+        }
+
+        assert!(matches!(
+            preview.write_apng(Vec::new(), &frames()[..0], Duration::from_millis(250)),
+            Err(ImageExportError::NoFrame)
+        ));
+    }
+
+    #[test]
+    fn golden_gif() {
+        let preview = ImagePreview::new(LayoutKind::FrCh.layout()).with_key_size(8, 1);
+        let mut gif = Vec::new();
+        preview
+            .write_gif(&mut gif, &frames(), Duration::from_millis(250))
+            .unwrap();
+        let decoded = assert_golden("animation.gif", &gif, decode_gif);
+        assert_eq!(decoded.plays, Some(0));
+        assert_eq!(decoded.frames.len(), 3);
+        for (decoded, frame) in decoded.frames.iter().zip(frames()) {
+            assert_eq!(decoded.delay, Some((25, 100)));
+            // the palette starts with the background
+            let background = DEFAULT_BACKGROUND;
+            assert_eq!(
+                decoded.palette.as_ref().unwrap()[..3],
+                [background.r(), background.g(), background.b()]
+            );
+            assert_eq!(decoded.pixels, preview.render(&frame).as_bytes());
+        }
+    }
+}
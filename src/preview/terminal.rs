@@ -0,0 +1,386 @@
+//! Contains [`TerminalPreview`] and [`TerminalKeyboard`]
+
+use std::cell::{Cell, RefCell};
+use std::fmt::{Debug, Formatter};
+use std::io::{self, Stdout, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::{
+    color::{ColorBuffer, ColorRgb, NUMBER_KEY_LED_BUFFER},
+    error::ErrorRoccatVulcanApi,
+    keyboard::{ControlerFeatureKind, Keyboard},
+    layout::{KeyPress, Layout},
+};
+
+/// Default number of characters per key, including the space between keys
+const DEFAULT_KEY_WIDTH: usize = 5;
+/// Escape sequence restoring the default colors
+const RESET: &str = "\x1b[0m";
+
+/// A key placed in the preview
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PlacedKey {
+    /// Row, from the top
+    row: usize,
+    /// Column in key units, from the left
+    column: usize,
+    /// Index of the light in the [`ColorBuffer`]
+    light: usize,
+    /// Label printed on the key
+    label: String,
+}
+
+/// Draws a [`ColorBuffer`] as a keyboard in a terminal with ANSI truecolor escape sequences.
+///
+/// Each key of the [`Layout`] that has a light is placed according to its position
+/// and labelled with the start of [`crate::KeyName::description`].
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{ColorBuffer, ColorRgb, LayoutKind, TerminalPreview};
+///
+/// let preview = TerminalPreview::new(LayoutKind::FrCh.layout());
+/// let frame = preview.render(&ColorBuffer::from_element(ColorRgb::new(255, 128, 0)));
+/// assert_eq!(frame.lines().count(), preview.height());
+/// assert!(frame.contains("\x1b[48;2;255;128;0m"));
+/// print!("{}", frame);
+/// ```
+#[derive(Clone)]
+pub struct TerminalPreview {
+    /// Layout drawn
+    layout: &'static dyn Layout,
+    /// Number of characters per key, including the space between keys
+    key_width: usize,
+    /// Keys sorted by row then column
+    keys: Vec<PlacedKey>,
+    /// Number of rows
+    height: usize,
+}
+
+impl TerminalPreview {
+    /// Create a preview of the keys of `layout`
+    pub fn new(layout: &'static dyn Layout) -> Self {
+        Self::with_key_width_layout(layout, DEFAULT_KEY_WIDTH)
+    }
+
+    /// Set the number of characters per key, including the space between keys,
+    /// at least 2 so that a key shows one character of its label
+    pub fn with_key_width(self, key_width: usize) -> Self {
+        Self::with_key_width_layout(self.layout, key_width)
+    }
+
+    /// Place the keys of `layout`
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn with_key_width_layout(layout: &'static dyn Layout, key_width: usize) -> Self {
+        let key_width = key_width.max(2);
+        // the positions are positive and small
+        let round = |value: f64| value.max(0_f64).round() as usize;
+        let keys: Vec<_> = layout
+            .layout()
+            .iter()
+            .filter(|info| usize::from(info.key_code_light().code()) < NUMBER_KEY_LED_BUFFER)
+            .collect();
+        let height = keys
+            .iter()
+            .map(|info| round(info.key_pos().y()) + 1)
+            .max()
+            .unwrap_or(0);
+        let mut placed: Vec<PlacedKey> = keys
+            .iter()
+            .map(|info| PlacedKey {
+                // the y axis of the layout goes up
+                row: height - 1 - round(info.key_pos().y()),
+                column: round(info.key_pos().x()),
+                light: usize::from(info.key_code_light().code()),
+                label: info
+                    .key_name()
+                    .description()
+                    .chars()
+                    .take(key_width - 1)
+                    .collect(),
+            })
+            .collect();
+        placed.sort_by_key(|key| (key.row, key.column));
+        Self {
+            layout,
+            key_width,
+            keys: placed,
+            height,
+        }
+    }
+
+    /// Get the layout drawn
+    pub fn layout(&self) -> &'static dyn Layout {
+        self.layout
+    }
+
+    /// Get the number of characters per key, including the space between keys
+    pub const fn key_width(&self) -> usize {
+        self.key_width
+    }
+
+    /// Get the number of lines of a frame
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Draw the buffer, each line ends with a new line.
+    ///
+    /// A key is drawn further right when a previous key of the row takes its place.
+    pub fn render(&self, buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>) -> String {
+        let mut frame = String::new();
+        let mut keys = self.keys.iter().peekable();
+        for row in 0..self.height {
+            let mut cursor = 0;
+            while let Some(key) = keys.next_if(|key| key.row == row) {
+                let start = (key.column * self.key_width).max(cursor);
+                frame.push_str(&" ".repeat(start - cursor));
+                let color: ColorRgb = buffer[key.light].into();
+                frame.push_str(&format!(
+                    "\x1b[48;2;{};{};{}m\x1b[38;2;{}m{:<width$}{} ",
+                    color.r(),
+                    color.g(),
+                    color.b(),
+                    text_color(color),
+                    key.label,
+                    RESET,
+                    width = self.key_width - 1
+                ));
+                cursor = start + self.key_width;
+            }
+            frame.push('\n');
+        }
+        frame
+    }
+
+    /// Write the frame to `out`
+    /// # Errors
+    /// Returns the errors of `out`.
+    pub fn write(
+        &self,
+        out: &mut impl Write,
+        buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>,
+    ) -> io::Result<()> {
+        out.write_all(self.render(buffer).as_bytes())?;
+        out.flush()
+    }
+
+    /// Write the frame over the previous one, moving the cursor up by [`Self::height`] lines
+    /// # Errors
+    /// Returns the errors of `out`.
+    pub fn rewrite(
+        &self,
+        out: &mut impl Write,
+        buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>,
+    ) -> io::Result<()> {
+        if self.height > 0 {
+            write!(out, "\x1b[{}A\r", self.height)?;
+        }
+        self.write(out, buffer)
+    }
+}
+
+/// Get the ANSI code of the text color readable on `background`, black or white
+fn text_color(background: ColorRgb) -> &'static str {
+    let luminance = 299 * u32::from(background.r())
+        + 587 * u32::from(background.g())
+        + 114 * u32::from(background.b());
+    if luminance > 128_000 {
+        "0;0;0"
+    } else {
+        "255;255;255"
+    }
+}
+
+impl Debug for TerminalPreview {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalPreview")
+            .field("key_width", &self.key_width)
+            .field("key_count", &self.keys.len())
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+/// [`Keyboard`] drawing in a terminal with a [`TerminalPreview`], refreshing the same lines.
+///
+/// No key is ever pressed, reading the key presses only waits, and the mode is ignored.
+/// It runs the effects without keyboard, for instance over SSH or in the logs of a CI.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorBuffer, ColorRgb, LayoutKind, ProfileRenderer, TerminalKeyboard, TerminalPreview, Wave,
+/// };
+///
+/// let layout = LayoutKind::FrCh.layout();
+/// let keyboard = TerminalKeyboard::new(TerminalPreview::new(layout), Vec::new());
+/// let mut renderer =
+///     ProfileRenderer::new(layout, ColorBuffer::from_element(ColorRgb::new(0, 0, 0)));
+/// renderer.push_effect(Wave::default(), None);
+/// for frame in 0..3_u32 {
+///     keyboard
+///         .render(&renderer.frame(Duration::from_millis(100) * frame))
+///         .unwrap();
+/// }
+/// assert_eq!(keyboard.frame_count(), 3);
+/// ```
+pub struct TerminalKeyboard<W: Write = Stdout> {
+    /// Preview drawn
+    preview: TerminalPreview,
+    /// Where the frames are written
+    out: RefCell<W>,
+    /// Number of frames written
+    frame_count: Cell<usize>,
+}
+
+impl TerminalKeyboard {
+    /// Draw on the standard output
+    pub fn stdout(preview: TerminalPreview) -> Self {
+        Self::new(preview, io::stdout())
+    }
+}
+
+impl<W: Write> TerminalKeyboard<W> {
+    /// Draw on `out`
+    pub const fn new(preview: TerminalPreview, out: W) -> Self {
+        Self {
+            preview,
+            out: RefCell::new(out),
+            frame_count: Cell::new(0),
+        }
+    }
+
+    /// Get the preview drawn
+    pub const fn preview(&self) -> &TerminalPreview {
+        &self.preview
+    }
+
+    /// Get the number of frames written
+    pub const fn frame_count(&self) -> usize {
+        self.frame_count.get()
+    }
+
+    /// Get back the output
+    pub fn into_inner(self) -> W {
+        self.out.into_inner()
+    }
+
+    /// Draw the buffer over the previous frame
+    /// # Errors
+    /// [`ErrorRoccatVulcanApi::Io`] if the output failed
+    pub fn render(
+        &self,
+        buffer: &ColorBuffer<impl Into<ColorRgb> + Copy>,
+    ) -> Result<(), ErrorRoccatVulcanApi> {
+        let mut out = self.out.borrow_mut();
+        if self.frame_count.get() == 0 {
+            self.preview.write(&mut *out, buffer)?;
+        } else {
+            self.preview.rewrite(&mut *out, buffer)?;
+        }
+        self.frame_count.set(self.frame_count.get() + 1);
+        Ok(())
+    }
+}
+
+impl<W: Write> Keyboard for TerminalKeyboard<W> {
+    fn render(&self, buffer: &ColorBuffer<ColorRgb>) -> Result<(), ErrorRoccatVulcanApi> {
+        Self::render(self, buffer)
+    }
+
+    fn read_key_press(&self, duration: Duration) -> Result<Vec<KeyPress>, ErrorRoccatVulcanApi> {
+        thread::sleep(duration);
+        Ok(Vec::new())
+    }
+
+    fn set_mode(&self, _kind: ControlerFeatureKind) -> Result<(), ErrorRoccatVulcanApi> {
+        Ok(())
+    }
+}
+
+impl<W: Write> Debug for TerminalKeyboard<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalKeyboard")
+            .field("preview", &self.preview)
+            .field("frame_count", &self.frame_count.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::{KeyName, LayoutKind};
+
+    /// Remove the escape sequences
+    fn strip_ansi(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                // skip up to the final letter of the sequence
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn render() {
+        let layout = LayoutKind::FrCh.layout();
+        let preview = TerminalPreview::new(layout);
+        let mut buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+        *buffer.get_key_mut(layout, KeyName::Escape).unwrap() = ColorRgb::new(255, 255, 0);
+        let frame = preview.render(&buffer);
+        assert_eq!(preview.height(), 6);
+        assert_eq!(frame.lines().count(), 6);
+        // Escape is the first key of the first line, in yellow with a black label
+        assert!(frame.starts_with("\x1b[48;2;255;255;0m\x1b[38;2;0;0;0mEsca\x1b[0m "));
+        assert!(frame.contains("\x1b[48;2;0;0;0m\x1b[38;2;255;255;255mF1  \x1b[0m "));
+
+        let plain = strip_ansi(&frame);
+        let lines: Vec<&str> = plain.lines().collect();
+        assert!(lines[0].starts_with("Esca      F1   F2   "));
+        assert!(lines[1].starts_with("\u{a7}    1    2    "));
+        assert!(lines[5].starts_with("left supe alt "));
+        assert!(!plain.contains("whee"));
+        // every key that has a light is drawn once
+        let key_count = layout
+            .layout()
+            .iter()
+            .filter(|info| usize::from(info.key_code_light().code()) < NUMBER_KEY_LED_BUFFER)
+            .count();
+        assert_eq!(frame.matches(RESET).count(), key_count);
+
+        let narrow = preview.with_key_width(0);
+        assert_eq!(narrow.key_width(), 2);
+        assert!(strip_ansi(&narrow.render(&buffer)).starts_with("E   F F "));
+    }
+
+    #[test]
+    fn refresh() {
+        let layout = LayoutKind::FrCh.layout();
+        let keyboard = TerminalKeyboard::new(TerminalPreview::new(layout), Vec::new());
+        let buffer = ColorBuffer::from_element(ColorRgb::new(0, 0, 255));
+        Keyboard::render(&keyboard, &buffer).unwrap();
+        assert!(keyboard
+            .read_key_press(Duration::from_millis(1))
+            .unwrap()
+            .is_empty());
+        keyboard.set_mode(ControlerFeatureKind::Rainbow).unwrap();
+        keyboard.render(&buffer).unwrap();
+        assert_eq!(keyboard.frame_count(), 2);
+        let frame = keyboard.preview().render(&buffer);
+        let output = String::from_utf8(keyboard.into_inner()).unwrap();
+        assert_eq!(output, format!("{}\x1b[6A\r{}", frame, frame));
+    }
+}
@@ -0,0 +1,19 @@
+//! Contains [`Profile`], a lighting setup that can be loaded from a file, and its renderer
+
+mod base;
+pub use base::*;
+
+mod target;
+pub use target::*;
+
+mod error;
+pub use error::*;
+
+mod renderer;
+pub use renderer::*;
+
+mod combo;
+pub use combo::*;
+
+mod manager;
+pub use manager::*;
@@ -0,0 +1,436 @@
+//! Contains [`Profile`], a full lighting setup that can be saved in a file
+
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "serde-serialize")]
+use std::path::Path;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::{KeyTarget, ProfileError, ProfileRenderer};
+use crate::{
+    color::{ColorBuffer, ColorRgb},
+    effect::EffectConfig,
+    layout::LayoutKind,
+};
+
+/// Version of the profile format written by this version of the library
+pub const PROFILE_VERSION: u64 = 1;
+
+/// A color applied on some keys before the effects
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(deny_unknown_fields))]
+pub struct BaseColor {
+    /// Keys colored
+    keys: Vec<KeyTarget>,
+    /// Color of the keys
+    color: ColorRgb,
+}
+
+impl BaseColor {
+    /// Create a new base color
+    pub const fn new(keys: Vec<KeyTarget>, color: ColorRgb) -> Self {
+        Self { keys, color }
+    }
+
+    /// Get the keys colored
+    pub fn keys(&self) -> &[KeyTarget] {
+        &self.keys
+    }
+
+    /// Get the color
+    pub const fn color(&self) -> ColorRgb {
+        self.color
+    }
+}
+
+/// An effect applied on some keys
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(deny_unknown_fields))]
+pub struct EffectLayer {
+    /// Effect and its parameters
+    effect: EffectConfig,
+    /// Keys changed by the effect, all the keys if empty
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    keys: Vec<KeyTarget>,
+}
+
+impl EffectLayer {
+    /// Create a new layer, if `keys` is empty the effect is applied on all the keys
+    pub fn new(effect: impl Into<EffectConfig>, keys: Vec<KeyTarget>) -> Self {
+        Self {
+            effect: effect.into(),
+            keys,
+        }
+    }
+
+    /// Get the effect
+    pub const fn effect(&self) -> &EffectConfig {
+        &self.effect
+    }
+
+    /// Get the keys changed by the effect, all the keys if empty
+    pub fn keys(&self) -> &[KeyTarget] {
+        &self.keys
+    }
+}
+
+/// A full lighting setup: the layout, a base color per key or group of keys
+/// and a stack of effects.
+///
+/// With the feature `serde-serialize` it can be loaded from a JSON file. The field `version`
+/// is required, the other fields are optional.
+/// ```json
+/// {
+///     "version": 1,
+///     "name": "sunset",
+///     "layout": "FrCh",
+///     "base": [
+///         { "keys": ["@All"], "color": "#201000" },
+///         { "keys": ["@FunctionKeys", "Escape"], "color": "orange" }
+///     ],
+///     "effects": [
+///         { "effect": { "type": "Breathing", "period": 6.0, "min_brightness": 0.3 } },
+///         { "effect": { "type": "Reactive", "color": "white", "fade": 0.5 }, "keys": ["@Letters"] }
+///     ]
+/// }
+/// ```
+/// The base colors are applied in order, the keys not colored are black.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{Breathing, ColorRgb, KeyGroup, KeyName, Profile};
+///
+/// let profile = Profile::new("red escape")
+///     .with_base_color(vec![KeyName::Escape.into()], ColorRgb::new(255, 0, 0))
+///     .with_effect(Breathing::default(), vec![KeyGroup::Letters.into()]);
+/// let mut renderer = profile.renderer().unwrap();
+/// let frame = renderer.frame(Duration::ZERO);
+/// assert_eq!(frame[0], ColorRgb::new(255, 0, 0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(deny_unknown_fields))]
+pub struct Profile {
+    /// Version of the format
+    version: u64,
+    /// Name of the profile
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    name: String,
+    /// Layout of the keyboard
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    layout: LayoutKind,
+    /// Base colors applied in order
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    base: Vec<BaseColor>,
+    /// Effects applied in order on top of the base colors
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    effects: Vec<EffectLayer>,
+}
+
+impl Profile {
+    /// Create an empty profile with the default layout
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            name: name.into(),
+            layout: LayoutKind::default(),
+            base: Vec::new(),
+            effects: Vec::new(),
+        }
+    }
+
+    /// Set the layout
+    pub const fn with_layout(mut self, layout: LayoutKind) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Add a base color
+    pub fn with_base_color(mut self, keys: Vec<KeyTarget>, color: ColorRgb) -> Self {
+        self.base.push(BaseColor::new(keys, color));
+        self
+    }
+
+    /// Add an effect on top of the others, if `keys` is empty the effect is applied on all
+    /// the keys
+    pub fn with_effect(mut self, effect: impl Into<EffectConfig>, keys: Vec<KeyTarget>) -> Self {
+        self.effects.push(EffectLayer::new(effect, keys));
+        self
+    }
+
+    /// Get the version of the format
+    pub const fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Get the name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the layout
+    pub const fn layout(&self) -> LayoutKind {
+        self.layout
+    }
+
+    /// Get the base colors
+    pub fn base(&self) -> &[BaseColor] {
+        &self.base
+    }
+
+    /// Get the effects
+    pub fn effects(&self) -> &[EffectLayer] {
+        &self.effects
+    }
+
+    /// Check the version, the keys and the parameters of the effects.
+    /// # Errors
+    /// - [`ProfileError::UnsupportedVersion`] if the version is not supported
+    /// - [`ProfileError::KeyWithoutLight`] if a key is not in the layout or has no light
+    /// - [`ProfileError::InvalidEffect`] if the parameters of an effect are invalid
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        if self.version != PROFILE_VERSION {
+            return Err(ProfileError::UnsupportedVersion(self.version));
+        }
+        let layout = self.layout.layout();
+        let targets = self
+            .base
+            .iter()
+            .flat_map(|base| base.keys.iter())
+            .chain(self.effects.iter().flat_map(|layer| layer.keys.iter()));
+        for target in targets {
+            target.light_indices(layout)?;
+        }
+        for (index, layer) in self.effects.iter().enumerate() {
+            layer
+                .effect
+                .validate()
+                .map_err(|reason| ProfileError::InvalidEffect { index, reason })?;
+        }
+        Ok(())
+    }
+
+    /// Validate the profile and create a renderer ready to run.
+    /// # Errors
+    /// See [`Self::validate`].
+    pub fn renderer(&self) -> Result<ProfileRenderer, ProfileError> {
+        self.validate()?;
+        let layout = self.layout.layout();
+        let mut base = ColorBuffer::from_element(ColorRgb::new(0, 0, 0));
+        for base_color in &self.base {
+            for target in &base_color.keys {
+                for index in target.light_indices(layout)? {
+                    base[index] = base_color.color;
+                }
+            }
+        }
+        let mut renderer = ProfileRenderer::new(layout, base);
+        for layer in &self.effects {
+            let lights = if layer.keys.is_empty() {
+                None
+            } else {
+                let mut lights = Vec::new();
+                for target in &layer.keys {
+                    lights.extend(target.light_indices(layout)?);
+                }
+                Some(lights)
+            };
+            renderer.push_effect(layer.effect.clone(), lights);
+        }
+        Ok(renderer)
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl Profile {
+    /// Parse and validate a profile in JSON.
+    ///
+    /// The version is checked before the rest of the file
+    /// so that a newer format gives [`ProfileError::UnsupportedVersion`].
+    /// # Errors
+    /// - [`ProfileError::Json`] if the file is not valid, for instance with an unknown key name
+    /// - [`ProfileError::MissingVersion`] if there is no version
+    /// - the errors of [`Self::validate`]
+    /// # Example
+    /// ```
+    /// use roccat_vulcan_api_rs::{Profile, ProfileError};
+    ///
+    /// let profile = Profile::from_json(r#"{"version": 1, "name": "empty"}"#).unwrap();
+    /// assert_eq!(profile.name(), "empty");
+    ///
+    /// let error =
+    ///     Profile::from_json(r#"{"version": 1, "base": [{"keys": ["Escap"], "color": "red"}]}"#)
+    ///         .unwrap_err();
+    /// assert!(matches!(error, ProfileError::Json(_)));
+    /// assert!(error.to_string().contains("unknown key name \"Escap\""));
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, ProfileError> {
+        /// Only the version, to check it before parsing the rest
+        #[derive(Deserialize)]
+        struct Version {
+            /// Version of the format
+            version: Option<u64>,
+        }
+
+        match serde_json::from_str::<Version>(json)?.version {
+            None => return Err(ProfileError::MissingVersion),
+            Some(PROFILE_VERSION) => {}
+            Some(version) => return Err(ProfileError::UnsupportedVersion(version)),
+        }
+        let profile: Self = serde_json::from_str(json)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Serialize the profile in pretty printed JSON
+    /// # Errors
+    /// [`ProfileError::Json`] if the serialization fails
+    pub fn to_json(&self) -> Result<String, ProfileError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Read a profile from a JSON file, see [`Self::from_json`]
+    /// # Errors
+    /// [`ProfileError::Io`] if the file cannot be read, and the errors of [`Self::from_json`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write the profile in a JSON file
+    /// # Errors
+    /// [`ProfileError::Io`] if the file cannot be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ProfileError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "profile \"{}\" ({} layout, {} base colors, {} effects)",
+            self.name,
+            self.layout,
+            self.base.len(),
+            self.effects.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        effect::Wave,
+        layout::{KeyGroup, KeyName, LayoutFrCh},
+    };
+
+    #[test]
+    fn renderer() {
+        let red = ColorRgb::new(255, 0, 0);
+        let green = ColorRgb::new(0, 255, 0);
+        let profile = Profile::new("test")
+            .with_base_color(vec![KeyGroup::All.into()], red)
+            .with_base_color(vec![KeyName::Escape.into()], green)
+            .with_effect(Wave::default(), vec![KeyGroup::Arrows.into()]);
+        assert!(profile.validate().is_ok());
+        let mut renderer = profile.renderer().unwrap();
+        let frame = renderer.frame(Duration::from_millis(300));
+        let layout = LayoutFrCh::new();
+        assert_eq!(frame.get_key(&layout, KeyName::Escape), Some(&green));
+        assert_eq!(frame.get_key(&layout, KeyName::A), Some(&red));
+        assert_ne!(frame.get_key(&layout, KeyName::ArrowUp), Some(&red));
+
+        let invalid = Profile::new("wheel").with_base_color(vec![KeyName::WheelDown.into()], red);
+        assert!(matches!(
+            invalid.renderer(),
+            Err(ProfileError::KeyWithoutLight(KeyName::WheelDown))
+        ));
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn json() {
+        use crate::effect::Reactive;
+
+        let json = r##"{
+            "version": 1,
+            "name": "sunset",
+            "layout": "FrCh",
+            "base": [
+                { "keys": ["@All"], "color": "#201000" },
+                { "keys": ["@FunctionKeys", "Escape"], "color": "orange" }
+            ],
+            "effects": [
+                { "effect": { "type": "Breathing", "period": 6.0, "min_brightness": 0.3 } },
+                { "effect": { "type": "Reactive", "color": "white", "fade": 0.5 }, "keys": ["@Letters"] }
+            ]
+        }"##;
+        let profile = Profile::from_json(json).unwrap();
+        assert_eq!(profile.name(), "sunset");
+        assert_eq!(profile.base().len(), 2);
+        assert_eq!(
+            profile.effects()[1],
+            EffectLayer::new(
+                Reactive::new(ColorRgb::new(255, 255, 255), Duration::from_millis(500)).unwrap(),
+                vec![KeyGroup::Letters.into()]
+            )
+        );
+        let round_trip = Profile::from_json(&profile.to_json().unwrap()).unwrap();
+        assert_eq!(round_trip, profile);
+
+        let error = |json| Profile::from_json(json).unwrap_err();
+        assert!(matches!(
+            error(r#"{"name": "no version"}"#),
+            ProfileError::MissingVersion
+        ));
+        assert!(matches!(
+            error(r#"{"version": 2, "some_new_field": []}"#),
+            ProfileError::UnsupportedVersion(2)
+        ));
+        let unknown = error(r#"{"version": 1, "base": [{"keys": ["Escap"], "color": "red"}]}"#);
+        assert!(
+            unknown.to_string().contains("unknown key name \"Escap\""),
+            "{}",
+            unknown
+        );
+        assert!(matches!(
+            error(
+                r#"{"version": 1, "effects": [{"effect": {"type": "Breathing", "period": -1}}]}"#
+            ),
+            ProfileError::InvalidEffect { index: 0, .. }
+        ));
+        assert!(matches!(
+            error(r#"{"version": 1, "base": [{"keys": ["WheelUp"], "color": "red"}]}"#),
+            ProfileError::KeyWithoutLight(KeyName::WheelUp)
+        ));
+        assert!(matches!(
+            error(r#"{"version": 1, "colour": []}"#),
+            ProfileError::Json(_)
+        ));
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn save_and_load() {
+        use crate::effect::Breathing;
+
+        let path = std::env::temp_dir().join(format!("profile-test-{}.json", std::process::id()));
+        let profile = Profile::new("saved").with_effect(Breathing::default(), Vec::new());
+        profile.save(&path).unwrap();
+        let loaded = Profile::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, profile);
+        assert!(matches!(Profile::load(&path), Err(ProfileError::Io(_))));
+    }
+}
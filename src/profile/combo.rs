@@ -0,0 +1,138 @@
+//! Contains [`KeyCombo`]
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::layout::{KeyName, KeyParseError};
+
+/// A combination of keys, like `Function+F1`.
+///
+/// The combination is triggered when the last key is pressed while the others are held.
+/// It is written as the names of the keys separated by `+`.
+/// # Example
+/// ```
+/// use std::collections::BTreeSet;
+///
+/// use roccat_vulcan_api_rs::{KeyCombo, KeyName};
+///
+/// let combo: KeyCombo = "Function+F1".parse().unwrap();
+/// assert_eq!(combo.trigger(), KeyName::F1);
+/// let held: BTreeSet<_> = vec![KeyName::Function].into_iter().collect();
+/// assert!(combo.is_triggered(KeyName::F1, &held));
+/// assert!(!combo.is_triggered(KeyName::F1, &BTreeSet::new()));
+/// assert_eq!(combo.to_string(), "Function+F1");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyCombo {
+    /// Keys of the combination, the last one triggers it
+    keys: Vec<KeyName>,
+}
+
+impl KeyCombo {
+    /// Create a combination, returns [`None`] if `keys` is empty
+    pub fn new(keys: Vec<KeyName>) -> Option<Self> {
+        if keys.is_empty() {
+            None
+        } else {
+            Some(Self { keys })
+        }
+    }
+
+    /// Get the keys of the combination
+    pub fn keys(&self) -> &[KeyName] {
+        &self.keys
+    }
+
+    /// Get the key that triggers the combination
+    pub fn trigger(&self) -> KeyName {
+        // the vector is never empty
+        self.keys[self.keys.len() - 1]
+    }
+
+    /// Returns whether pressing `pressed` while `held` are held triggers the combination
+    pub fn is_triggered(&self, pressed: KeyName, held: &BTreeSet<KeyName>) -> bool {
+        pressed == self.trigger()
+            && self.keys[..self.keys.len() - 1]
+                .iter()
+                .all(|key| held.contains(key))
+    }
+}
+
+impl From<KeyName> for KeyCombo {
+    fn from(key_name: KeyName) -> Self {
+        Self {
+            keys: vec![key_name],
+        }
+    }
+}
+
+impl Display for KeyCombo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, key) in self.keys.iter().enumerate() {
+            if index > 0 {
+                write!(f, "+")?;
+            }
+            write!(f, "{:?}", key)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = KeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let keys = s
+            .split('+')
+            .map(str::parse)
+            .collect::<Result<Vec<KeyName>, _>>()?;
+        // splitting always gives at least one element
+        Ok(Self { keys })
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl Serialize for KeyCombo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<'de> Deserialize<'de> for KeyCombo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        string.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn combo() {
+        let combo: KeyCombo = "LeftControl + LeftShift + P".parse().unwrap();
+        assert_eq!(
+            combo.keys(),
+            &[KeyName::LeftControl, KeyName::LeftShift, KeyName::P]
+        );
+        let mut held = BTreeSet::new();
+        held.insert(KeyName::LeftControl);
+        assert!(!combo.is_triggered(KeyName::P, &held));
+        held.insert(KeyName::LeftShift);
+        assert!(combo.is_triggered(KeyName::P, &held));
+        assert!(!combo.is_triggered(KeyName::O, &held));
+
+        assert!(KeyCombo::from(KeyName::F1).is_triggered(KeyName::F1, &BTreeSet::new()));
+        assert_eq!(KeyCombo::new(Vec::new()), None);
+        assert_eq!(
+            "Function+".parse::<KeyCombo>(),
+            Err(KeyParseError::UnknownKeyName(String::new()))
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Contains [`ProfileError`]
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::{error::ErrorRoccatVulcanApi, layout::KeyName};
+
+/// Error returned when loading or validating a [`super::Profile`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ProfileError {
+    /// Error while reading or writing the file
+    Io(std::io::Error),
+    /// The file is not valid JSON or does not match the format,
+    /// for instance an unknown key name
+    #[cfg(feature = "serde-serialize")]
+    Json(serde_json::Error),
+    /// The field `version` is missing
+    MissingVersion,
+    /// The version of the file is not supported
+    UnsupportedVersion(u64),
+    /// The key is not in the layout or has no light
+    KeyWithoutLight(KeyName),
+    /// A parameter of an effect is invalid
+    InvalidEffect {
+        /// Index of the effect in the profile
+        index: usize,
+        /// Description of the problem
+        reason: &'static str,
+    },
+    /// There is no profile with this name
+    UnknownProfile(String),
+    /// Error of the keyboard while rendering a profile
+    Keyboard(ErrorRoccatVulcanApi),
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "io error : {}", error),
+            #[cfg(feature = "serde-serialize")]
+            Self::Json(error) => write!(f, "invalid profile : {}", error),
+            Self::MissingVersion => write!(f, "the profile has no version"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported profile version {}, the latest supported is {}",
+                version,
+                super::PROFILE_VERSION
+            ),
+            Self::KeyWithoutLight(key_name) => write!(
+                f,
+                "the key {:?} is not in the layout or has no light",
+                key_name
+            ),
+            Self::InvalidEffect { index, reason } => {
+                write!(f, "invalid effect at index {} : {}", index, reason)
+            }
+            Self::UnknownProfile(name) => write!(f, "unknown profile \"{}\"", name),
+            Self::Keyboard(error) => write!(f, "keyboard error : {}", error),
+        }
+    }
+}
+
+impl Error for ProfileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            #[cfg(feature = "serde-serialize")]
+            Self::Json(error) => Some(error),
+            Self::Keyboard(error) => Some(error),
+            Self::MissingVersion
+            | Self::UnsupportedVersion(_)
+            | Self::KeyWithoutLight(_)
+            | Self::InvalidEffect { .. }
+            | Self::UnknownProfile(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProfileError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ErrorRoccatVulcanApi> for ProfileError {
+    fn from(error: ErrorRoccatVulcanApi) -> Self {
+        Self::Keyboard(error)
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl From<serde_json::Error> for ProfileError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
@@ -0,0 +1,429 @@
+//! Contains [`ProfileManager`]
+
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::{KeyCombo, Profile, ProfileError, ProfileRenderer};
+use crate::{
+    color::{ColorBuffer, ColorRgb, ColorSpace},
+    keyboard::Keyboard,
+    layout::{KeyName, KeyPress, LayoutKind},
+};
+
+/// Default duration of the cross-fade
+const DEFAULT_FADE: Duration = Duration::from_millis(500);
+
+/// Profile being rendered
+struct Running {
+    /// Index of the profile
+    index: usize,
+    /// Renderer of the profile
+    renderer: ProfileRenderer,
+    /// Time at which the profile was activated
+    started: Duration,
+}
+
+impl Running {
+    /// Render the frame at `elapsed`, relative to the start of the profile
+    fn frame(&mut self, elapsed: Duration) -> ColorBuffer<ColorRgb> {
+        self.renderer.frame(elapsed.saturating_sub(self.started))
+    }
+}
+
+/// Hold several named [`Profile`]s and switch between them with key combinations.
+///
+/// When switching, the frames of the old and the new profile are cross-faded.
+/// If a state file is given, the name of the active profile is saved in it and
+/// restored by [`Self::restore`].
+///
+/// The time is given explicitly as `elapsed`, the time since the start of the rendering,
+/// [`Self::run`] uses the real clock.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorRgb, KeyGroup, KeyName, KeyPress, Layout, LayoutFrCh, Profile, ProfileManager,
+/// };
+///
+/// let red = ColorRgb::new(255, 0, 0);
+/// let blue = ColorRgb::new(0, 0, 255);
+/// let mut manager = ProfileManager::new().with_fade(Duration::from_secs(1));
+/// manager
+///     .add_profile(Profile::new("red").with_base_color(vec![KeyGroup::All.into()], red))
+///     .unwrap();
+/// manager
+///     .add_profile(Profile::new("blue").with_base_color(vec![KeyGroup::All.into()], blue))
+///     .unwrap();
+/// manager
+///     .bind("Function+F2".parse().unwrap(), "blue")
+///     .unwrap();
+/// manager.restore(Duration::ZERO).unwrap();
+/// assert_eq!(manager.active_name(), Some("red"));
+///
+/// let layout = LayoutFrCh::new();
+/// let code = |key| *layout.find_from_key_name(key).unwrap().key_code_press();
+/// let time = Duration::from_secs(10);
+/// manager
+///     .key_press(KeyPress::new(code(KeyName::Function), true), time)
+///     .unwrap();
+/// manager
+///     .key_press(KeyPress::new(code(KeyName::F2), true), time)
+///     .unwrap();
+/// assert_eq!(manager.active_name(), Some("blue"));
+///
+/// // half way through the cross-fade
+/// let frame = manager.frame(time + Duration::from_millis(500));
+/// assert_eq!(frame[0], ColorRgb::new(128, 0, 128));
+/// assert_eq!(manager.frame(time + Duration::from_secs(1))[0], blue);
+/// ```
+pub struct ProfileManager {
+    /// Profiles, the names are unique
+    profiles: Vec<Profile>,
+    /// Key combinations and the name of the profile they activate
+    bindings: Vec<(KeyCombo, String)>,
+    /// Duration of the cross-fade
+    fade: Duration,
+    /// File where the name of the active profile is saved
+    state_file: Option<PathBuf>,
+    /// Active profile
+    active: Option<Running>,
+    /// Previous profile while fading out, with the time of the switch
+    fading: Option<(Running, Duration)>,
+    /// Keys currently held
+    held: BTreeSet<KeyName>,
+}
+
+impl ProfileManager {
+    /// Create a manager without profile
+    pub const fn new() -> Self {
+        Self {
+            profiles: Vec::new(),
+            bindings: Vec::new(),
+            fade: DEFAULT_FADE,
+            state_file: None,
+            active: None,
+            fading: None,
+            held: BTreeSet::new(),
+        }
+    }
+
+    /// Set the duration of the cross-fade, zero to switch instantly
+    pub const fn with_fade(mut self, fade: Duration) -> Self {
+        self.fade = fade;
+        self
+    }
+
+    /// Set the file where the name of the active profile is saved
+    pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Get the duration of the cross-fade
+    pub const fn fade(&self) -> Duration {
+        self.fade
+    }
+
+    /// Get the file where the name of the active profile is saved
+    pub fn state_file(&self) -> Option<&Path> {
+        self.state_file.as_deref()
+    }
+
+    /// Get the profiles
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    /// Get the key combinations and the name of the profile they activate
+    pub fn bindings(&self) -> &[(KeyCombo, String)] {
+        &self.bindings
+    }
+
+    /// Get the name of the active profile
+    pub fn active_name(&self) -> Option<&str> {
+        self.active
+            .as_ref()
+            .map(|running| self.profiles[running.index].name())
+    }
+
+    /// Add a profile, replacing the one with the same name.
+    ///
+    /// If the profile replaced is active, the new version is used at the next switch.
+    /// # Errors
+    /// The errors of [`Profile::validate`].
+    pub fn add_profile(&mut self, profile: Profile) -> Result<(), ProfileError> {
+        profile.validate()?;
+        match self.find(profile.name()) {
+            Some(index) => self.profiles[index] = profile,
+            None => self.profiles.push(profile),
+        }
+        Ok(())
+    }
+
+    /// Activate the profile `name` when `combo` is pressed
+    /// # Errors
+    /// [`ProfileError::UnknownProfile`] if there is no profile with this name.
+    pub fn bind(&mut self, combo: KeyCombo, name: &str) -> Result<(), ProfileError> {
+        if self.find(name).is_none() {
+            return Err(ProfileError::UnknownProfile(name.to_owned()));
+        }
+        self.bindings.retain(|(other, _)| *other != combo);
+        self.bindings.push((combo, name.to_owned()));
+        Ok(())
+    }
+
+    /// Activate the profile saved in the state file, or the first profile if there is none.
+    ///
+    /// There is no cross-fade. Does nothing if there is no profile.
+    /// # Errors
+    /// [`ProfileError::Io`] if the state file exists but cannot be read,
+    /// and the errors of [`Profile::renderer`].
+    pub fn restore(&mut self, elapsed: Duration) -> Result<(), ProfileError> {
+        let saved = match &self.state_file {
+            Some(path) if path.exists() => {
+                let name = std::fs::read_to_string(path)?;
+                self.find(name.trim())
+            }
+            _ => None,
+        };
+        if let Some(index) = saved.or_else(|| (!self.profiles.is_empty()).then_some(0)) {
+            self.active = Some(self.start(index, elapsed)?);
+            self.fading = None;
+        }
+        Ok(())
+    }
+
+    /// Activate the profile `name`, cross-fading from the current one,
+    /// and save its name in the state file.
+    /// # Errors
+    /// - [`ProfileError::UnknownProfile`] if there is no profile with this name
+    /// - [`ProfileError::Io`] if the state file cannot be written
+    /// - the errors of [`Profile::renderer`]
+    pub fn activate(&mut self, name: &str, elapsed: Duration) -> Result<(), ProfileError> {
+        let index = self
+            .find(name)
+            .ok_or_else(|| ProfileError::UnknownProfile(name.to_owned()))?;
+        let running = self.start(index, elapsed)?;
+        self.fading = self
+            .active
+            .replace(running)
+            .filter(|_| !self.fade.is_zero())
+            .map(|previous| (previous, elapsed));
+        if let Some(path) = &self.state_file {
+            std::fs::write(path, name)?;
+        }
+        Ok(())
+    }
+
+    /// Handle a key press: update the keys held, activate the profile bound to a key
+    /// combination and forward the key press to the active profile.
+    ///
+    /// Returns whether the profile changed.
+    /// # Errors
+    /// The errors of [`Self::activate`].
+    pub fn key_press(
+        &mut self,
+        key_press: KeyPress,
+        elapsed: Duration,
+    ) -> Result<bool, ProfileError> {
+        let layout = self.active.as_ref().map_or_else(
+            || LayoutKind::default().layout(),
+            |running| running.renderer.layout(),
+        );
+        let key_name = layout
+            .find_from_key_code(*key_press.key_code())
+            .map(|info| *info.key_name());
+        let mut switched = false;
+        if let Some(key_name) = key_name {
+            if key_press.is_pressed() {
+                let bound = self
+                    .bindings
+                    .iter()
+                    .find(|(combo, _)| combo.is_triggered(key_name, &self.held))
+                    .map(|(_, name)| name.clone());
+                if let Some(name) = bound {
+                    if self.active_name() != Some(name.as_str()) {
+                        self.activate(&name, elapsed)?;
+                        switched = true;
+                    }
+                }
+                self.held.insert(key_name);
+            } else {
+                self.held.remove(&key_name);
+            }
+        }
+        if let Some(running) = &mut self.active {
+            let relative = elapsed.saturating_sub(running.started);
+            running.renderer.key_press(key_press, relative);
+        }
+        Ok(switched)
+    }
+
+    /// Compute the colors at `elapsed`, black if there is no active profile
+    pub fn frame(&mut self, elapsed: Duration) -> ColorBuffer<ColorRgb> {
+        let mut frame = match &mut self.active {
+            Some(running) => running.frame(elapsed),
+            None => return ColorBuffer::from_element(ColorRgb::new(0, 0, 0)),
+        };
+        if let Some((previous, switched)) = &mut self.fading {
+            let t = elapsed.saturating_sub(*switched).as_secs_f64() / self.fade.as_secs_f64();
+            if t >= 1_f64 {
+                self.fading = None;
+            } else {
+                let old = previous.frame(elapsed);
+                for (new, old) in frame.iter_mut().zip(old.iter()) {
+                    *new = old.lerp(*new, t, ColorSpace::Rgb);
+                }
+            }
+        }
+        frame
+    }
+
+    /// Render on the keyboard until `stop` returns true for a key press,
+    /// switching profiles with the key combinations.
+    ///
+    /// Restores the active profile first if none is active.
+    /// # Errors
+    /// [`ProfileError::Keyboard`] if the keyboard encounter an error
+    /// and the errors of [`Self::key_press`].
+    pub fn run<K: Keyboard + ?Sized>(
+        &mut self,
+        keyboard: &K,
+        frame_duration: Duration,
+        mut stop: impl FnMut(KeyPress) -> bool,
+    ) -> Result<(), ProfileError> {
+        let start = Instant::now();
+        if self.active.is_none() {
+            self.restore(Duration::ZERO)?;
+        }
+        loop {
+            keyboard.render(&self.frame(start.elapsed()))?;
+            for key_press in keyboard.read_key_press(frame_duration)? {
+                if stop(key_press) {
+                    return Ok(());
+                }
+                self.key_press(key_press, start.elapsed())?;
+            }
+        }
+    }
+
+    /// Find the index of a profile by name
+    fn find(&self, name: &str) -> Option<usize> {
+        self.profiles
+            .iter()
+            .position(|profile| profile.name() == name)
+    }
+
+    /// Create the renderer of a profile
+    fn start(&self, index: usize, elapsed: Duration) -> Result<Running, ProfileError> {
+        Ok(Running {
+            index,
+            renderer: self.profiles[index].renderer()?,
+            started: elapsed,
+        })
+    }
+}
+
+impl Default for ProfileManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for ProfileManager {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProfileManager")
+            .field("profiles", &self.profiles)
+            .field("bindings", &self.bindings)
+            .field("fade", &self.fade)
+            .field("state_file", &self.state_file)
+            .field("active", &self.active_name())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::{KeyGroup, Layout, LayoutFrCh};
+
+    fn colors() -> ProfileManager {
+        let mut manager = ProfileManager::new().with_fade(Duration::ZERO);
+        for (name, color) in [
+            ("red", ColorRgb::new(255, 0, 0)),
+            ("green", ColorRgb::new(0, 255, 0)),
+            ("blue", ColorRgb::new(0, 0, 255)),
+        ] {
+            manager
+                .add_profile(Profile::new(name).with_base_color(vec![KeyGroup::All.into()], color))
+                .unwrap();
+        }
+        manager
+    }
+
+    fn press(manager: &mut ProfileManager, key: KeyName, is_pressed: bool) -> bool {
+        let layout = LayoutFrCh::new();
+        let code = *layout.find_from_key_name(key).unwrap().key_code_press();
+        manager
+            .key_press(KeyPress::new(code, is_pressed), Duration::ZERO)
+            .unwrap()
+    }
+
+    #[test]
+    fn bindings() {
+        let mut manager = colors();
+        assert_eq!(manager.frame(Duration::ZERO)[0], ColorRgb::new(0, 0, 0));
+        manager.restore(Duration::ZERO).unwrap();
+        assert_eq!(manager.active_name(), Some("red"));
+        manager
+            .bind("Function+F2".parse().unwrap(), "green")
+            .unwrap();
+        manager
+            .bind("Function+F3".parse().unwrap(), "blue")
+            .unwrap();
+        assert!(matches!(
+            manager.bind(KeyName::F4.into(), "yellow"),
+            Err(ProfileError::UnknownProfile(_))
+        ));
+
+        // F2 without Fn
+        assert!(!press(&mut manager, KeyName::F2, true));
+        assert!(!press(&mut manager, KeyName::F2, false));
+        assert_eq!(manager.active_name(), Some("red"));
+
+        assert!(!press(&mut manager, KeyName::Function, true));
+        assert!(press(&mut manager, KeyName::F3, true));
+        assert!(!press(&mut manager, KeyName::F3, false));
+        assert_eq!(manager.active_name(), Some("blue"));
+        assert_eq!(manager.frame(Duration::ZERO)[0], ColorRgb::new(0, 0, 255));
+        // already active
+        assert!(!press(&mut manager, KeyName::F3, true));
+        assert!(!press(&mut manager, KeyName::F3, false));
+        assert!(!press(&mut manager, KeyName::Function, false));
+        assert!(!press(&mut manager, KeyName::F2, true));
+    }
+
+    #[test]
+    fn state_file() {
+        let path = std::env::temp_dir().join(format!("profile-state-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut manager = colors().with_state_file(&path);
+        manager.restore(Duration::ZERO).unwrap();
+        assert_eq!(manager.active_name(), Some("red"));
+        manager.activate("green", Duration::ZERO).unwrap();
+
+        let mut restarted = colors().with_state_file(&path);
+        restarted.restore(Duration::ZERO).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restarted.active_name(), Some("green"));
+        assert!(matches!(
+            restarted.activate("yellow", Duration::ZERO),
+            Err(ProfileError::UnknownProfile(_))
+        ));
+        assert_eq!(restarted.active_name(), Some("green"));
+    }
+}
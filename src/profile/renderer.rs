@@ -0,0 +1,192 @@
+//! Contains [`ProfileRenderer`]
+
+use std::fmt::{Debug, Formatter};
+use std::time::{Duration, Instant};
+
+use crate::{
+    color::{ColorBuffer, ColorRgb},
+    effect::Effect,
+    keyboard::Keyboard,
+    layout::{KeyPress, Layout},
+    ErrorRoccatVulcanApi,
+};
+
+/// An effect restricted to some keys
+struct RenderLayer {
+    /// Effect rendered
+    effect: Box<dyn Effect>,
+    /// Indices of the keys modified by the effect, or [`None`] for all the keys
+    lights: Option<Vec<usize>>,
+}
+
+/// Render the base colors and a stack of effects.
+///
+/// Usually created by [`super::Profile::renderer`].
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     Breathing, ColorBuffer, ColorRgb, LayoutFrCh, ProfileRenderer, Value,
+/// };
+///
+/// let mut renderer = ProfileRenderer::new(
+///     &LayoutFrCh,
+///     ColorBuffer::from_element(ColorRgb::new(255, 0, 0)),
+/// );
+/// renderer.push_effect(
+///     Breathing::new(Duration::from_secs(2), Value::new(0_f64).unwrap()).unwrap(),
+///     None,
+/// );
+/// assert_eq!(renderer.frame(Duration::ZERO)[0], ColorRgb::new(255, 0, 0));
+/// assert_eq!(
+///     renderer.frame(Duration::from_secs(1))[0],
+///     ColorRgb::new(0, 0, 0)
+/// );
+/// ```
+pub struct ProfileRenderer {
+    /// Layout given to the effects
+    layout: &'static dyn Layout,
+    /// Colors before the effects
+    base: ColorBuffer<ColorRgb>,
+    /// Effects in the order they are rendered
+    layers: Vec<RenderLayer>,
+}
+
+impl ProfileRenderer {
+    /// Create a renderer without effect
+    pub fn new(layout: &'static dyn Layout, base: ColorBuffer<ColorRgb>) -> Self {
+        Self {
+            layout,
+            base,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Add an effect on top of the others.
+    ///
+    /// If `lights` is given, the effect only changes the keys at these indices of the buffer.
+    pub fn push_effect(&mut self, effect: impl Effect + 'static, lights: Option<Vec<usize>>) {
+        self.layers.push(RenderLayer {
+            effect: Box::new(effect),
+            lights,
+        });
+    }
+
+    /// Get the layout
+    pub fn layout(&self) -> &'static dyn Layout {
+        self.layout
+    }
+
+    /// Get the colors before the effects
+    pub const fn base(&self) -> &ColorBuffer<ColorRgb> {
+        &self.base
+    }
+
+    /// Get the number of effects
+    pub const fn effect_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Compute the colors at `elapsed` since the start of the rendering
+    pub fn frame(&mut self, elapsed: Duration) -> ColorBuffer<ColorRgb> {
+        let mut buffer = self.base;
+        for layer in &mut self.layers {
+            match &layer.lights {
+                None => layer.effect.render(&mut buffer, self.layout, elapsed),
+                Some(lights) => {
+                    let mut rendered = buffer;
+                    layer.effect.render(&mut rendered, self.layout, elapsed);
+                    for index in lights {
+                        buffer[*index] = rendered[*index];
+                    }
+                }
+            }
+        }
+        buffer
+    }
+
+    /// Forward a key press to the effects
+    pub fn key_press(&mut self, key_press: KeyPress, elapsed: Duration) {
+        for layer in &mut self.layers {
+            layer.effect.key_press(key_press, self.layout, elapsed);
+        }
+    }
+
+    /// Render on the keyboard until `stop` returns true for a key press.
+    ///
+    /// The key presses are read during `frame_duration` between each frame.
+    /// # Errors
+    /// Returns the errors of [`Keyboard::read_key_press`] and [`Keyboard::render`].
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use roccat_vulcan_api_rs::{
+    ///     ColorRgb, ErrorRoccatVulcanApi, KeyGroup, KeyName, KeyboardApi, Layout, LayoutFrCh, Profile,
+    /// };
+    ///
+    /// # fn main() -> Result<(), ErrorRoccatVulcanApi> {
+    /// # #[cfg(not(feature = "no-keyboard-test"))]
+    /// # {
+    /// let keyboard = KeyboardApi::new()?;
+    /// let mut renderer = Profile::new("white")
+    ///     .with_base_color(vec![KeyGroup::All.into()], ColorRgb::new(255, 255, 255))
+    ///     .renderer()
+    ///     .unwrap();
+    /// let layout = LayoutFrCh::new();
+    /// renderer.run(&keyboard, Duration::from_millis(50), |key_press| {
+    ///     layout
+    ///         .find_from_key_code(*key_press.key_code())
+    ///         .map(|info| *info.key_name())
+    ///         == Some(KeyName::Escape)
+    /// })?;
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run<K: Keyboard + ?Sized>(
+        &mut self,
+        keyboard: &K,
+        frame_duration: Duration,
+        mut stop: impl FnMut(KeyPress) -> bool,
+    ) -> Result<(), ErrorRoccatVulcanApi> {
+        let start = Instant::now();
+        loop {
+            keyboard.render(&self.frame(start.elapsed()))?;
+            for key_press in keyboard.read_key_press(frame_duration)? {
+                if stop(key_press) {
+                    return Ok(());
+                }
+                self.key_press(key_press, start.elapsed());
+            }
+        }
+    }
+}
+
+impl Debug for ProfileRenderer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProfileRenderer")
+            .field("base", &self.base)
+            .field("effect_count", &self.layers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{effect::Wave, layout::LayoutFrCh};
+
+    #[test]
+    fn restricted_effect() {
+        let black = ColorRgb::new(0, 0, 0);
+        let mut renderer = ProfileRenderer::new(&LayoutFrCh, ColorBuffer::from_element(black));
+        renderer.push_effect(Wave::default(), Some(vec![0, 1]));
+        assert_eq!(renderer.effect_count(), 1);
+        let frame = renderer.frame(Duration::ZERO);
+        assert_ne!(frame[0], black);
+        assert_ne!(frame[1], black);
+        assert!(frame.iter().skip(2).all(|color| *color == black));
+    }
+}
@@ -0,0 +1,140 @@
+//! Contains [`KeyTarget`], the selection of keys in a profile
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::ProfileError;
+use crate::{
+    color::NUMBER_KEY_LED_BUFFER,
+    layout::{KeyGroup, KeyName, KeyParseError, Layout},
+};
+
+/// A key or a group of keys.
+///
+/// Written as the name of the key, like `"Escape"`, or as the name of the group
+/// prefixed by `@`, like `"@Letters"`.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{KeyGroup, KeyName, KeyTarget};
+///
+/// assert_eq!("Escape".parse(), Ok(KeyTarget::Key(KeyName::Escape)));
+/// assert_eq!("@Letters".parse(), Ok(KeyTarget::Group(KeyGroup::Letters)));
+/// assert_eq!(KeyTarget::Group(KeyGroup::Arrows).to_string(), "@Arrows");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum KeyTarget {
+    /// One key
+    Key(KeyName),
+    /// A group of keys
+    Group(KeyGroup),
+}
+
+impl KeyTarget {
+    /// Get the indices in the [`crate::ColorBuffer`] of the keys selected.
+    ///
+    /// The keys of a group that are not in the layout are ignored.
+    /// # Errors
+    /// Returns [`ProfileError::KeyWithoutLight`] if the key is not in the layout
+    /// or does not have a light.
+    pub fn light_indices(self, layout: &dyn Layout) -> Result<Vec<usize>, ProfileError> {
+        let index = |key_name| {
+            layout
+                .find_from_key_name(key_name)
+                .map(|info| usize::from(info.key_code_light().code()))
+                .filter(|index| *index < NUMBER_KEY_LED_BUFFER)
+        };
+        match self {
+            Self::Key(key_name) => index(key_name)
+                .map(|index| vec![index])
+                .ok_or(ProfileError::KeyWithoutLight(key_name)),
+            Self::Group(group) => Ok(group.keys().iter().filter_map(|key| index(*key)).collect()),
+        }
+    }
+}
+
+impl From<KeyName> for KeyTarget {
+    fn from(key_name: KeyName) -> Self {
+        Self::Key(key_name)
+    }
+}
+
+impl From<KeyGroup> for KeyTarget {
+    fn from(group: KeyGroup) -> Self {
+        Self::Group(group)
+    }
+}
+
+impl Display for KeyTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key(key_name) => write!(f, "{:?}", key_name),
+            Self::Group(group) => write!(f, "@{}", group),
+        }
+    }
+}
+
+impl FromStr for KeyTarget {
+    type Err = KeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().strip_prefix('@') {
+            Some(group) => group.parse().map(Self::Group),
+            None => s.parse().map(Self::Key),
+        }
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl Serialize for KeyTarget {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<'de> Deserialize<'de> for KeyTarget {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        string.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::LayoutFrCh;
+
+    #[test]
+    fn light_indices() {
+        let layout = LayoutFrCh::new();
+        assert_eq!(
+            KeyTarget::Key(KeyName::Escape)
+                .light_indices(&layout)
+                .unwrap(),
+            vec![0]
+        );
+        assert_eq!(
+            KeyTarget::Group(KeyGroup::Letters)
+                .light_indices(&layout)
+                .unwrap()
+                .len(),
+            26
+        );
+        assert!(KeyTarget::Group(KeyGroup::Wheel)
+            .light_indices(&layout)
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            KeyTarget::Key(KeyName::WheelUp).light_indices(&layout),
+            Err(ProfileError::KeyWithoutLight(KeyName::WheelUp))
+        ));
+        assert_eq!(
+            "@Nope".parse::<KeyTarget>(),
+            Err(KeyParseError::UnknownKeyGroup("Nope".to_owned()))
+        );
+    }
+}
@@ -1,4 +1,15 @@
-//! Conntains the constant reports
+//! Conntains the constant reports and the typed [`FeatureReport`]
+
+use crate::keyboard::ControlerFeatureKind;
+
+mod error;
+pub use error::*;
+
+mod feature;
+pub use feature::*;
+
+mod key_map;
+pub use key_map::*;
 
 /// First report for the control device
 const FEATURE_REPORT_1: [u8; 3] = [0x15, 0x00, 0x01];
@@ -125,5 +136,79 @@ pub(crate) const FEATURE_REPORT_CUSTOM: [&[u8]; 9] = [
     &FEATURE_REPORT_9,
 ];
 
+/// Get the reports sent to the control device to initialise the keyboard in the given mode,
+/// with the key map in place of the sixth report
+pub(crate) fn handshake(key_map: &KeyMap, kind: ControlerFeatureKind) -> Vec<Vec<u8>> {
+    let feature_reports = match kind {
+        ControlerFeatureKind::Rainbow => &FEATURE_REPORT_RAINBOW,
+        ControlerFeatureKind::Custom => &FEATURE_REPORT_CUSTOM,
+    };
+    let mut reports: Vec<Vec<u8>> = feature_reports
+        .iter()
+        .map(|report| report.to_vec())
+        .collect();
+    reports[5] = key_map.report().encode();
+    reports
+}
+
 /// header for the led write buffer
 pub(crate) const LED_FEATURE_REPORT_HEAD: [u8; 4] = [0xa1, 0x01, 0x01, 0xb4];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Format of each report of [`FEATURE_REPORT_RAINBOW`] and [`FEATURE_REPORT_CUSTOM`]
+    const FEATURE_REPORT_FORMATS: [ReportFormat; 9] = [
+        ReportFormat::Plain,
+        ReportFormat::Length,
+        ReportFormat::Checksummed,
+        ReportFormat::Checksummed,
+        ReportFormat::Checksummed,
+        ReportFormat::Checksummed,
+        ReportFormat::Checksummed,
+        ReportFormat::Checksummed,
+        ReportFormat::ShortLength,
+    ];
+
+    #[test]
+    fn regenerate_reports() {
+        for reports in [FEATURE_REPORT_RAINBOW, FEATURE_REPORT_CUSTOM] {
+            for (bytes, format) in reports.iter().zip(FEATURE_REPORT_FORMATS) {
+                let report = FeatureReport::decode(bytes, format).unwrap();
+                // rebuilt from the named fields only
+                let rebuilt =
+                    FeatureReport::new(report.id(), format, report.payload().to_vec()).unwrap();
+                assert_eq!(rebuilt.encode(), *bytes);
+                assert_eq!(rebuilt.len(), bytes.len());
+            }
+        }
+
+        let report = FeatureReport::decode(&FEATURE_REPORT_3, ReportFormat::Checksummed).unwrap();
+        assert_eq!(report.id(), 0x07);
+        assert_eq!(report.checksum(), Some(0x0ecd));
+        let report = FeatureReport::decode(&FEATURE_REPORT_8, ReportFormat::Checksummed).unwrap();
+        assert_eq!(report.len(), 443);
+        assert_eq!(report.checksum(), Some(0xcf21));
+        let report =
+            FeatureReport::decode(&FEATURE_REPORT_8_ALT, ReportFormat::Checksummed).unwrap();
+        assert_eq!(report.checksum(), Some(0xcf24));
+        let report = FeatureReport::decode(&FEATURE_REPORT_9, ReportFormat::ShortLength).unwrap();
+        assert_eq!(report.payload(), [0x01, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        let report = FeatureReport::decode(&FEATURE_REPORT_1, ReportFormat::Plain).unwrap();
+        assert_eq!(report.payload(), [0x00, 0x01]);
+    }
+
+    #[test]
+    fn handshake_reports() {
+        let key_map = KeyMap::new();
+        assert_eq!(
+            handshake(&key_map, ControlerFeatureKind::Rainbow),
+            FEATURE_REPORT_RAINBOW
+        );
+        assert_eq!(
+            handshake(&key_map, ControlerFeatureKind::Custom),
+            FEATURE_REPORT_CUSTOM
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Contains [`ReportError`]
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Error returned when decoding a [`super::FeatureReport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReportError {
+    /// The report is shorter than its header and trailer
+    TooShort {
+        /// Minimum number of bytes of the format
+        minimum: usize,
+        /// Number of bytes of the report
+        actual: usize,
+    },
+    /// The length written in the report is not its length
+    LengthMismatch {
+        /// Length written in the report
+        declared: usize,
+        /// Number of bytes of the report
+        actual: usize,
+    },
+    /// The checksum written in the report is not the sum of its bytes
+    ChecksumMismatch {
+        /// Checksum written in the report
+        declared: u16,
+        /// Checksum computed from the bytes
+        computed: u16,
+    },
+}
+
+impl Display for ReportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooShort { minimum, actual } => write!(
+                f,
+                "the report has {} bytes, the format needs at least {}",
+                actual, minimum
+            ),
+            Self::LengthMismatch { declared, actual } => write!(
+                f,
+                "the report declares {} bytes but has {}",
+                declared, actual
+            ),
+            Self::ChecksumMismatch { declared, computed } => write!(
+                f,
+                "the report declares the checksum {:#06x} but its bytes sum to {:#06x}",
+                declared, computed
+            ),
+        }
+    }
+}
+
+impl Error for ReportError {}
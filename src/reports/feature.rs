@@ -0,0 +1,314 @@
+//! Contains [`FeatureReport`], [`ReportFormat`] and [`report_checksum`]
+
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+use super::ReportError;
+
+/// Framing of the payload of a [`FeatureReport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ReportFormat {
+    /// The id followed by the payload
+    Plain,
+    /// The id, the length of the whole report on one byte, then the payload
+    ShortLength,
+    /// The id, the length of the whole report on two bytes in little endian, then the payload
+    Length,
+    /// Like [`Self::Length`], followed by the [`report_checksum`] of all the previous bytes
+    /// on two bytes in little endian
+    Checksummed,
+}
+
+impl ReportFormat {
+    /// Get the number of bytes before the payload
+    pub const fn header_len(self) -> usize {
+        match self {
+            Self::Plain => 1,
+            Self::ShortLength => 2,
+            Self::Length | Self::Checksummed => 3,
+        }
+    }
+
+    /// Get the number of bytes after the payload
+    pub const fn trailer_len(self) -> usize {
+        match self {
+            Self::Checksummed => 2,
+            Self::Plain | Self::ShortLength | Self::Length => 0,
+        }
+    }
+
+    /// Get the largest length of a report that the length field can hold
+    const fn max_len(self) -> usize {
+        match self {
+            Self::Plain => usize::MAX,
+            Self::ShortLength => u8::MAX as usize,
+            Self::Length | Self::Checksummed => u16::MAX as usize,
+        }
+    }
+}
+
+impl Display for ReportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Get the checksum of the control device: the sum of the bytes, wrapping on 16 bits.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::report_checksum;
+///
+/// assert_eq!(
+///     report_checksum(&[0x0a, 0x08, 0x00, 0xff, 0xf1, 0x00]),
+///     0x0202
+/// );
+/// ```
+pub fn report_checksum(bytes: &[u8]) -> u16 {
+    bytes
+        .iter()
+        .fold(0_u16, |sum, byte| sum.wrapping_add(u16::from(*byte)))
+}
+
+/// Feature report sent to the control device, with its id, its format and its payload.
+///
+/// The length and the checksum are computed by [`Self::encode`], so that the payload can be
+/// changed without recomputing them by hand.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{FeatureReport, ReportFormat};
+///
+/// let report =
+///     FeatureReport::new(0x0a, ReportFormat::Checksummed, vec![0xff, 0xf1, 0x00]).unwrap();
+/// assert_eq!(report.len(), 8);
+/// assert_eq!(report.checksum(), Some(0x0202));
+/// let bytes = report.encode();
+/// assert_eq!(bytes, [0x0a, 0x08, 0x00, 0xff, 0xf1, 0x00, 0x02, 0x02]);
+/// assert_eq!(
+///     FeatureReport::decode(&bytes, ReportFormat::Checksummed),
+///     Ok(report)
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FeatureReport {
+    /// Report id, the first byte
+    id: u8,
+    /// Framing of the payload
+    format: ReportFormat,
+    /// Bytes between the header and the trailer
+    payload: Vec<u8>,
+}
+
+impl FeatureReport {
+    /// Create a report, returns [`None`] if the report is too long for its length field
+    pub fn new(id: u8, format: ReportFormat, payload: impl Into<Vec<u8>>) -> Option<Self> {
+        let report = Self {
+            id,
+            format,
+            payload: payload.into(),
+        };
+        if report.len() <= format.max_len() {
+            Some(report)
+        } else {
+            None
+        }
+    }
+
+    /// Read a report of the format `format`, checking its length and its checksum
+    /// # Errors
+    /// - [`ReportError::TooShort`] if `bytes` is shorter than the header and the trailer
+    /// - [`ReportError::LengthMismatch`] if the length field is not the length of `bytes`
+    /// - [`ReportError::ChecksumMismatch`] if the checksum is not the one of the bytes
+    pub fn decode(bytes: &[u8], format: ReportFormat) -> Result<Self, ReportError> {
+        let header = format.header_len();
+        let minimum = header + format.trailer_len();
+        if bytes.len() < minimum {
+            return Err(ReportError::TooShort {
+                minimum,
+                actual: bytes.len(),
+            });
+        }
+        let declared = match format {
+            ReportFormat::Plain => None,
+            ReportFormat::ShortLength => Some(usize::from(bytes[1])),
+            ReportFormat::Length | ReportFormat::Checksummed => {
+                Some(usize::from(u16::from_le_bytes([bytes[1], bytes[2]])))
+            }
+        };
+        if let Some(declared) = declared {
+            if declared != bytes.len() {
+                return Err(ReportError::LengthMismatch {
+                    declared,
+                    actual: bytes.len(),
+                });
+            }
+        }
+        let end = bytes.len() - format.trailer_len();
+        if format == ReportFormat::Checksummed {
+            let declared = u16::from_le_bytes([bytes[end], bytes[end + 1]]);
+            let computed = report_checksum(&bytes[..end]);
+            if declared != computed {
+                return Err(ReportError::ChecksumMismatch { declared, computed });
+            }
+        }
+        Ok(Self {
+            id: bytes[0],
+            format,
+            payload: bytes[header..end].to_vec(),
+        })
+    }
+
+    /// Get the report id
+    pub const fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Get the format
+    pub const fn format(&self) -> ReportFormat {
+        self.format
+    }
+
+    /// Get the payload
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Get the payload mutably, its length cannot change so that the report stays valid
+    pub fn payload_mut(&mut self) -> &mut [u8] {
+        &mut self.payload
+    }
+
+    /// Get the length of the encoded report, written in the length field
+    #[allow(clippy::len_without_is_empty)]
+    pub const fn len(&self) -> usize {
+        self.format.header_len() + self.payload.len() + self.format.trailer_len()
+    }
+
+    /// Get the checksum of the report, [`None`] if the format has none
+    pub fn checksum(&self) -> Option<u16> {
+        match self.format {
+            ReportFormat::Checksummed => Some(report_checksum(&self.header_and_payload())),
+            ReportFormat::Plain | ReportFormat::ShortLength | ReportFormat::Length => None,
+        }
+    }
+
+    /// Get the bytes of the report without the trailer
+    #[allow(clippy::cast_possible_truncation)]
+    fn header_and_payload(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+        bytes.push(self.id);
+        // the length fits, checked by the constructor
+        match self.format {
+            ReportFormat::Plain => {}
+            ReportFormat::ShortLength => bytes.push(self.len() as u8),
+            ReportFormat::Length | ReportFormat::Checksummed => {
+                bytes.extend_from_slice(&(self.len() as u16).to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+
+    /// Get the bytes sent to the control device
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.header_and_payload();
+        if let Some(checksum) = self.checksum() {
+            bytes.extend_from_slice(&checksum.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl TryFrom<(&[u8], ReportFormat)> for FeatureReport {
+    type Error = ReportError;
+
+    /// See [`Self::decode`]
+    fn try_from((bytes, format): (&[u8], ReportFormat)) -> Result<Self, Self::Error> {
+        Self::decode(bytes, format)
+    }
+}
+
+impl Display for FeatureReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "report {:#04x} of {} bytes ({})",
+            self.id,
+            self.len(),
+            self.format
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode_and_decode() {
+        for format in [
+            ReportFormat::Plain,
+            ReportFormat::ShortLength,
+            ReportFormat::Length,
+            ReportFormat::Checksummed,
+        ] {
+            let report = FeatureReport::new(0x42, format, vec![1, 2, 3]).unwrap();
+            let bytes = report.encode();
+            assert_eq!(bytes.len(), report.len());
+            assert_eq!(bytes[0], 0x42);
+            assert_eq!(FeatureReport::decode(&bytes, format), Ok(report.clone()));
+            assert_eq!(
+                FeatureReport::try_from((bytes.as_slice(), format)),
+                Ok(report)
+            );
+        }
+
+        let mut report = FeatureReport::new(0x0d, ReportFormat::Checksummed, vec![0; 3]).unwrap();
+        assert_eq!(report.encode(), [0x0d, 0x08, 0x00, 0, 0, 0, 0x15, 0x00]);
+        report.payload_mut()[1] = 0xff;
+        assert_eq!(report.checksum(), Some(0x0114));
+        assert_eq!(report.encode(), [0x0d, 0x08, 0x00, 0, 0xff, 0, 0x14, 0x01]);
+        assert_eq!(report.to_string(), "report 0x0d of 8 bytes (Checksummed)");
+
+        assert!(FeatureReport::new(0, ReportFormat::ShortLength, vec![0; 253]).is_some());
+        assert!(FeatureReport::new(0, ReportFormat::ShortLength, vec![0; 254]).is_none());
+        assert!(FeatureReport::new(0, ReportFormat::Length, vec![0; 65_533]).is_none());
+        // the sum wraps
+        assert_eq!(report_checksum(&[0xff; 258]), 0x00fe);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            FeatureReport::decode(&[0x0a, 0x05, 0x00, 0x00], ReportFormat::Checksummed),
+            Err(ReportError::TooShort {
+                minimum: 5,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            FeatureReport::decode(&[0x05, 0x05, 0x00, 0x04], ReportFormat::Length),
+            Err(ReportError::LengthMismatch {
+                declared: 5,
+                actual: 4
+            })
+        );
+        let error = FeatureReport::decode(
+            &[0x0a, 0x08, 0x00, 0xff, 0xf1, 0x00, 0x03, 0x02],
+            ReportFormat::Checksummed,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            ReportError::ChecksumMismatch {
+                declared: 0x0203,
+                computed: 0x0202
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "the report declares the checksum 0x0203 but its bytes sum to 0x0202"
+        );
+        assert!(FeatureReport::decode(&[], ReportFormat::Plain).is_err());
+    }
+}
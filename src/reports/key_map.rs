@@ -0,0 +1,213 @@
+//! Contains [`KeyMap`]
+
+use std::fmt::{Display, Formatter};
+
+use super::{FeatureReport, ReportFormat, FEATURE_REPORT_6};
+use crate::layout::{KeyCode, KeyName, Layout};
+
+/// Id of the report holding the key map
+const KEY_MAP_REPORT_ID: u8 = 0x06;
+
+/// Number of keys in the key map
+const KEY_MAP_LEN: usize = 128;
+
+/// Event type of the key codes of most keys, see [`KeyCode::first_u8`]
+const EVENT_TYPE_KEY: u8 = 0xfb;
+
+/// The key codes of the event type [`EVENT_TYPE_KEY`] are ordered like the key map from this
+/// value
+const FIRST_KEY_CODE: u8 = 0x10;
+
+/// Key code of the caps lock, which has its own event type
+const CAPS_LOCK: KeyCode = KeyCode::new(0x0a, 0x39);
+
+/// Index of the caps lock in the key map
+const CAPS_LOCK_INDEX: usize = 5;
+
+/// Table of the USB HID usage sent by each key, stored by the firmware.
+///
+/// A remapped key behaves as the other key on every computer without any software running.
+/// The keys are found by their [`KeyName`] in a [`Layout`]. The map is sent with the other
+/// reports when the keyboard is initialised, see [`crate::KeyboardApi::set_key_map`].
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{KeyMap, KeyName, LayoutKind};
+///
+/// let layout = LayoutKind::FrCh.layout();
+/// let mut key_map = KeyMap::new();
+/// assert_eq!(key_map.usage(KeyName::Escape, layout), Some(0x29));
+/// assert!(key_map.swap(KeyName::CapsLock, KeyName::Escape, layout));
+/// assert_eq!(key_map.usage(KeyName::Escape, layout), Some(0x39));
+/// assert_eq!(key_map.usage(KeyName::CapsLock, layout), Some(0x29));
+///
+/// let report = key_map.report();
+/// assert_eq!(report.len(), 133);
+/// assert_eq!(KeyMap::from_report(&report), Some(key_map));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyMap {
+    /// USB HID usage of each key, ordered by key code
+    usages: [u8; KEY_MAP_LEN],
+}
+
+impl KeyMap {
+    /// Create the default key map of the keyboard
+    pub fn new() -> Self {
+        let report = FeatureReport::decode(&FEATURE_REPORT_6, ReportFormat::Checksummed)
+            .expect("the report of the library is valid");
+        Self::from_report(&report).expect("the report of the library is a key map")
+    }
+
+    /// Get the index in the map of a key, [`None`] if the key is not in the layout or cannot
+    /// be remapped
+    fn index(key: KeyName, layout: &dyn Layout) -> Option<usize> {
+        let key_code = *layout.find_from_key_name(key)?.key_code_press();
+        if key_code == CAPS_LOCK {
+            return Some(CAPS_LOCK_INDEX);
+        }
+        if key_code.first_u8() != EVENT_TYPE_KEY {
+            return None;
+        }
+        let index = usize::from(key_code.seconde_u8().checked_sub(FIRST_KEY_CODE)?);
+        if index < KEY_MAP_LEN {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Get the USB HID usage sent by a key, [`None`] if the key cannot be remapped
+    pub fn usage(&self, key: KeyName, layout: &dyn Layout) -> Option<u8> {
+        Self::index(key, layout).map(|index| self.usages[index])
+    }
+
+    /// Set the USB HID usage sent by a key, returns `false` if the key cannot be remapped
+    pub fn set_usage(&mut self, key: KeyName, usage: u8, layout: &dyn Layout) -> bool {
+        match Self::index(key, layout) {
+            Some(index) => {
+                self.usages[index] = usage;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Make `key` send what `target` sends in the default key map, returns `false` if one of
+    /// the keys cannot be remapped
+    pub fn remap(&mut self, key: KeyName, target: KeyName, layout: &dyn Layout) -> bool {
+        match Self::new().usage(target, layout) {
+            Some(usage) => self.set_usage(key, usage, layout),
+            None => false,
+        }
+    }
+
+    /// Exchange what two keys send, returns `false` if one of the keys cannot be remapped
+    pub fn swap(&mut self, first: KeyName, second: KeyName, layout: &dyn Layout) -> bool {
+        match (Self::index(first, layout), Self::index(second, layout)) {
+            (Some(first), Some(second)) => {
+                self.usages.swap(first, second);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the number of keys which do not send their default usage
+    pub fn remapped(&self) -> usize {
+        self.usages
+            .iter()
+            .zip(Self::new().usages.iter())
+            .filter(|(usage, default)| usage != default)
+            .count()
+    }
+
+    /// Get the report holding the key map
+    pub fn report(&self) -> FeatureReport {
+        FeatureReport::new(
+            KEY_MAP_REPORT_ID,
+            ReportFormat::Checksummed,
+            self.usages.to_vec(),
+        )
+        .expect("the report is shorter than the maximum length")
+    }
+
+    /// Read a key map from its report, returns [`None`] if the report is not a key map
+    pub fn from_report(report: &FeatureReport) -> Option<Self> {
+        if report.id() != KEY_MAP_REPORT_ID || report.format() != ReportFormat::Checksummed {
+            return None;
+        }
+        let mut usages = [0_u8; KEY_MAP_LEN];
+        if report.payload().len() != usages.len() {
+            return None;
+        }
+        usages.copy_from_slice(report.payload());
+        Some(Self { usages })
+    }
+}
+
+impl Default for KeyMap {
+    /// Returns [`KeyMap::new`]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for KeyMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "key map with {} remapped key(s)", self.remapped())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LayoutKind;
+
+    #[test]
+    fn default_map() {
+        let key_map = KeyMap::new();
+        assert_eq!(key_map.report().encode(), FEATURE_REPORT_6);
+        assert_eq!(key_map.remapped(), 0);
+
+        let layout = LayoutKind::FrCh.layout();
+        for (key, usage) in [
+            (KeyName::F1, 0x3a),
+            (KeyName::Escape, 0x29),
+            (KeyName::CapsLock, 0x39),
+            (KeyName::Q, 0x14),
+            (KeyName::A, 0x04),
+            (KeyName::Enter, 0x28),
+            (KeyName::LeftControl, 0xe0),
+            (KeyName::ArrowUp, 0x52),
+            (KeyName::NumEnter, 0x58),
+        ] {
+            assert_eq!(key_map.usage(key, layout), Some(usage), "{}", key);
+        }
+        assert_eq!(key_map.usage(KeyName::WheelUp, layout), None);
+    }
+
+    #[test]
+    fn remap() {
+        let layout = LayoutKind::FrCh.layout();
+        let mut key_map = KeyMap::new();
+        assert!(key_map.remap(KeyName::CapsLock, KeyName::Escape, layout));
+        assert_eq!(key_map.usage(KeyName::CapsLock, layout), Some(0x29));
+        assert_eq!(key_map.remapped(), 1);
+        assert!(key_map.remap(KeyName::Escape, KeyName::CapsLock, layout));
+        let mut swapped = KeyMap::new();
+        assert!(swapped.swap(KeyName::Escape, KeyName::CapsLock, layout));
+        assert_eq!(key_map, swapped);
+        assert_eq!(key_map.to_string(), "key map with 2 remapped key(s)");
+
+        assert!(!key_map.remap(KeyName::WheelDown, KeyName::A, layout));
+        assert!(!key_map.swap(KeyName::A, KeyName::WheelDown, layout));
+        assert!(key_map.set_usage(KeyName::A, 0x00, layout));
+        assert_eq!(key_map.usage(KeyName::A, layout), Some(0x00));
+
+        let bytes = key_map.report().encode();
+        let report = FeatureReport::decode(&bytes, ReportFormat::Checksummed).unwrap();
+        assert_eq!(KeyMap::from_report(&report), Some(key_map));
+        let not_key_map = FeatureReport::new(0x06, ReportFormat::Checksummed, vec![0; 3]).unwrap();
+        assert_eq!(KeyMap::from_report(&not_key_map), None);
+    }
+}
@@ -0,0 +1,78 @@
+//! Contains the [`Transport`] trait, the link between [`crate::KeyboardApi`] and the device
+
+use std::time::Duration;
+
+use crate::ErrorRoccatVulcanApi;
+
+mod hid;
+pub use hid::*;
+
+mod info;
+pub use info::*;
+
+mod mock;
+pub use mock::*;
+
+/// Access to the three interfaces of the keyboard.
+///
+/// [`HidTransport`] talks to a real keyboard and [`MockTransport`] records what is sent,
+/// to test code using [`crate::KeyboardApi`] without a keyboard.
+pub trait Transport {
+    /// Write a packet on the led device
+    /// # Errors
+    /// [`ErrorRoccatVulcanApi::LedDeviceError`] if the led device encountered an error
+    fn write_led(&self, packet: &[u8]) -> Result<(), ErrorRoccatVulcanApi>;
+
+    /// Send a feature report to the control device
+    /// # Errors
+    /// [`ErrorRoccatVulcanApi::ControlDeviceError`] if the control device encountered an error
+    fn send_feature_report(&self, report: &[u8]) -> Result<(), ErrorRoccatVulcanApi>;
+
+    /// Get a feature report from the control device, the first byte of `buffer` is the report id.
+    /// Returns the number of bytes read.
+    /// # Errors
+    /// [`ErrorRoccatVulcanApi::ControlDeviceError`] if the control device encountered an error
+    fn get_feature_report(&self, buffer: &mut [u8]) -> Result<usize, ErrorRoccatVulcanApi>;
+
+    /// Read a report of the read device, waiting at most `timeout` or until a report
+    /// arrives if it is [`None`]. Returns the number of bytes read, zero if the timeout was reached.
+    /// # Errors
+    /// - [`ErrorRoccatVulcanApi::ReadDeviceError`] if the read device encountered an error
+    /// - [`ErrorRoccatVulcanApi::InvalidInput`] if the timeout is too long
+    fn read(
+        &self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<usize, ErrorRoccatVulcanApi>;
+
+    /// Get the information on the device, by default only empty information
+    fn device_info(&self) -> DeviceInfo {
+        DeviceInfo::default()
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    fn write_led(&self, packet: &[u8]) -> Result<(), ErrorRoccatVulcanApi> {
+        (**self).write_led(packet)
+    }
+
+    fn send_feature_report(&self, report: &[u8]) -> Result<(), ErrorRoccatVulcanApi> {
+        (**self).send_feature_report(report)
+    }
+
+    fn get_feature_report(&self, buffer: &mut [u8]) -> Result<usize, ErrorRoccatVulcanApi> {
+        (**self).get_feature_report(buffer)
+    }
+
+    fn read(
+        &self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<usize, ErrorRoccatVulcanApi> {
+        (**self).read(buffer, timeout)
+    }
+
+    fn device_info(&self) -> DeviceInfo {
+        (**self).device_info()
+    }
+}
@@ -0,0 +1,151 @@
+//! Contains [`HidTransport`]
+
+use std::time::Duration;
+
+use hidapi::{HidApi, HidDevice};
+
+use super::{DeviceInfo, FirmwareVersion, Transport, INFO_REPORT_ID};
+use crate::{ErrorRoccatVulcanApi, KeyboardIntrefacesFilter};
+
+/// [`Transport`] to a keyboard through hidapi
+pub struct HidTransport {
+    /// Read device that look for key press
+    read: HidDevice,
+    /// Control device where feature are send to initialize the keyboard
+    control: HidDevice,
+    /// Led device which send color for the keyboard
+    led: HidDevice,
+    /// Information read when opening the devices, without the firmware version
+    info: DeviceInfo,
+}
+
+impl HidTransport {
+    /// Open the devices of the keyboard described by `interface`.
+    /// # Errors
+    /// - [`ErrorRoccatVulcanApi::KeyboardNotFound`] Keyboard not found,
+    /// - [`ErrorRoccatVulcanApi::NoLedDevice`] Led device not found,
+    /// - [`ErrorRoccatVulcanApi::LedDeviceError`] Led device error,
+    /// - [`ErrorRoccatVulcanApi::NoControlDevice`] Control device not found,
+    /// - [`ErrorRoccatVulcanApi::NoReadDevice`] Read device not found,
+    /// - [`ErrorRoccatVulcanApi::ReadDeviceError`] Read device error,
+    pub fn open(
+        api: &HidApi,
+        interface: &KeyboardIntrefacesFilter,
+    ) -> Result<Self, ErrorRoccatVulcanApi> {
+        if !api
+            .device_list()
+            .any(|device| device.product_id() == interface.control_interface().product_id())
+        {
+            return Err(ErrorRoccatVulcanApi::KeyboardNotFound {
+                product_ids: vec![interface.control_interface().product_id()],
+            });
+        }
+        let read_info = api
+            .device_list()
+            .find(|device| interface.read_interface().match_filter(device))
+            .ok_or(ErrorRoccatVulcanApi::NoReadDevice(
+                *interface.read_interface(),
+            ))?;
+        let led_info = api
+            .device_list()
+            .find(|device| interface.led_interface().match_filter(device))
+            .ok_or(ErrorRoccatVulcanApi::NoLedDevice(
+                *interface.led_interface(),
+            ))?;
+        let control_info_list = api
+            .device_list()
+            .filter(|device| interface.control_interface().match_filter(device));
+
+        let (control_info, control) = control_info_list
+            .filter_map(|device| Some((device, device.open_device(api).ok()?)))
+            .find(|(_, device)| Self::is_correct_control_device(device))
+            .ok_or(ErrorRoccatVulcanApi::NoControlDevice(
+                *interface.control_interface(),
+            ))?;
+        let read = read_info
+            .open_device(api)
+            .map_err(ErrorRoccatVulcanApi::ReadDeviceError)?;
+        let led = led_info
+            .open_device(api)
+            .map_err(ErrorRoccatVulcanApi::led_device)?;
+        read.set_blocking_mode(true)
+            .map_err(ErrorRoccatVulcanApi::ReadDeviceError)?;
+        let path = |device: &hidapi::DeviceInfo| Some(device.path().to_string_lossy().into_owned());
+        let info = DeviceInfo::new(control_info.vendor_id(), control_info.product_id())
+            .with_release_number(control_info.release_number())
+            .with_manufacturer(control_info.manufacturer_string().map(str::to_owned))
+            .with_product(control_info.product_string().map(str::to_owned))
+            .with_serial_number(control_info.serial_number().map(str::to_owned))
+            .with_read_path(path(read_info))
+            .with_control_path(path(control_info))
+            .with_led_path(path(led_info));
+        Ok(Self {
+            read,
+            control,
+            led,
+            info,
+        })
+    }
+
+    /// Verify if the given device is the correct control device.
+    /// Note that this change the state of the HidDevice.
+    /// If you want to close it you will have to resend a similar feature report.
+    fn is_correct_control_device(device: &HidDevice) -> bool {
+        let mut buffer: [u8; 255] = [0x00; 255];
+        buffer[0] = INFO_REPORT_ID;
+        let a = device.get_feature_report(&mut buffer);
+        match a {
+            Ok(val) => val > 0,
+            Err(_) => false,
+        }
+    }
+}
+
+impl Transport for HidTransport {
+    fn write_led(&self, packet: &[u8]) -> Result<(), ErrorRoccatVulcanApi> {
+        self.led
+            .write(packet)
+            .map(|_| ())
+            .map_err(ErrorRoccatVulcanApi::led_device)
+    }
+
+    fn send_feature_report(&self, report: &[u8]) -> Result<(), ErrorRoccatVulcanApi> {
+        self.control
+            .send_feature_report(report)
+            .map_err(ErrorRoccatVulcanApi::control_device)
+    }
+
+    fn get_feature_report(&self, buffer: &mut [u8]) -> Result<usize, ErrorRoccatVulcanApi> {
+        self.control
+            .get_feature_report(buffer)
+            .map_err(ErrorRoccatVulcanApi::control_device)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn read(
+        &self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<usize, ErrorRoccatVulcanApi> {
+        match timeout {
+            Some(timeout) => {
+                if timeout.as_millis() > i32::MAX as u128 {
+                    return Err(ErrorRoccatVulcanApi::InvalidInput);
+                }
+                self.read.read_timeout(buffer, timeout.as_millis() as i32)
+            }
+            None => self.read.read(buffer),
+        }
+        .map_err(ErrorRoccatVulcanApi::ReadDeviceError)
+    }
+
+    fn device_info(&self) -> DeviceInfo {
+        let mut buffer: [u8; 255] = [0x00; 255];
+        buffer[0] = INFO_REPORT_ID;
+        let firmware_version = match self.control.get_feature_report(&mut buffer) {
+            Ok(size) => FirmwareVersion::from_report(&buffer[..size.min(buffer.len())]),
+            Err(_) => None,
+        };
+        self.info.clone().with_firmware_version(firmware_version)
+    }
+}
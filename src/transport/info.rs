@@ -0,0 +1,302 @@
+//! Contains [`DeviceInfo`] and [`FirmwareVersion`]
+
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::interface::{VULCAN_100_PRODUCT_ID, VULCAN_120_PRODUCT_ID};
+
+/// Id of the report of the control device holding the firmware version
+pub(crate) const INFO_REPORT_ID: u8 = 0x0f;
+
+/// Version of the firmware read from the report `0x0f` of the control device.
+///
+/// The report is read like the one of the Ryos keyboards in roccat-tools, the id, the length,
+/// the firmware version then the DFU version. The layout is not confirmed on the Vulcan.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::FirmwareVersion;
+///
+/// let version = FirmwareVersion::from_report(&[0x0f, 0x08, 0x6c, 0x02, 0x00]).unwrap();
+/// assert_eq!(version.firmware(), 108);
+/// assert_eq!(version.to_string(), "1.08 (DFU 0.02)");
+/// assert!(FirmwareVersion::from_report(&[0x0f, 0x00]).is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct FirmwareVersion {
+    /// Version of the firmware, displayed divided by 100
+    firmware: u8,
+    /// Version of the device firmware upgrade, displayed divided by 100
+    dfu: u8,
+}
+
+impl FirmwareVersion {
+    /// Create a version
+    pub const fn new(firmware: u8, dfu: u8) -> Self {
+        Self { firmware, dfu }
+    }
+
+    /// Read the version from the report `0x0f`, returns [`None`] if the report is not the one
+    /// of the version or is too short
+    pub fn from_report(report: &[u8]) -> Option<Self> {
+        match report {
+            [INFO_REPORT_ID, _, firmware, dfu, ..] => Some(Self::new(*firmware, *dfu)),
+            _ => None,
+        }
+    }
+
+    /// Get the version of the firmware
+    pub const fn firmware(self) -> u8 {
+        self.firmware
+    }
+
+    /// Get the version of the device firmware upgrade
+    pub const fn dfu(self) -> u8 {
+        self.dfu
+    }
+}
+
+impl Display for FirmwareVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{:02} (DFU {}.{:02})",
+            self.firmware / 100,
+            self.firmware % 100,
+            self.dfu / 100,
+            self.dfu % 100
+        )
+    }
+}
+
+/// Information on the keyboard, see [`crate::KeyboardApi::device_info`].
+///
+/// The strings are read by hidapi from the USB descriptors and are [`None`] if the device
+/// does not provide them.
+/// # Example
+/// ```
+/// use roccat_vulcan_api_rs::{DeviceInfo, FirmwareVersion};
+///
+/// let info = DeviceInfo::new(0x1e7d, 12_440)
+///     .with_product(Some("ROCCAT Vulcan AIMO".to_owned()))
+///     .with_serial_number(Some("0123".to_owned()))
+///     .with_firmware_version(Some(FirmwareVersion::new(108, 2)));
+/// assert_eq!(info.model(), Some("Vulcan 120"));
+/// assert_eq!(
+///     info.to_string(),
+///     "ROCCAT Vulcan AIMO (Vulcan 120, 1e7d:3098), firmware (unverified) 1.08 (DFU 0.02), serial 0123"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct DeviceInfo {
+    /// USB vendor id
+    vendor_id: u16,
+    /// USB product id
+    product_id: u16,
+    /// Release number of the device in binary coded decimal
+    release_number: u16,
+    /// Version read from the control device
+    firmware_version: Option<FirmwareVersion>,
+    /// Manufacturer string
+    manufacturer: Option<String>,
+    /// Product string
+    product: Option<String>,
+    /// Serial number string
+    serial_number: Option<String>,
+    /// Path of the read interface
+    read_path: Option<String>,
+    /// Path of the control interface
+    control_path: Option<String>,
+    /// Path of the led interface
+    led_path: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Create the information of a device with nothing but its ids
+    pub const fn new(vendor_id: u16, product_id: u16) -> Self {
+        Self {
+            vendor_id,
+            product_id,
+            release_number: 0,
+            firmware_version: None,
+            manufacturer: None,
+            product: None,
+            serial_number: None,
+            read_path: None,
+            control_path: None,
+            led_path: None,
+        }
+    }
+
+    /// Set the release number
+    pub const fn with_release_number(mut self, release_number: u16) -> Self {
+        self.release_number = release_number;
+        self
+    }
+
+    /// Set the firmware version
+    pub const fn with_firmware_version(
+        mut self,
+        firmware_version: Option<FirmwareVersion>,
+    ) -> Self {
+        self.firmware_version = firmware_version;
+        self
+    }
+
+    /// Set the manufacturer string
+    pub fn with_manufacturer(mut self, manufacturer: Option<String>) -> Self {
+        self.manufacturer = manufacturer;
+        self
+    }
+
+    /// Set the product string
+    pub fn with_product(mut self, product: Option<String>) -> Self {
+        self.product = product;
+        self
+    }
+
+    /// Set the serial number
+    pub fn with_serial_number(mut self, serial_number: Option<String>) -> Self {
+        self.serial_number = serial_number;
+        self
+    }
+
+    /// Set the path of the read interface
+    pub fn with_read_path(mut self, read_path: Option<String>) -> Self {
+        self.read_path = read_path;
+        self
+    }
+
+    /// Set the path of the control interface
+    pub fn with_control_path(mut self, control_path: Option<String>) -> Self {
+        self.control_path = control_path;
+        self
+    }
+
+    /// Set the path of the led interface
+    pub fn with_led_path(mut self, led_path: Option<String>) -> Self {
+        self.led_path = led_path;
+        self
+    }
+
+    /// Get the USB vendor id
+    pub const fn vendor_id(&self) -> u16 {
+        self.vendor_id
+    }
+
+    /// Get the USB product id
+    pub const fn product_id(&self) -> u16 {
+        self.product_id
+    }
+
+    /// Get the model from the product id, [`None`] if the model is unknown
+    pub const fn model(&self) -> Option<&'static str> {
+        match self.product_id {
+            VULCAN_100_PRODUCT_ID => Some("Vulcan 100"),
+            VULCAN_120_PRODUCT_ID => Some("Vulcan 120"),
+            _ => None,
+        }
+    }
+
+    /// Get the release number in binary coded decimal
+    pub const fn release_number(&self) -> u16 {
+        self.release_number
+    }
+
+    /// Get the firmware version
+    pub const fn firmware_version(&self) -> Option<FirmwareVersion> {
+        self.firmware_version
+    }
+
+    /// Get the manufacturer string
+    pub fn manufacturer(&self) -> Option<&str> {
+        self.manufacturer.as_deref()
+    }
+
+    /// Get the product string
+    pub fn product(&self) -> Option<&str> {
+        self.product.as_deref()
+    }
+
+    /// Get the serial number
+    pub fn serial_number(&self) -> Option<&str> {
+        self.serial_number.as_deref()
+    }
+
+    /// Get the path of the read interface
+    pub fn read_path(&self) -> Option<&str> {
+        self.read_path.as_deref()
+    }
+
+    /// Get the path of the control interface
+    pub fn control_path(&self) -> Option<&str> {
+        self.control_path.as_deref()
+    }
+
+    /// Get the path of the led interface
+    pub fn led_path(&self) -> Option<&str> {
+        self.led_path.as_deref()
+    }
+}
+
+impl Display for DeviceInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.manufacturer(), self.product()) {
+            (Some(manufacturer), Some(product)) if !product.starts_with(manufacturer) => {
+                write!(f, "{} {} ", manufacturer, product)?;
+            }
+            (_, Some(product)) => write!(f, "{} ", product)?,
+            (Some(manufacturer), None) => write!(f, "{} ", manufacturer)?,
+            (None, None) => write!(f, "keyboard ")?,
+        }
+        write!(f, "(")?;
+        if let Some(model) = self.model() {
+            write!(f, "{}, ", model)?;
+        }
+        write!(f, "{:04x}:{:04x})", self.vendor_id, self.product_id)?;
+        if let Some(version) = self.firmware_version {
+            // the layout of the report is not confirmed on the Vulcan
+            write!(f, ", firmware (unverified) {}", version)?;
+        }
+        if let Some(serial_number) = self.serial_number() {
+            write!(f, ", serial {}", serial_number)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let info = DeviceInfo::new(0x1e7d, 12_410)
+            .with_manufacturer(Some("ROCCAT".to_owned()))
+            .with_product(Some("Vulcan AIMO".to_owned()))
+            .with_release_number(0x0100)
+            .with_read_path(Some("/dev/hidraw1".to_owned()))
+            .with_control_path(Some("/dev/hidraw2".to_owned()))
+            .with_led_path(Some("/dev/hidraw3".to_owned()));
+        assert_eq!(info.model(), Some("Vulcan 100"));
+        assert_eq!(info.read_path(), Some("/dev/hidraw1"));
+        assert_eq!(info.control_path(), Some("/dev/hidraw2"));
+        assert_eq!(info.led_path(), Some("/dev/hidraw3"));
+        assert_eq!(
+            info.to_string(),
+            "ROCCAT Vulcan AIMO (Vulcan 100, 1e7d:307a)"
+        );
+        assert_eq!(DeviceInfo::default().to_string(), "keyboard (0000:0000)");
+        assert_eq!(
+            FirmwareVersion::from_report(&[0x0f, 0x08, 0x6c, 0x02]),
+            Some(FirmwareVersion::new(108, 2))
+        );
+        assert_eq!(
+            FirmwareVersion::from_report(&[0x0e, 0x08, 0x6c, 0x02]),
+            None
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Contains [`MockTransport`]
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use super::{DeviceInfo, Transport};
+use crate::{
+    color::{ColorBuffer, ColorRgb, BUFFER_SIZE_PACKETED},
+    keyboard::ControlerFeatureKind,
+    layout::KeyPress,
+    reports::{self, FeatureReport, KeyMap, ReportFormat},
+    ErrorRoccatVulcanApi,
+};
+
+/// Event type byte of the caps lock key, its pressed byte is inverted
+const EVENT_TYPE_CAPS_LOCK: u8 = 0x0a;
+
+/// What was sent to and what will be read from a [`MockTransport`]
+#[derive(Debug, Default)]
+struct MockState {
+    /// Data written on the led device
+    led: Vec<u8>,
+    /// Feature reports sent to the control device
+    feature_reports: Vec<Vec<u8>>,
+    /// Reports waiting to be read
+    input: VecDeque<[u8; 5]>,
+    /// Information returned by [`Transport::device_info`]
+    device_info: DeviceInfo,
+}
+
+/// [`Transport`] without keyboard, for tests.
+///
+/// It records the frames rendered and the feature reports sent, and returns the reports
+/// given with [`Self::push_report`] when reading. The clones share the same state,
+/// so a clone can be kept to inspect the transport given to [`crate::KeyboardApi`].
+/// When there is nothing to read it waits for the timeout, a read without timeout
+/// returns immediately with no data.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use roccat_vulcan_api_rs::{
+///     ColorBuffer, ColorRgb, ControlerFeatureKind, KeyCode, KeyPress, KeyboardApi, MockTransport,
+/// };
+///
+/// let transport = MockTransport::new();
+/// let keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+/// assert_eq!(transport.mode(), Some(ControlerFeatureKind::Custom));
+///
+/// let buffer = ColorBuffer::from_element(ColorRgb::new(255, 0, 0));
+/// keyboard.render(&buffer).unwrap();
+/// assert_eq!(transport.last_frame(), Some(buffer));
+///
+/// let key_press = KeyPress::new(KeyCode::new(251, 17), true);
+/// transport.push_key_press(key_press);
+/// assert_eq!(
+///     keyboard.read_key_press(Duration::from_millis(1)).unwrap(),
+///     vec![key_press]
+/// );
+///
+/// drop(keyboard);
+/// assert_eq!(transport.mode(), Some(ControlerFeatureKind::Rainbow));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    /// State shared between the clones
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    /// Create a transport with nothing to read
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock the state, ignoring a panic of another thread
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Set the information returned by [`Transport::device_info`]
+    pub fn set_device_info(&self, device_info: DeviceInfo) {
+        self.state().device_info = device_info;
+    }
+
+    /// Add a raw report of the read device
+    pub fn push_report(&self, report: [u8; 5]) {
+        self.state().input.push_back(report);
+    }
+
+    /// Add the report of the read device corresponding to a key press
+    pub fn push_key_press(&self, key_press: KeyPress) {
+        let key_code = key_press.key_code();
+        let pressed = if key_code.first_u8() == EVENT_TYPE_CAPS_LOCK {
+            !key_press.is_pressed()
+        } else {
+            key_press.is_pressed()
+        };
+        self.push_report([
+            0x03,
+            0x00,
+            key_code.first_u8(),
+            key_code.seconde_u8(),
+            u8::from(pressed),
+        ]);
+    }
+
+    /// Get all the data written on the led device
+    pub fn led_data(&self) -> Vec<u8> {
+        self.state().led.clone()
+    }
+
+    /// Get the frames rendered, the data that cannot be decoded is skipped
+    pub fn frames(&self) -> Vec<ColorBuffer<ColorRgb>> {
+        self.state()
+            .led
+            .chunks_exact(BUFFER_SIZE_PACKETED)
+            .filter_map(ColorBuffer::from_led_buffer)
+            .collect()
+    }
+
+    /// Get the last frame rendered
+    pub fn last_frame(&self) -> Option<ColorBuffer<ColorRgb>> {
+        self.frames().pop()
+    }
+
+    /// Get the feature reports sent to the control device
+    pub fn feature_reports(&self) -> Vec<Vec<u8>> {
+        self.state().feature_reports.clone()
+    }
+
+    /// Get the last mode set on the control device
+    pub fn mode(&self) -> Option<ControlerFeatureKind> {
+        self.state()
+            .feature_reports
+            .iter()
+            .rev()
+            .find_map(|report| {
+                if report.as_slice() == reports::FEATURE_REPORT_RAINBOW[7] {
+                    Some(ControlerFeatureKind::Rainbow)
+                } else if report.as_slice() == reports::FEATURE_REPORT_CUSTOM[7] {
+                    Some(ControlerFeatureKind::Custom)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Get the last key map sent to the control device, see [`crate::KeyboardApi::set_key_map`]
+    pub fn key_map(&self) -> Option<KeyMap> {
+        self.state()
+            .feature_reports
+            .iter()
+            .rev()
+            .find_map(|report| {
+                FeatureReport::decode(report, ReportFormat::Checksummed)
+                    .ok()
+                    .and_then(|report| KeyMap::from_report(&report))
+            })
+    }
+
+    /// Forget what was written and sent
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.led.clear();
+        state.feature_reports.clear();
+    }
+}
+
+impl Transport for MockTransport {
+    fn write_led(&self, packet: &[u8]) -> Result<(), ErrorRoccatVulcanApi> {
+        self.state().led.extend_from_slice(packet);
+        Ok(())
+    }
+
+    fn send_feature_report(&self, report: &[u8]) -> Result<(), ErrorRoccatVulcanApi> {
+        self.state().feature_reports.push(report.to_vec());
+        Ok(())
+    }
+
+    fn get_feature_report(&self, buffer: &mut [u8]) -> Result<usize, ErrorRoccatVulcanApi> {
+        Ok(buffer.len())
+    }
+
+    fn read(
+        &self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<usize, ErrorRoccatVulcanApi> {
+        // the lock must be released before sleeping
+        let report = self.state().input.pop_front();
+        match report {
+            Some(report) => {
+                let size = report.len().min(buffer.len());
+                buffer[..size].copy_from_slice(&report[..size]);
+                Ok(size)
+            }
+            None => {
+                if let Some(timeout) = timeout {
+                    thread::sleep(timeout);
+                }
+                Ok(0)
+            }
+        }
+    }
+
+    fn device_info(&self) -> DeviceInfo {
+        self.state().device_info.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        keyboard::KeyboardApi,
+        layout::{KeyCode, KeyName},
+        FirmwareVersion, KeyboardEvent, LayoutKind,
+    };
+
+    #[test]
+    fn mock() {
+        let transport = MockTransport::new();
+        let keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+        assert_eq!(
+            transport.feature_reports().len(),
+            reports::FEATURE_REPORT_CUSTOM.len()
+        );
+        keyboard.set_mode(ControlerFeatureKind::Rainbow).unwrap();
+        assert_eq!(transport.mode(), Some(ControlerFeatureKind::Rainbow));
+        transport.clear();
+        assert_eq!(transport.mode(), None);
+
+        let red = ColorBuffer::from_element(ColorRgb::new(255, 0, 0));
+        let blue = ColorBuffer::from_element(ColorRgb::new(0, 0, 255));
+        keyboard.render(&red).unwrap();
+        keyboard.render(&blue).unwrap();
+        assert_eq!(transport.frames(), vec![red, blue]);
+
+        let caps_lock = KeyPress::new(KeyCode::new(EVENT_TYPE_CAPS_LOCK, 0x39), true);
+        transport.push_key_press(caps_lock);
+        transport.push_report([0x03, 0x00, 0xcc, 0x01, 0x00]);
+        let events = keyboard.read_events(Duration::from_millis(1)).unwrap();
+        assert_eq!(events[0], KeyboardEvent::from(caps_lock));
+        assert_eq!(events.len(), 2);
+        assert!(keyboard
+            .read_key_press(Duration::from_millis(1))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn key_map() {
+        let transport = MockTransport::new();
+        let mut keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+        assert_eq!(transport.key_map(), Some(KeyMap::new()));
+
+        let mut key_map = KeyMap::new();
+        assert!(key_map.swap(
+            KeyName::CapsLock,
+            KeyName::Escape,
+            LayoutKind::FrCh.layout()
+        ));
+        keyboard.set_key_map(key_map.clone()).unwrap();
+        assert_eq!(transport.key_map(), Some(key_map.clone()));
+        assert_eq!(transport.mode(), Some(ControlerFeatureKind::Custom));
+        assert_eq!(keyboard.key_map(), &key_map);
+
+        // the key map is kept when switching modes and after the API is dropped
+        transport.clear();
+        keyboard.set_mode(ControlerFeatureKind::Rainbow).unwrap();
+        assert_eq!(transport.key_map(), Some(key_map.clone()));
+        drop(keyboard);
+        assert_eq!(transport.key_map(), Some(key_map));
+
+        // as in the captured initialisation, a new API sends the default key map
+        let keyboard = KeyboardApi::with_transport(transport.clone()).unwrap();
+        assert_eq!(keyboard.key_map(), &KeyMap::new());
+        assert_eq!(transport.key_map(), Some(KeyMap::new()));
+    }
+
+    #[test]
+    fn device_info() {
+        let transport = MockTransport::new();
+        let info = DeviceInfo::new(0x1e7d, 12_440)
+            .with_serial_number(Some("0123".to_owned()))
+            .with_firmware_version(Some(FirmwareVersion::new(108, 2)));
+        transport.set_device_info(info.clone());
+        let keyboard = KeyboardApi::with_transport(transport).unwrap();
+        assert_eq!(keyboard.device_info(), &info);
+        assert_eq!(
+            keyboard.to_string(),
+            "keyboard (Vulcan 120, 1e7d:3098), firmware (unverified) 1.08 (DFU 0.02), serial 0123"
+        );
+        let debug = format!("{:?}", keyboard);
+        assert!(debug.starts_with("KeyboardApi { device_info: DeviceInfo {"));
+    }
+}